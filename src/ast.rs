@@ -1,6 +1,7 @@
 use super::lexer::{TkType, Token};
 use crate::lexer::Location;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Tag {
@@ -28,12 +29,66 @@ impl Tag {
 /// ```elz
 /// module io.utils
 /// ```
+///
+/// definitions are private to their module by default, only `pub` definitions can be imported by
+/// other modules, and only `pub` fields and methods can be used out of the module of class
+///
+/// ```elz
+/// pub class Counter {
+///   pub count: int;
+///   step: int = 1;
+/// }
+/// ```
 #[derive(Clone, Debug, PartialEq)]
 pub struct Module {
+    /// location of the module header
+    pub location: Location,
     pub name: String,
     pub top_list: Vec<TopAst>,
 }
 
+impl Module {
+    /// definitions returns names defined in the module, including private ones
+    pub fn definitions(&self) -> Vec<&String> {
+        self.top_list
+            .iter()
+            .filter_map(|top| match top {
+                TopAst::Function(f) => Some(&f.name),
+                TopAst::Variable(v) => Some(&v.name),
+                TopAst::Class(c) => Some(&c.name),
+                TopAst::Trait(t) => Some(&t.name),
+                TopAst::Enum(e) => Some(&e.name),
+                TopAst::Import(_) => None,
+            })
+            .collect()
+    }
+    /// public_definitions returns names can be imported by other modules
+    pub fn public_definitions(&self) -> Vec<&String> {
+        self.top_list
+            .iter()
+            .filter_map(|top| match top {
+                TopAst::Function(f) if f.public => Some(&f.name),
+                TopAst::Variable(v) if v.public => Some(&v.name),
+                TopAst::Class(c) if c.public => Some(&c.name),
+                TopAst::Trait(t) if t.public => Some(&t.name),
+                TopAst::Enum(e) if e.public => Some(&e.name),
+                _ => None,
+            })
+            .collect()
+    }
+    /// private_definition returns where `name` is defined when it's not `pub`
+    pub fn private_definition(&self, name: &str) -> Option<&Location> {
+        self.top_list.iter().find_map(|top| match top {
+            TopAst::Function(f) if !f.public && f.name == name => Some(&f.location),
+            TopAst::Variable(v) if !v.public && v.name == name => Some(&v.location),
+            TopAst::Class(c) if !c.public && c.name == name => Some(&c.location),
+            TopAst::Trait(t) if !t.public && t.name == name => Some(&t.location),
+            TopAst::Enum(e) if !e.public && e.name == name => Some(&e.location),
+            _ => None,
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum TopAst {
     Import(Import),
@@ -41,6 +96,7 @@ pub enum TopAst {
     Variable(Variable),
     Class(Class),
     Trait(Trait),
+    Enum(Enum),
 }
 
 /// Import
@@ -54,11 +110,37 @@ pub enum TopAst {
 ///   println("Hello, {user_name}");
 /// }
 /// ```
+///
+/// names of module can always be referred by qualified form `io.println`, the module can be
+/// renamed by `import io as io2`, then it's `io2.println`
 #[derive(Clone, Debug, PartialEq)]
 pub struct Import {
     pub location: Location,
     pub import_path: String,
-    pub imported_component: Vec<String>,
+    pub alias: Option<String>,
+    pub imported_component: Vec<ImportedComponent>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportedComponent {
+    /// `*` imports all names of the module
+    All(Location),
+    /// `println`, or `println as say` to import it by another name
+    Name {
+        location: Location,
+        name: String,
+        alias: Option<String>,
+    },
+}
+
+impl ImportedComponent {
+    pub fn name<T: ToString>(location: Location, name: T) -> ImportedComponent {
+        ImportedComponent::Name {
+            location,
+            name: name.to_string(),
+            alias: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -76,10 +158,64 @@ impl TypeParameter {
     }
 }
 
+/// Enum
+///
+/// ```elz
+/// enum Shape {
+///   Circle(r: f64),
+///   Rect(w: f64, h: f64),
+///   Empty,
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enum {
+    pub location: Location,
+    pub tag: Option<Tag>,
+    pub public: bool,
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+}
+
+impl Enum {
+    pub fn new<T: ToString>(
+        location: Location,
+        tag: Option<Tag>,
+        name: T,
+        variants: Vec<EnumVariant>,
+    ) -> Enum {
+        Enum {
+            location,
+            tag,
+            public: false,
+            name: name.to_string(),
+            variants,
+        }
+    }
+}
+
+/// EnumVariant is constructed by `Shape::Circle(1.0)`, or `Shape::Empty` when it has no fields
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumVariant {
+    pub location: Location,
+    pub name: String,
+    pub fields: Vec<Parameter>,
+}
+
+impl EnumVariant {
+    pub fn new<T: ToString>(location: Location, name: T, fields: Vec<Parameter>) -> EnumVariant {
+        EnumVariant {
+            location,
+            name: name.to_string(),
+            fields,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Trait {
     pub location: Location,
     pub tag: Option<Tag>,
+    pub public: bool,
     pub with_traits: Vec<String>,
     pub name: String,
     pub type_parameters: Vec<TypeParameter>,
@@ -106,6 +242,7 @@ impl Trait {
             tag,
             with_traits,
             name: name.to_string(),
+            public: false,
             type_parameters,
             members,
         }
@@ -116,6 +253,7 @@ impl Trait {
 pub struct Class {
     pub location: Location,
     pub tag: Option<Tag>,
+    pub public: bool,
     pub parents: Vec<String>,
     pub name: String,
     pub type_parameters: Vec<TypeParameter>,
@@ -143,6 +281,7 @@ impl Class {
             tag,
             parents,
             name: name.to_string(),
+            public: false,
             type_parameters,
             members,
         }
@@ -152,6 +291,7 @@ impl Class {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Field {
     pub location: Location,
+    pub public: bool,
     pub name: String,
     pub typ: ParsedType,
     pub expr: Option<Expr>,
@@ -167,6 +307,7 @@ impl Field {
         Field {
             location,
             name: name.to_string(),
+            public: false,
             typ,
             expr,
         }
@@ -180,6 +321,13 @@ pub enum ParsedType {
         name: String,
         type_parameters: Vec<ParsedType>,
     },
+    /// `(int, int): int`
+    FunctionType {
+        parameters: Vec<ParsedType>,
+        ret_typ: Box<ParsedType>,
+    },
+    /// `(int, string)`
+    TupleType(Vec<ParsedType>),
 }
 
 impl ParsedType {
@@ -193,15 +341,41 @@ impl ParsedType {
         }
     }
 
+    pub fn function_type(parameters: Vec<ParsedType>, ret_typ: ParsedType) -> ParsedType {
+        ParsedType::FunctionType {
+            parameters,
+            ret_typ: ret_typ.into(),
+        }
+    }
+
+    pub fn tuple_type(elements: Vec<ParsedType>) -> ParsedType {
+        ParsedType::TupleType(elements)
+    }
+
+    /// name returns the name of type, function type and tuple type have no name so they're shown
+    /// as their form
     pub fn name(&self) -> String {
         match self {
             ParsedType::TypeName(name) => name.clone(),
             ParsedType::GenericType { name, .. } => name.clone(),
+            ParsedType::FunctionType {
+                parameters,
+                ret_typ,
+            } => {
+                let parameters: Vec<String> = parameters.iter().map(|p| p.name()).collect();
+                format!("({}): {}", parameters.join(", "), ret_typ.name())
+            }
+            ParsedType::TupleType(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.name()).collect();
+                format!("({})", elements.join(", "))
+            }
         }
     }
     pub fn generics(&self) -> Vec<ParsedType> {
         match self {
-            ParsedType::TypeName(_) => vec![],
+            ParsedType::TypeName(_)
+            | ParsedType::FunctionType { .. }
+            | ParsedType::TupleType(_) => vec![],
             ParsedType::GenericType {
                 type_parameters, ..
             } => type_parameters.clone(),
@@ -213,9 +387,11 @@ impl ParsedType {
 pub struct Variable {
     pub location: Location,
     pub tag: Option<Tag>,
+    pub public: bool,
     pub name: String,
     pub typ: ParsedType,
-    pub expr: Expr,
+    // an extern variable has no value, e.g. `@extern(c) stdin: _c_file;`
+    pub expr: Option<Expr>,
 }
 
 impl Variable {
@@ -230,8 +406,24 @@ impl Variable {
             location,
             tag,
             name: name.to_string(),
+            public: false,
             typ,
-            expr,
+            expr: Some(expr),
+        }
+    }
+    pub fn new_declaration<T: ToString>(
+        location: Location,
+        tag: Option<Tag>,
+        name: T,
+        typ: ParsedType,
+    ) -> Variable {
+        Variable {
+            location,
+            tag,
+            name: name.to_string(),
+            public: false,
+            typ,
+            expr: None,
         }
     }
 }
@@ -240,6 +432,7 @@ impl Variable {
 pub struct Function {
     pub location: Location,
     pub tag: Option<Tag>,
+    pub public: bool,
     pub name: String,
     pub parameters: Vec<Parameter>,
     pub ret_typ: ParsedType,
@@ -259,6 +452,7 @@ impl Function {
             location,
             tag,
             name: name.to_string(),
+            public: false,
             parameters,
             ret_typ,
             body: Some(body),
@@ -275,6 +469,7 @@ impl Function {
             location,
             tag,
             name: name.to_string(),
+            public: false,
             parameters,
             ret_typ,
             body: None,
@@ -309,10 +504,30 @@ impl Block {
     }
 }
 
+/// ValueBlock:
+///
+/// `{ y: int = x + 1; y }`, a block ends with its value, it's a branch of if expression
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueBlock {
+    pub block: Block,
+    pub value: Box<Expr>,
+}
+
+impl ValueBlock {
+    pub fn new(block: Block, value: Expr) -> ValueBlock {
+        ValueBlock {
+            block,
+            value: value.into(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Parameter {
     pub name: String,
     pub typ: ParsedType,
+    /// `x: int = 0`, argument of the parameter can be omitted
+    pub default: Option<Expr>,
 }
 
 impl Parameter {
@@ -320,6 +535,14 @@ impl Parameter {
         Parameter {
             name: name.to_string(),
             typ,
+            default: None,
+        }
+    }
+    pub fn with_default<T: ToString>(name: T, typ: ParsedType, default: Expr) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            typ,
+            default: Some(default),
         }
     }
 }
@@ -362,6 +585,12 @@ impl Statement {
             },
         }
     }
+    pub fn destructure(location: Location, names: Vec<String>, expr: Expr) -> Statement {
+        Statement {
+            location,
+            value: StatementVariant::Destructure { names, expr },
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -378,78 +607,135 @@ pub enum StatementVariant {
         clauses: Vec<(Expr, Block)>,
         else_block: Block,
     },
+    /// `(a, b) := f();`, `_` ignores the element
+    Destructure { names: Vec<String>, expr: Expr },
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// ExprId identifies an expression, its clones keep the same id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExprId(usize);
+
+impl ExprId {
+    fn new() -> ExprId {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        ExprId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Expr {
+    pub id: ExprId,
     pub location: Location,
     pub value: ExprVariant,
 }
 
+// expressions are the same when they have the same location and value, ids are left out
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.location == other.location && self.value == other.value
+    }
+}
+
 impl Expr {
-    pub fn binary(location: Location, l: Expr, r: Expr, op: Operator) -> Expr {
+    fn new(location: Location, value: ExprVariant) -> Expr {
         Expr {
+            id: ExprId::new(),
             location,
-            value: ExprVariant::Binary(l.into(), r.into(), op),
+            value,
         }
     }
+    pub fn binary(location: Location, l: Expr, r: Expr, op: Operator) -> Expr {
+        Expr::new(location, ExprVariant::Binary(l.into(), r.into(), op))
+    }
     pub fn f64(location: Location, f: f64) -> Expr {
-        Expr {
-            location,
-            value: ExprVariant::F64(f),
-        }
+        Expr::new(location, ExprVariant::F64(f))
     }
-    pub fn int(location: Location, i: i64) -> Expr {
-        Expr {
-            location,
-            value: ExprVariant::Int(i),
-        }
+    pub fn int(location: Location, i: u64) -> Expr {
+        Expr::new(location, ExprVariant::Int(i))
     }
     pub fn bool(location: Location, b: bool) -> Expr {
-        Expr {
-            location,
-            value: ExprVariant::Bool(b),
-        }
+        Expr::new(location, ExprVariant::Bool(b))
     }
     pub fn string<T: ToString>(location: Location, s: T) -> Expr {
-        Expr {
-            location,
-            value: ExprVariant::String(s.to_string()),
-        }
+        Expr::new(location, ExprVariant::String(s.to_string()))
     }
     pub fn list(location: Location, lst: Vec<Expr>) -> Expr {
-        Expr {
-            location,
-            value: ExprVariant::List(lst),
-        }
+        Expr::new(location, ExprVariant::List(lst))
     }
     pub fn func_call(location: Location, expr: Expr, args: Vec<Argument>) -> Expr {
-        Expr {
-            location,
-            value: ExprVariant::FuncCall(expr.into(), args),
-        }
+        Expr::new(location, ExprVariant::FuncCall(expr.into(), args))
     }
     pub fn member_access<T: ToString>(location: Location, from: Expr, access: T) -> Expr {
-        Expr {
+        Expr::new(
             location,
-            value: ExprVariant::MemberAccess(from.into(), access.to_string()),
-        }
+            ExprVariant::MemberAccess(from.into(), access.to_string()),
+        )
     }
     pub fn identifier<T: ToString>(location: Location, id: T) -> Expr {
-        Expr {
+        Expr::new(location, ExprVariant::Identifier(id.to_string()))
+    }
+    pub fn interpolation(location: Location, expr: Expr) -> Expr {
+        Expr::new(location, ExprVariant::Interpolation(expr.into()))
+    }
+    pub fn cast(location: Location, expr: Expr, typ: ParsedType) -> Expr {
+        Expr::new(location, ExprVariant::Cast(expr.into(), typ))
+    }
+    pub fn match_expr(location: Location, expr: Expr, arms: Vec<MatchArm>) -> Expr {
+        Expr::new(location, ExprVariant::Match(expr.into(), arms))
+    }
+    pub fn if_expr(
+        location: Location,
+        clauses: Vec<(Expr, ValueBlock)>,
+        else_block: ValueBlock,
+    ) -> Expr {
+        Expr::new(
             location,
-            value: ExprVariant::Identifier(id.to_string()),
+            ExprVariant::If {
+                clauses,
+                else_block,
+            },
+        )
+    }
+    pub fn lambda(
+        location: Location,
+        parameters: Vec<Parameter>,
+        ret_typ: ParsedType,
+        body: Expr,
+    ) -> Expr {
+        Expr::new(
+            location,
+            ExprVariant::Lambda {
+                parameters,
+                ret_typ,
+                body: body.into(),
+            },
+        )
+    }
+    pub fn tuple(location: Location, elements: Vec<Expr>) -> Expr {
+        Expr::new(location, ExprVariant::Tuple(elements))
+    }
+    pub fn tuple_access(location: Location, tuple: Expr, index: usize) -> Expr {
+        Expr::new(location, ExprVariant::TupleAccess(tuple.into(), index))
+    }
+    /// access_path returns `a.b.c` for expression `a.b.c` made by identifier and member access
+    pub fn access_path(&self) -> Option<String> {
+        match &self.value {
+            ExprVariant::Identifier(name) => Some(name.clone()),
+            ExprVariant::MemberAccess(from, member) => {
+                Some(format!("{}.{}", from.access_path()?, member))
+            }
+            _ => None,
         }
     }
     pub fn class_construction<T: ToString>(
         location: Location,
         class_name: T,
-        field_inits: HashMap<String, Expr>,
+        field_inits: BTreeMap<String, Expr>,
     ) -> Expr {
-        Expr {
+        Expr::new(
             location,
-            value: ExprVariant::ClassConstruction(class_name.to_string(), field_inits),
-        }
+            ExprVariant::ClassConstruction(class_name.to_string(), field_inits),
+        )
     }
 }
 
@@ -459,8 +745,8 @@ pub enum ExprVariant {
     Binary(Box<Expr>, Box<Expr>, Operator),
     /// `1.345`
     F64(f64),
-    /// `1`
-    Int(i64),
+    /// `1`, `0xFF`, `0o17`, `0b1010` or `1_000`, it's not negative since `-` is not a part of it
+    Int(u64),
     /// `true` or `false`
     Bool(bool),
     /// `"str"`
@@ -474,7 +760,64 @@ pub enum ExprVariant {
     /// `n`
     Identifier(String),
     /// We can have a class construction expression: `Foo { bar: 0 }` for definition `class Foo { bar: int; }`
-    ClassConstruction(String, HashMap<String, Expr>),
+    ClassConstruction(String, BTreeMap<String, Expr>),
+    /// `{x}` in string template `"x = {x}"`, `x` would be converted to string by its `to_string` method
+    Interpolation(Box<Expr>),
+    /// `x as u8`, converts a number to another number type
+    Cast(Box<Expr>, ParsedType),
+    /// `match x { 1 => "one", _ => "many" }`
+    Match(Box<Expr>, Vec<MatchArm>),
+    /// `if x == 0 { "zero" } else { "many" }`, `else` is required since it must have a value
+    If {
+        clauses: Vec<(Expr, ValueBlock)>,
+        else_block: ValueBlock,
+    },
+    /// `(x: int): int => x + n`, names of enclosing scope used by the body are captured
+    Lambda {
+        parameters: Vec<Parameter>,
+        ret_typ: ParsedType,
+        body: Box<Expr>,
+    },
+    /// `(1, "one")`, a tuple has at least two elements
+    Tuple(Vec<Expr>),
+    /// `t.0`
+    TupleAccess(Box<Expr>, usize),
+}
+
+/// MatchArm:
+///
+/// `Shape::Circle(r) => r`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchArm {
+    pub location: Location,
+    pub pattern: Pattern,
+    pub expr: Expr,
+}
+
+impl MatchArm {
+    pub fn new(location: Location, pattern: Pattern, expr: Expr) -> MatchArm {
+        MatchArm {
+            location,
+            pattern,
+            expr,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// `_`, matches any value
+    Wildcard,
+    /// `n`, matches any value and binds it to the name
+    Binding(String),
+    /// `1`
+    Int(u64),
+    /// `true` or `false`
+    Bool(bool),
+    /// `"str"`
+    String(String),
+    /// `Shape::Circle(r)`, binds fields of the variant to names, `_` drops the field
+    Variant(String, Vec<String>),
 }
 
 /// Argument:
@@ -499,14 +842,27 @@ impl Argument {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Operator {
+    /// `x + y`
     Plus,
+    /// `x == y`
+    Equal,
 }
 
 impl Operator {
     pub fn from_token(token: Token) -> Operator {
         match token.tk_type() {
             TkType::Plus => Operator::Plus,
+            TkType::EqualEqual => Operator::Equal,
             tok => unimplemented!("{:?} is not a operator", tok),
         }
     }
 }
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Operator::Plus => write!(f, "+"),
+            Operator::Equal => write!(f, "=="),
+        }
+    }
+}
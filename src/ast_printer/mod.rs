@@ -0,0 +1,398 @@
+use crate::ast::*;
+
+/// AstPrinter shows modules as a tree, one node per line and children are indented under their
+/// parent. Locations are left out, so the output only changes when the program does.
+///
+/// ```text
+/// module main
+///   variable x: int
+///     binary +
+///       int 1
+///       identifier y
+/// ```
+///
+/// A typed printer shows the type of each expression after it, e.g. `identifier y : int`
+pub struct AstPrinter<'a> {
+    type_of: TypeOf<'a>,
+    output: String,
+    depth: usize,
+}
+
+/// TypeOf gives the type name of an expression, or `None` to leave it out
+type TypeOf<'a> = Box<dyn Fn(&Expr) -> Option<String> + 'a>;
+
+/// print_modules shows modules by `AstPrinter`
+pub fn print_modules(modules: &[Module]) -> String {
+    let mut printer = AstPrinter::new();
+    for m in modules {
+        printer.module(m);
+    }
+    printer.output
+}
+
+impl<'a> AstPrinter<'a> {
+    pub fn new() -> AstPrinter<'a> {
+        AstPrinter::typed(|_| None)
+    }
+    /// typed creates a printer shows expressions with types given by `type_of`
+    pub fn typed<F: Fn(&Expr) -> Option<String> + 'a>(type_of: F) -> AstPrinter<'a> {
+        AstPrinter {
+            type_of: Box::new(type_of),
+            output: String::new(),
+            depth: 0,
+        }
+    }
+    pub fn finish(self) -> String {
+        self.output
+    }
+
+    fn line<T: AsRef<str>>(&mut self, text: T) {
+        for _ in 0..self.depth {
+            self.output.push_str("  ");
+        }
+        self.output.push_str(text.as_ref());
+        self.output.push('\n');
+    }
+    /// node shows `text` and the children printed by `children` under it
+    fn node<T: AsRef<str>, F: FnOnce(&mut Self)>(&mut self, text: T, children: F) {
+        self.line(text);
+        self.depth += 1;
+        children(self);
+        self.depth -= 1;
+    }
+
+    pub fn module(&mut self, m: &Module) {
+        self.node(format!("module {}", m.name), |p| {
+            for top in &m.top_list {
+                p.top(top);
+            }
+        });
+    }
+
+    fn top(&mut self, top: &TopAst) {
+        match top {
+            TopAst::Import(i) => {
+                let mut s = format!("import {}", i.import_path);
+                if let Some(alias) = &i.alias {
+                    s.push_str(&format!(" as {}", alias));
+                }
+                let components: Vec<String> = i
+                    .imported_component
+                    .iter()
+                    .map(|c| match c {
+                        ImportedComponent::All(_) => "*".to_string(),
+                        ImportedComponent::Name {
+                            name,
+                            alias: Some(alias),
+                            ..
+                        } => format!("{} as {}", name, alias),
+                        ImportedComponent::Name { name, .. } => name.clone(),
+                    })
+                    .collect();
+                if !components.is_empty() {
+                    s.push_str(&format!(" ({})", components.join(", ")));
+                }
+                self.line(s);
+            }
+            TopAst::Variable(v) => {
+                let header = format!(
+                    "{}{}variable {}: {}",
+                    tag(&v.tag),
+                    visibility(v.public),
+                    v.name,
+                    v.typ.name()
+                );
+                self.node(header, |p| {
+                    if let Some(e) = &v.expr {
+                        p.expr(e);
+                    }
+                });
+            }
+            TopAst::Function(f) => self.function("function ", f),
+            TopAst::Class(c) => {
+                let mut header = format!(
+                    "{}{}class {}{}",
+                    tag(&c.tag),
+                    visibility(c.public),
+                    c.name,
+                    type_parameters(&c.type_parameters)
+                );
+                if !c.parents.is_empty() {
+                    header.push_str(&format!(" <: {}", c.parents.join(", ")));
+                }
+                self.node(header, |p| {
+                    for member in &c.members {
+                        match member {
+                            ClassMember::Field(field) => p.field(field),
+                            ClassMember::Method(f) => p.function("method ", f),
+                            ClassMember::StaticMethod(f) => p.function("static method ::", f),
+                        }
+                    }
+                });
+            }
+            TopAst::Trait(t) => {
+                let mut header = format!(
+                    "{}{}trait {}{}",
+                    tag(&t.tag),
+                    visibility(t.public),
+                    t.name,
+                    type_parameters(&t.type_parameters)
+                );
+                if !t.with_traits.is_empty() {
+                    header.push_str(&format!(" with {}", t.with_traits.join(", ")));
+                }
+                self.node(header, |p| {
+                    for member in &t.members {
+                        match member {
+                            TraitMember::Field(field) => p.field(field),
+                            TraitMember::Method(f) => p.function("method ", f),
+                        }
+                    }
+                });
+            }
+            TopAst::Enum(e) => {
+                let header = format!("{}{}enum {}", tag(&e.tag), visibility(e.public), e.name);
+                self.node(header, |p| {
+                    for variant in &e.variants {
+                        let mut header = format!("variant {}", variant.name);
+                        if !variant.fields.is_empty() {
+                            header.push_str(&format!("({})", parameters(&variant.fields)));
+                        }
+                        p.node(header, |p| p.defaults(&variant.fields));
+                    }
+                });
+            }
+        }
+    }
+
+    fn function(&mut self, kind: &str, f: &Function) {
+        let header = format!(
+            "{}{}{}{}({}): {}",
+            tag(&f.tag),
+            visibility(f.public),
+            kind,
+            f.name,
+            parameters(&f.parameters),
+            f.ret_typ.name()
+        );
+        self.node(header, |p| {
+            p.defaults(&f.parameters);
+            match &f.body {
+                Some(Body::Expr(e)) => p.expr(e),
+                Some(Body::Block(b)) => p.block(b),
+                None => (),
+            }
+        });
+    }
+
+    fn field(&mut self, field: &Field) {
+        let header = format!(
+            "{}field {}: {}",
+            visibility(field.public),
+            field.name,
+            field.typ.name()
+        );
+        self.node(header, |p| {
+            if let Some(e) = &field.expr {
+                p.expr(e);
+            }
+        });
+    }
+
+    fn defaults(&mut self, parameters: &[Parameter]) {
+        for param in parameters {
+            if let Some(default) = &param.default {
+                self.node(format!("default {}", param.name), |p| p.expr(default));
+            }
+        }
+    }
+
+    fn block(&mut self, b: &Block) {
+        self.node("block", |p| p.statements(&b.statements));
+    }
+
+    fn value_block(&mut self, b: &ValueBlock) {
+        self.node("block", |p| {
+            p.statements(&b.block.statements);
+            p.node("value", |p| p.expr(&b.value));
+        });
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            match &stmt.value {
+                StatementVariant::Return(None) => self.line("return"),
+                StatementVariant::Return(Some(e)) => self.node("return", |p| p.expr(e)),
+                StatementVariant::Variable(v) => {
+                    self.node(format!("variable {}: {}", v.name, v.typ.name()), |p| {
+                        if let Some(e) = &v.expr {
+                            p.expr(e);
+                        }
+                    })
+                }
+                StatementVariant::Expression(e) => self.node("expression", |p| p.expr(e)),
+                StatementVariant::IfBlock {
+                    clauses,
+                    else_block,
+                } => self.node("if", |p| {
+                    for (condition, block) in clauses {
+                        p.node("clause", |p| {
+                            p.expr(condition);
+                            p.block(block);
+                        });
+                    }
+                    p.node("else", |p| p.block(else_block));
+                }),
+                StatementVariant::Destructure { names, expr } => self
+                    .node(format!("destructure ({})", names.join(", ")), |p| {
+                        p.expr(expr)
+                    }),
+            }
+        }
+    }
+
+    fn expr(&mut self, e: &Expr) {
+        use ExprVariant::*;
+        let label = match &e.value {
+            Binary(_, _, op) => format!("binary {}", op),
+            F64(f) => format!("float {:?}", f),
+            Int(i) => format!("int {}", i),
+            Bool(b) => format!("bool {}", b),
+            String(s) => format!("string {:?}", s),
+            List(_) => "list".to_string(),
+            FuncCall(..) => "call".to_string(),
+            MemberAccess(_, member) => format!("member {}", member),
+            Identifier(name) => format!("identifier {}", name),
+            ClassConstruction(name, _) => format!("construct {}", name),
+            Interpolation(_) => "interpolation".to_string(),
+            Cast(_, typ) => format!("cast {}", typ.name()),
+            Match(..) => "match".to_string(),
+            If { .. } => "if".to_string(),
+            Lambda {
+                parameters: params,
+                ret_typ,
+                ..
+            } => format!("lambda ({}): {}", parameters(params), ret_typ.name()),
+            Tuple(_) => "tuple".to_string(),
+            TupleAccess(_, index) => format!("element {}", index),
+        };
+        let label = match (self.type_of)(e) {
+            Some(typ) => format!("{} : {}", label, typ),
+            None => label,
+        };
+        self.node(label, |p| match &e.value {
+            Binary(l, r, _) => {
+                p.expr(l);
+                p.expr(r);
+            }
+            List(es) | Tuple(es) => {
+                for e in es {
+                    p.expr(e);
+                }
+            }
+            FuncCall(f, args) => {
+                p.expr(f);
+                for arg in args {
+                    let label = match &arg.name {
+                        Some(name) => format!("argument {}", name),
+                        None => "argument".to_string(),
+                    };
+                    p.node(label, |p| p.expr(&arg.expr));
+                }
+            }
+            MemberAccess(from, _) | Interpolation(from) | Cast(from, _) | TupleAccess(from, _) => {
+                p.expr(from)
+            }
+            ClassConstruction(_, field_inits) => {
+                for (name, e) in field_inits {
+                    p.node(format!("field {}", name), |p| p.expr(e));
+                }
+            }
+            Match(value, arms) => {
+                p.expr(value);
+                for arm in arms {
+                    p.node(format!("arm {}", pattern(&arm.pattern)), |p| {
+                        p.expr(&arm.expr)
+                    });
+                }
+            }
+            If {
+                clauses,
+                else_block,
+            } => {
+                for (condition, block) in clauses {
+                    p.node("clause", |p| {
+                        p.expr(condition);
+                        p.value_block(block);
+                    });
+                }
+                p.node("else", |p| p.value_block(else_block));
+            }
+            Lambda { body, .. } => p.expr(body),
+            F64(..) | Int(..) | Bool(..) | String(..) | Identifier(..) => (),
+        });
+    }
+}
+
+impl<'a> Default for AstPrinter<'a> {
+    fn default() -> Self {
+        AstPrinter::new()
+    }
+}
+
+fn tag(tag: &Option<Tag>) -> String {
+    match tag {
+        Some(Tag { name, properties }) if properties.is_empty() => format!("@{} ", name),
+        Some(Tag { name, properties }) => format!("@{}({}) ", name, properties.join(", ")),
+        None => String::new(),
+    }
+}
+
+fn visibility(public: bool) -> &'static str {
+    if public {
+        "pub "
+    } else {
+        ""
+    }
+}
+
+fn type_parameters(type_parameters: &[TypeParameter]) -> String {
+    if type_parameters.is_empty() {
+        return String::new();
+    }
+    let type_parameters: Vec<String> = type_parameters
+        .iter()
+        .map(|t| {
+            if t.parent_types.is_empty() {
+                t.name.clone()
+            } else {
+                let parents: Vec<String> = t.parent_types.iter().map(|p| p.name()).collect();
+                format!("{} <: {}", t.name, parents.join(", "))
+            }
+        })
+        .collect();
+    format!("[{}]", type_parameters.join(", "))
+}
+
+fn parameters(parameters: &[Parameter]) -> String {
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|p| format!("{}: {}", p.name, p.typ.name()))
+        .collect();
+    parameters.join(", ")
+}
+
+fn pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Binding(name) => name.clone(),
+        Pattern::Int(i) => i.to_string(),
+        Pattern::Bool(b) => b.to_string(),
+        Pattern::String(s) => format!("{:?}", s),
+        Pattern::Variant(path, bindings) if bindings.is_empty() => path.clone(),
+        Pattern::Variant(path, bindings) => format!("{}({})", path, bindings.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,102 @@
+use super::*;
+use crate::parser::Parser;
+
+#[test]
+fn print_module_as_tree() {
+    let code = "module main
+    import std.io as io (print, read_line as read)
+    @extern(c)
+    puts(s: _c_string): i32;
+    pub offset(x: int, by: int = 1): int = x + by;
+    main(): void {
+      (a, _) := (offset(by: 2, x: 1), \"s\");
+      name: string = match a { 0 => \"zero\", _ => \"{a}\" };
+      if a == 1 {
+        return;
+      }
+    }
+    ";
+    let module = Parser::parse_program("", code).unwrap();
+    assert_eq!(
+        print_modules(&[module]),
+        "module main
+  import std.io as io (print, read_line as read)
+  @extern(c) function puts(s: _c_string): i32
+  pub function offset(x: int, by: int): int
+    default by
+      int 1
+    binary +
+      identifier x
+      identifier by
+  function main(): void
+    block
+      destructure (a, _)
+        tuple
+          call
+            identifier offset
+            argument by
+              int 2
+            argument x
+              int 1
+          string \"s\"
+      variable name: string
+        match
+          identifier a
+          arm 0
+            string \"zero\"
+          arm _
+            binary +
+              binary +
+                string \"\"
+                interpolation
+                  identifier a
+              string \"\"
+      if
+        clause
+          binary ==
+            identifier a
+            int 1
+          block
+            return
+        else
+          block
+"
+    );
+}
+
+#[test]
+fn print_class_and_enum() {
+    let code = "module main
+    class Point {
+      pub x: int = 0;
+      ::origin(): Point = Point { x: 0 };
+      moved(by: int): Point = Point { x: x + by };
+    }
+    enum Shape {
+      Circle(r: f64),
+      Empty,
+    }
+    ";
+    let module = Parser::parse_program("", code).unwrap();
+    assert_eq!(
+        print_modules(&[module]),
+        "module main
+  class Point
+    pub field x: int
+      int 0
+    static method ::origin(): Point
+      construct Point
+        field x
+          int 0
+    method moved(by: int): Point
+      construct Point
+        field x
+          binary +
+            identifier x
+            identifier by
+  enum Shape
+    variant Circle(r: f64)
+    variant Empty
+"
+    );
+}
@@ -0,0 +1,45 @@
+use crate::cmd::compile::{emit_program, Emit};
+use crate::loader::Loader;
+use crate::project::Project;
+use std::path::Path;
+
+pub const CMD_NAME: &str = "build";
+
+/// build compiles all modules of the project in `dir` into `<out_dir>/<package name>.ll`, output
+/// directory is `build` under the project by default
+pub fn build(dir: &str, out_dir: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let project = Project::open(Path::new(dir))?;
+    let package = &project.manifest.package;
+    // package name is the name of output file, it must not escape the output directory
+    if package.name.contains(std::path::is_separator) {
+        return Err(format!(
+            "package name `{}` cannot contain path separator",
+            package.name
+        )
+        .into());
+    }
+    if project.entry_file().is_none() {
+        return Err(format!(
+            "entry module `{}` of package `{}` not found in source directories",
+            package.entry, package.name
+        )
+        .into());
+    }
+    let source_files: Vec<String> = project
+        .source_files()
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    let files = source_files.iter().map(|file| file.as_str()).collect();
+    let loader = Loader::new(project.search_paths()?);
+    let result = emit_program(&loader, files, Emit::LLVM)?;
+    let out_dir = match out_dir {
+        Some(out_dir) => Path::new(out_dir).to_path_buf(),
+        None => project.root.join("build"),
+    };
+    std::fs::create_dir_all(&out_dir)?;
+    let output = out_dir.join(format!("{}.ll", package.name));
+    std::fs::write(&output, result)?;
+    println!("compiled `{}` into {}", package.name, output.display());
+    Ok(())
+}
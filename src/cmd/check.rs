@@ -0,0 +1,13 @@
+use crate::cmd::compile::check_program;
+use crate::diagnostic::Reporter;
+use crate::loader::Loader;
+
+pub const CMD_NAME: &str = "check";
+
+/// check only parses and checks files as a program without generating code
+pub fn check(files: Vec<&str>, lib_dirs: Vec<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut reporter = Reporter::new();
+    let modules = Loader::for_files(&files, lib_dirs).load(&mut reporter, files)?;
+    check_program(&mut reporter, &modules)?;
+    Ok(())
+}
@@ -1,64 +1,122 @@
-use crate::ast::{Import, TopAst};
+use crate::ast::Module;
+use crate::ast_printer::print_modules;
 use crate::codegen::llvm::LLVMValue;
 use crate::codegen::CodeGenerator;
 use crate::diagnostic::Reporter;
-use crate::lexer::Location;
-use crate::parser::{parse_prelude, Parser};
+use crate::lexer;
+use crate::loader::Loader;
 use crate::semantic::SemanticChecker;
+use std::io::Write;
 
-pub const CMD_NAME: &'static str = "compile";
+pub const CMD_NAME: &str = "compile";
 
-pub fn compile(files: Vec<&str>) -> Result<(), Box<dyn std::error::Error>> {
-    let mut reporter = Reporter::new();
-    // FIXME: comment out code generator for now to focus on semantic checking
-    let program = check(&mut reporter, files)?;
-    let code_generator = CodeGenerator::new();
-    let module = code_generator.generate_module(&program);
-    println!("{}", module.llvm_represent());
+/// Emit is the stage of compiler to output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emit {
+    /// tokens from lexer, one token per line
+    Tokens,
+    /// AST from parser
+    Ast,
+    /// AST with the type of each expression after semantic checking
+    TypedAst,
+    /// LLVM IR text of the whole program, which can be linked
+    LLVM,
+}
+
+impl Emit {
+    pub const STAGES: &[&str] = &["tokens", "ast", "typed-ast", "llvm"];
+}
+
+impl std::str::FromStr for Emit {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tokens" => Ok(Emit::Tokens),
+            "ast" => Ok(Emit::Ast),
+            "typed-ast" => Ok(Emit::TypedAst),
+            "llvm" => Ok(Emit::LLVM),
+            _ => Err(format!(
+                "unknown emit stage `{}`, expected one of: {}",
+                s,
+                Emit::STAGES.join(", ")
+            )),
+        }
+    }
+}
+
+/// compile writes the output of `emit` stage into `output`, or stdout when no output file
+pub fn compile(
+    files: Vec<&str>,
+    lib_dirs: Vec<&str>,
+    output: Option<&str>,
+    emit: Emit,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = emit_stage(files, lib_dirs, emit)?;
+    match output {
+        Some(path) => std::fs::write(path, result)?,
+        None => std::io::stdout().write_all(result.as_bytes())?,
+    }
     Ok(())
 }
 
-fn check(
-    reporter: &mut Reporter,
+fn emit_stage(
     files: Vec<&str>,
-) -> Result<Vec<TopAst>, Box<dyn std::error::Error>> {
-    // FIXME: for now to make code simple we only handle the first input file.
-    let code = std::fs::read_to_string(files[0])?;
-    let mut file_reporter = reporter.for_file(files[0], &code);
-    let mut module = match Parser::parse_program(files[0], &code) {
-        Ok(p) => p,
-        Err(err) => {
-            file_reporter.add_diagnostic(err.location(), format!("{}", err), err.message());
-            file_reporter.report(reporter);
-            return Err(err.into());
+    lib_dirs: Vec<&str>,
+    emit: Emit,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let loader = Loader::for_files(&files, lib_dirs);
+    emit_program(&loader, files, emit)
+}
+
+/// emit_program returns the output of `emit` stage of the program made by `files` and modules
+/// they import. Prelude is not a part of input, so it's left out by all stages except `llvm`,
+/// which must be the whole program.
+pub(crate) fn emit_program(
+    loader: &Loader,
+    files: Vec<&str>,
+    emit: Emit,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut reporter = Reporter::new();
+    if emit == Emit::Tokens {
+        let mut s = String::new();
+        for file_name in files {
+            let code = std::fs::read_to_string(file_name)?;
+            for token in lexer::lex(file_name, code.as_str()) {
+                s.push_str(format!("{}\n", token).as_str());
+            }
         }
-    };
-    // insert import prelude
-    module.top_list.push(TopAst::Import(Import {
-        location: Location::none(),
-        import_path: "prelude".to_string(),
-        imported_component: vec![
-            "int".to_string(),
-            "void".to_string(),
-            "f64".to_string(),
-            "bool".to_string(),
-            "string".to_string(),
-            "List".to_string(),
-            "println".to_string(),
-        ],
-    }));
+        return Ok(s);
+    }
+    let modules = loader.load(&mut reporter, files)?;
+    // loader puts prelude first
+    let input = &modules[1..];
+    if emit == Emit::Ast {
+        return Ok(print_modules(input));
+    }
+    let semantic_checker = check_program(&mut reporter, &modules)?;
+    if emit == Emit::TypedAst {
+        return Ok(semantic_checker.print_typed(input));
+    }
+    let code_generator = CodeGenerator::new();
+    let ir_module = code_generator.generate_program(&modules);
+    Ok(ir_module.llvm_represent())
+}
 
-    let prelude = parse_prelude();
-    let mut l = prelude.top_list.clone();
-    l.append(module.top_list.as_mut());
-    let program = vec![prelude, module];
-    // check program
+/// check_program checks `modules` as a program, the error is reported by `reporter`
+pub(crate) fn check_program(
+    reporter: &mut Reporter,
+    modules: &Vec<Module>,
+) -> Result<SemanticChecker, Box<dyn std::error::Error>> {
     let mut semantic_checker = SemanticChecker::new();
-    match semantic_checker.check_program(&program) {
-        Ok(..) => Ok(l),
+    match semantic_checker.check_program(modules) {
+        Ok(()) => Ok(semantic_checker),
         Err(err) => {
-            file_reporter.add_diagnostic(err.location(), format!("{}", err), err.message());
-            file_reporter.report(reporter);
+            reporter.report_with_related(
+                err.location(),
+                format!("{}", err),
+                err.message(),
+                err.related(),
+            );
             Err(err.into())
         }
     }
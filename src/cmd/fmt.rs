@@ -4,11 +4,14 @@ use std::io::Write;
 use std::path::Path;
 use walkdir::WalkDir;
 
-pub const CMD_NAME: &'static str = "fmt";
+pub const CMD_NAME: &str = "fmt";
 
 pub fn format(paths: Vec<&str>) -> Result<(), Box<dyn std::error::Error>> {
     for path_str in paths {
         let path = Path::new(path_str);
+        if !path.exists() {
+            return Err(format!("cannot find {}", path_str).into());
+        }
         path.extension().map_or_else(
             || handle_dir(path),
             |extension| {
@@ -1,2 +1,8 @@
+pub mod build;
+pub mod check;
 pub mod compile;
 pub mod fmt;
+pub mod new;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,17 @@
+use crate::project::Project;
+use std::path::Path;
+
+pub const CMD_NAME: &str = "new";
+
+/// new creates a project in directory `path`, the project is named by the last component of
+/// `path`, e.g. `elz new apps/hello` creates project `hello`
+pub fn new(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let root = Path::new(path);
+    let name = root
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("cannot name a project by {}", path))?;
+    Project::create(root, name)?;
+    println!("created project `{}` in {}", name, root.display());
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use super::build::build;
+use super::compile::{compile, Emit};
+use super::new::new;
+use crate::project::{Manifest, Project, MANIFEST_FILE};
+use crate::temp_dir::TempDir;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn read_line_puts_back_rest_of_long_line() {
+    let dir = TempDir::new("cmd-read_line_ir");
+    std::fs::create_dir_all(&*dir).unwrap();
+    let main = dir.join("main.elz").display().to_string();
+    std::fs::write(&main, READ_LINES).unwrap();
+    let ll = dir.join("main.ll").display().to_string();
+    compile(vec![main.as_str()], vec![], Some(ll.as_str()), Emit::LLVM).unwrap();
+    let ir = std::fs::read_to_string(&ll).unwrap();
+    // the `FILE *` of libc, buffered input is shared with other C functions
+    assert!(ir.contains("@stdin = external global i8*"));
+    assert!(ir.contains("call i32 @ungetc(i32 "));
+}
+
+#[test]
+#[ignore = "runs llc and cc, run it by `cargo test -- --ignored` where they are installed"]
+fn read_line_returns_long_line_in_pieces() {
+    let input = format!("{}\nb\n", "a".repeat(5000));
+    assert_eq!(run("read_line", READ_LINES, &input), "4095 905 b\n");
+}
+
+#[test]
+fn new_project_in_nested_absolute_directory_is_named_by_last_component() {
+    let root = TempDir::new("cmd-new-nested");
+    let project_dir = root.join("apps").join("hello");
+    assert!(project_dir.is_absolute());
+    new(&project_dir.display().to_string()).unwrap();
+    let project = Project::open(&project_dir).unwrap();
+    assert_eq!(project.manifest, Manifest::new("hello"));
+    build(&project_dir.display().to_string(), None).unwrap();
+    assert!(project_dir.join("build").join("hello.ll").is_file());
+}
+
+#[test]
+fn new_project_must_be_named_by_identifier() {
+    let root = TempDir::new("cmd-new-invalid");
+    for path in &["my-app", "1st", "apps/.."] {
+        assert!(new(&root.join(path).display().to_string()).is_err());
+    }
+}
+
+#[test]
+fn build_refuses_package_name_has_path_separator() {
+    let root = TempDir::new("cmd-build-separator");
+    new(&root.join("app").display().to_string()).unwrap();
+    std::fs::write(
+        root.join("app").join(MANIFEST_FILE),
+        "[package]\nname = \"/tmp/app\"\n",
+    )
+    .unwrap();
+    let err = build(&root.join("app").display().to_string(), None).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "package name `/tmp/app` cannot contain path separator"
+    );
+}
+
+// helpers, must put tests before this line
+const READ_LINES: &str = "module main
+import std.io (read_line)
+main(): void {
+  first: string = read_line();
+  second: string = read_line();
+  third: string = read_line();
+  println(\"{first.len()} {second.len()} {third}\");
+}
+";
+
+/// run compiles `code` as module main by `llc` and `cc`, then runs it with `input` as standard
+/// input and returns standard output
+fn run(name: &str, code: &str, input: &str) -> String {
+    let dir = TempDir::new(&format!("cmd-{}", name));
+    std::fs::create_dir_all(&*dir).unwrap();
+    let main = dir.join("main.elz").display().to_string();
+    std::fs::write(&main, code).unwrap();
+    let ll = dir.join("main.ll").display().to_string();
+    compile(vec![main.as_str()], vec![], Some(ll.as_str()), Emit::LLVM).unwrap();
+    let asm = dir.join("main.s");
+    let exe = dir.join("main");
+    let status = Command::new("llc")
+        .args(["-relocation-model=pic", ll.as_str(), "-o"])
+        .arg(&asm)
+        .status()
+        .expect("cannot run llc");
+    assert!(status.success());
+    let status = Command::new("cc")
+        .arg(&asm)
+        .arg("-o")
+        .arg(&exe)
+        .arg("-lm")
+        .status()
+        .expect("cannot run cc");
+    assert!(status.success());
+    let mut child = Command::new(&exe)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
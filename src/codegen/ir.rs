@@ -1,7 +1,10 @@
 use crate::ast;
 use crate::ast::*;
+use crate::codegen::tag::CodegenTag;
+use crate::lexer::Location;
+use crate::semantic::free_names;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Formatter;
 use std::ops::Deref;
 use std::rc::Rc;
@@ -10,10 +13,30 @@ pub struct Module {
     // helpers
     pub(crate) known_functions: HashMap<String, Type>,
     pub(crate) known_variables: HashMap<String, Type>,
+    // global variables folded to constant, other globals can be folded with them
+    constants: HashMap<String, Expr>,
+    // parameter types of variadic functions, a call to them must write down the function type
+    pub(crate) variadic_functions: HashMap<String, Vec<Type>>,
+    // builtin classes are LLVM types, e.g. `@builtin(i64) class int {}`
+    builtin_types: Vec<(String, Type)>,
+    // a call to builtin function is an LLVM instruction, e.g. `@builtin(sitofp)` function
+    builtin_functions: HashMap<String, String>,
+    // parameter types of functions, methods don't include `self`, integer literal arguments
+    // have the type of parameter
+    parameter_types: HashMap<String, Vec<Type>>,
+    // parameters of functions, arguments of calls are arranged by their names and default values
+    parameters: HashMap<String, Vec<Parameter>>,
+    // variants of enums, e.g. `Shape::Circle`
+    pub(crate) enum_variants: HashMap<String, EnumVariant>,
+    // lambdas are generated as functions `elz.lambda.N`
+    lambda_count: usize,
     // output parts
-    pub(crate) functions: HashMap<String, Function>,
+    // definitions are kept in the order they were pushed, so output is the same in every run
+    pub(crate) functions: Vec<Function>,
     pub(crate) variables: Vec<Variable>,
-    pub(crate) types: HashMap<String, Type>,
+    pub(crate) types: Vec<Type>,
+    // initializes global variables those cannot be folded to constant before `main`
+    pub(crate) initializer: Option<Function>,
 }
 
 impl Module {
@@ -21,25 +44,167 @@ impl Module {
         Module {
             known_functions: HashMap::new(),
             known_variables: HashMap::new(),
-            functions: HashMap::new(),
+            constants: HashMap::new(),
+            variadic_functions: HashMap::new(),
+            builtin_types: vec![],
+            builtin_functions: HashMap::new(),
+            parameter_types: HashMap::new(),
+            parameters: HashMap::new(),
+            enum_variants: HashMap::new(),
+            lambda_count: 0,
+            functions: vec![],
             variables: vec![],
-            types: HashMap::new(),
+            types: vec![],
+            initializer: None,
         }
     }
-    pub(crate) fn remember_function(&mut self, f: &ast::Function) {
+    pub(crate) fn remember_function(&mut self, name: String, f: &ast::Function) {
         let ret_type = Type::from_ast(&f.ret_typ, self);
-        self.known_functions.insert(f.name.clone(), ret_type);
+        let parameters: Vec<Type> = f
+            .parameters
+            .iter()
+            .map(|p| Type::from_ast(&p.typ, self))
+            .collect();
+        if f.tag.is_variadic() {
+            self.variadic_functions
+                .insert(name.clone(), parameters.clone());
+        }
+        self.parameter_types.insert(name.clone(), parameters);
+        self.parameters.insert(name.clone(), f.parameters.clone());
+        if let Some(instruction) = f.tag.builtin_instruction() {
+            self.builtin_functions.insert(name.clone(), instruction);
+        }
+        self.known_functions.insert(name, ret_type);
     }
     pub(crate) fn remember_variable(&mut self, v: &ast::Variable) {
         self.known_variables
             .insert(v.name.clone(), Type::from_ast(&v.typ, self));
     }
+    /// push_global_variables generates global variables in initialization order, a variable
+    /// that cannot be folded to constant is zero at start and is initialized by `initializer`
+    pub(crate) fn push_global_variables(&mut self, variables: Vec<&ast::Variable>) {
+        let mut to_initialize = vec![];
+        for v in variables {
+            let typ = Type::from_ast(&v.typ, self);
+            let expr = match &v.expr {
+                Some(expr) => expr,
+                None => {
+                    self.push_variable(Variable::external(v.name.clone(), typ));
+                    continue;
+                }
+            };
+            let value = match self.fold_constant(expr) {
+                Some(constant) => {
+                    let constant = constant.typed_as(&typ);
+                    self.constants.insert(v.name.clone(), constant.clone());
+                    constant
+                }
+                None => {
+                    to_initialize.push(v);
+                    Expr::Zero(typ)
+                }
+            };
+            self.push_variable(Variable::new(v.name.clone(), value));
+        }
+        if !to_initialize.is_empty() {
+            self.initializer = Some(Function::initializer(&to_initialize, self));
+        }
+    }
+    /// fold_constant computes the value of expression at compile time, returns None when the
+    /// expression has to be evaluated at runtime, e.g. function call
+    fn fold_constant(&mut self, e: &ast::Expr) -> Option<Expr> {
+        use ExprVariant::*;
+        match &e.value {
+            Int(..) | F64(..) | Bool(..) => Some(Expr::from_ast(e)),
+            Identifier(name) => self
+                .constants
+                .get(name)
+                .cloned()
+                .or_else(|| self.function_value(name)),
+            Binary(lhs, rhs, op) => {
+                let lhs = self.fold_constant(lhs)?;
+                let rhs = self.fold_constant(rhs)?;
+                match (op, lhs, rhs) {
+                    (Operator::Plus, Expr::Int(typ, l), Expr::Int(_, r)) => {
+                        Some(Expr::Int(typ, l.wrapping_add(r)))
+                    }
+                    (Operator::Plus, Expr::F64(l), Expr::F64(r)) => Some(Expr::F64(l + r)),
+                    (Operator::Equal, Expr::Int(_, l), Expr::Int(_, r)) => Some(Expr::Bool(l == r)),
+                    (Operator::Equal, l, r) => Some(Expr::Bool(l == r)),
+                    _ => None,
+                }
+            }
+            Tuple(elements) => {
+                let mut constants = vec![];
+                for e in elements {
+                    constants.push(self.fold_constant(e)?);
+                }
+                let typ = Type::tuple(constants.iter().map(|c| c.type_()).collect());
+                Some(Expr::Struct(typ, constants))
+            }
+            _ => None,
+        }
+    }
+    /// function returns function `name` for direct call
+    fn function(&self, name: &str) -> Expr {
+        let typ = Type::FunctionPointer {
+            parameters: self.parameter_types.get(name).cloned().unwrap_or_default(),
+            ret_typ: self.known_functions[name].clone().into(),
+        };
+        Expr::Function(typ, name.to_string())
+    }
+    /// function_value returns function `name` as a value of function type, which is a closure
+    /// without environment, its code is `name.closure` that ignores the environment and calls
+    /// the function
+    fn function_value(&mut self, name: &str) -> Option<Expr> {
+        let ret_typ = self.known_functions.get(name)?.clone();
+        let typ = Type::Function {
+            parameters: self.parameter_types[name].clone(),
+            ret_typ: ret_typ.into(),
+        };
+        let code = format!("{}.closure", name);
+        if !self
+            .functions
+            .iter()
+            .any(|f| f.name == function_name(&code))
+        {
+            let adapter = Function::closure_adapter(name, &code, &typ, self);
+            self.push_function(adapter);
+        }
+        Some(Expr::closure(typ, &code, Expr::Zero(Type::env())))
+    }
     pub(crate) fn push_function(&mut self, f: Function) {
-        self.functions.insert(f.name.clone(), f);
+        // an extern function can be declared by many modules, semantic checker ensures they have
+        // the same signature
+        if f.body.is_none() && self.functions.iter().any(|g| g.name == f.name) {
+            return;
+        }
+        self.functions.push(f);
     }
     pub(crate) fn push_variable(&mut self, v: Variable) {
+        if let GlobalName::ID(id) = &v.name {
+            let anonymous_count = self
+                .variables
+                .iter()
+                .filter(|v| matches!(v.name, GlobalName::ID(..)))
+                .count();
+            id.borrow_mut().set_id(anonymous_count as u64);
+        }
         self.variables.push(v);
     }
+    /// declare_builtin_type makes class `type_name` refer to LLVM type `typ`
+    pub(crate) fn declare_builtin_type(&mut self, type_name: &str, typ: Type) {
+        self.builtin_types.push((type_name.to_string(), typ));
+    }
+    /// declare_type makes the name of class known before its fields were generated, so fields
+    /// can refer to classes defined later
+    pub(crate) fn declare_type(&mut self, type_name: &str) {
+        let typ = Type::Struct {
+            name: type_name.to_string(),
+            fields: vec![],
+        };
+        self.types.push(typ);
+    }
     pub(crate) fn push_type(&mut self, type_name: &String, fields: &Vec<ClassMember>) {
         let typ = Type::Struct {
             name: type_name.clone(),
@@ -58,10 +223,173 @@ impl Module {
                 })
                 .collect(),
         };
-        self.types.insert(type_name.clone(), typ);
+        self.replace_type(typ);
     }
-    fn lookup_type(&self, type_name: &String) -> &Type {
-        self.types.get(type_name).unwrap()
+    /// replace_type replaces the declaration of type
+    fn replace_type(&mut self, typ: Type) {
+        match self.types.iter().position(|t| t.name() == typ.name()) {
+            Some(i) => self.types[i] = typ,
+            None => self.types.push(typ),
+        }
+    }
+    /// push_enum generates enum as a tagged union `{ i64, [n x i8] }`, the tag is the index of
+    /// variant and the array stores the payload of variant, which is a struct of its fields, so
+    /// `n` is the size in bytes of the largest payload.
+    /// Variants are constructed by functions, e.g. `Shape::Circle(r: f64): Shape`
+    pub(crate) fn push_enum(&mut self, e: &ast::Enum) {
+        // a field of class or enum type is a pointer, so payloads can be sized before those
+        // types are complete
+        let variant_fields: Vec<Vec<Field>> = e
+            .variants
+            .iter()
+            .map(|variant| {
+                variant
+                    .fields
+                    .iter()
+                    .map(|f| Field {
+                        name: f.name.clone(),
+                        typ: Type::from_ast(&f.typ, self).into(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let bytes = variant_fields
+            .iter()
+            .map(|fields| {
+                Type::Struct {
+                    name: String::new(),
+                    fields: fields.clone(),
+                }
+                .layout()
+                .0
+            })
+            .max()
+            .unwrap_or(0);
+        let typ = Type::Struct {
+            name: e.name.clone(),
+            fields: vec![
+                Field {
+                    name: "tag".to_string(),
+                    typ: Type::Int(64).into(),
+                },
+                Field {
+                    name: "payload".to_string(),
+                    typ: Type::Array {
+                        len: bytes,
+                        element_type: Type::Int(8).into(),
+                    }
+                    .into(),
+                },
+            ],
+        };
+        self.replace_type(typ.clone());
+        for ((tag, variant), fields) in e.variants.iter().enumerate().zip(variant_fields) {
+            let name = format!("{}::{}", e.name, variant.name);
+            let payload = if fields.is_empty() {
+                None
+            } else {
+                let payload = Type::Struct {
+                    name: format!("{}.{}", e.name, variant.name),
+                    fields,
+                };
+                self.types.push(payload.clone());
+                Some(payload)
+            };
+            let parameters = variant
+                .fields
+                .iter()
+                .map(|f| Type::from_ast(&f.typ, self))
+                .collect();
+            self.parameter_types.insert(name.clone(), parameters);
+            self.parameters.insert(name.clone(), variant.fields.clone());
+            self.known_functions.insert(name.clone(), typ.clone());
+            self.enum_variants.insert(
+                name,
+                EnumVariant {
+                    tag: tag as u64,
+                    payload,
+                },
+            );
+        }
+    }
+    pub(crate) fn lookup_type(&self, type_name: &String) -> &Type {
+        if let Some((_, typ)) = self
+            .builtin_types
+            .iter()
+            .find(|(name, _)| name == type_name)
+        {
+            return typ;
+        }
+        self.types
+            .iter()
+            .find(|t| t.name() == type_name)
+            .unwrap_or_else(|| panic!("no type named: `{}`", type_name))
+    }
+    /// lookup_function finds function by its LLVM name, e.g. `@main`
+    #[cfg(test)]
+    pub(crate) fn lookup_function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+    /// class_name returns the name of class in elz of a type, methods of the class are named by it
+    fn class_name(&self, typ: &Type) -> String {
+        match typ {
+            Type::Struct { name, .. } | Type::Named(name) => name.clone(),
+            t => match self.builtin_types.iter().find(|(_, builtin)| builtin == t) {
+                Some((name, _)) => name.clone(),
+                None => unreachable!("type `{:?}` has no class", t),
+            },
+        }
+    }
+    /// lookup_fields returns the fields of a class type
+    /// has_field reports whether type is a struct has field `name`
+    fn has_field(&self, typ: &Type, name: &str) -> bool {
+        match typ {
+            Type::Struct { .. } | Type::Named(_) => {
+                self.lookup_fields(typ).iter().any(|f| f.name == name)
+            }
+            _ => false,
+        }
+    }
+    fn lookup_fields(&self, typ: &Type) -> Vec<Field> {
+        let name = match typ {
+            Type::Struct { name, .. } | Type::Named(name) => name,
+            _ => unreachable!("non-class type cannot access member: `{:?}`", typ),
+        };
+        match self.lookup_type(name) {
+            Type::Struct { fields, .. } => fields.clone(),
+            _ => unreachable!("non-class type cannot access member: `{:?}`", typ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EnumVariant {
+    pub(crate) tag: u64,
+    // struct of fields, a variant without fields has no payload
+    pub(crate) payload: Option<Type>,
+}
+
+/// Debug only shows output parts, helpers are HashMaps which have no stable order
+impl std::fmt::Debug for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Module")
+            .field("types", &self.types)
+            .field("variables", &self.variables)
+            .field("functions", &self.functions)
+            .field("initializer", &self.initializer)
+            .finish()
+    }
+}
+
+/// function_name returns the LLVM name of a function, a name like `Foo::new` must be quoted
+pub(crate) fn function_name(name: &str) -> String {
+    if name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    {
+        format!("@{}", name)
+    } else {
+        format!("@\"{}\"", name)
     }
 }
 
@@ -115,8 +443,11 @@ pub(crate) enum Instruction {
     },
     FunctionCall {
         id: Rc<RefCell<ID>>,
-        func_name: String,
+        // function `@foo` or a value of function type for indirect call
+        callee: Expr,
         ret_type: Box<Type>,
+        // only variadic function has this, e.g. `(i8*, ...)`
+        variadic_parameters: Option<Vec<Type>>,
         args_expr: Vec<Expr>,
     },
     BinaryOperation {
@@ -125,6 +456,11 @@ pub(crate) enum Instruction {
         lhs: Expr,
         rhs: Expr,
     },
+    /// stack slot of a value computed in different branches, e.g. result of match
+    Alloca {
+        id: Rc<RefCell<ID>>,
+        typ: Type,
+    },
     Malloca {
         id: Rc<RefCell<ID>>,
         typ: Type,
@@ -134,13 +470,33 @@ pub(crate) enum Instruction {
         from_id: Rc<RefCell<ID>>,
         target_type: Type,
     },
+    /// conversion between types, e.g. `sitofp i64 %x to double`
+    Convert {
+        id: Rc<RefCell<ID>>,
+        op_name: String,
+        value: Expr,
+        target_type: Type,
+    },
     Load {
         id: Rc<RefCell<ID>>,
         load_from: Expr,
     },
+    /// element of struct value, e.g. the code of closure
+    ExtractValue {
+        id: Rc<RefCell<ID>>,
+        aggregate: Expr,
+        index: u64,
+    },
+    /// struct value with element at `index` replaced, e.g. closure with its environment
+    InsertValue {
+        id: Rc<RefCell<ID>>,
+        aggregate: Expr,
+        element: Expr,
+        index: u64,
+    },
     Store {
         source: Expr,
-        destination: Rc<RefCell<ID>>,
+        destination: Expr,
     },
 }
 
@@ -157,9 +513,15 @@ impl Instruction {
         use Instruction::*;
         match self {
             Label(label) => label.id.borrow_mut().set_id(value),
+            // call to a void function has no result, so it doesn't take a number
+            FunctionCall { ret_type, .. } if **ret_type == Type::Void => false,
             Load { id, .. }
+            | Alloca { id, .. }
             | Malloca { id, .. }
             | BitCast { id, .. }
+            | Convert { id, .. }
+            | ExtractValue { id, .. }
+            | InsertValue { id, .. }
             | GEP { id, .. }
             | FunctionCall { id, .. }
             | BinaryOperation { id, .. } => id.borrow_mut().set_id(value),
@@ -170,7 +532,12 @@ impl Instruction {
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum LocalVariable {
-    Name { typ: Type, name: String },
+    Name {
+        typ: Type,
+        name: String,
+    },
+    /// local variable defined by `x: int = 1;`, refer to the value directly
+    Value(Expr),
 }
 
 impl LocalVariable {
@@ -183,12 +550,18 @@ impl LocalVariable {
 pub(crate) struct Body {
     pub(crate) instructions: Vec<Instruction>,
     // local variables(including parameters)
-    variables: HashMap<String, LocalVariable>,
+    variables: BTreeMap<String, LocalVariable>,
+    ret_typ: Type,
 }
 
 impl Body {
-    fn from_ast(b: &ast::Body, module: &mut Module, parameters: &Vec<Parameter>) -> Body {
-        let mut variables = HashMap::new();
+    fn from_ast(
+        b: &ast::Body,
+        module: &mut Module,
+        parameters: &Vec<Parameter>,
+        ret_typ: Type,
+    ) -> Body {
+        let mut variables = BTreeMap::new();
 
         for p in parameters {
             // FIXME: type from duplicate in ir::Function, share information
@@ -200,22 +573,33 @@ impl Body {
         let mut body = Body {
             instructions: vec![],
             variables,
+            ret_typ,
         };
         match b {
             ast::Body::Expr(e) => {
-                let e = body.expr_from_ast(e, module);
-                body.instructions.push(Instruction::Return(Some(e)));
+                let e = body.expr_from_ast_as(e, &body.ret_typ.clone(), module);
+                // void has no value to return, e.g. `f(): void = g();`
+                let ret = if e.type_() == Type::Void {
+                    None
+                } else {
+                    Some(e)
+                };
+                body.instructions.push(Instruction::Return(ret));
             }
             ast::Body::Block(b) => body.generate_instructions(&b.statements, module),
         };
-        // update local identifier value
+        body.update_ids();
+        body
+    }
+
+    /// update_ids updates local identifier values, they must be sequential in a function
+    fn update_ids(&mut self) {
         let mut counter = 1;
-        for inst in &mut body.instructions {
+        for inst in &mut self.instructions {
             if inst.set_id(counter) {
                 counter += 1;
             }
         }
-        body
     }
 
     fn lookup_variable(&self, name: &String) -> Option<&LocalVariable> {
@@ -229,7 +613,10 @@ impl Body {
                 Return(e) => {
                     let inst = match e {
                         None => Instruction::Return(None),
-                        Some(ex) => Instruction::Return(Some(self.expr_from_ast(ex, module))),
+                        Some(ex) => {
+                            let ret_typ = self.ret_typ.clone();
+                            Instruction::Return(Some(self.expr_from_ast_as(ex, &ret_typ, module)))
+                        }
                     };
                     self.instructions.push(inst)
                 }
@@ -269,12 +656,30 @@ impl Body {
                         .push(Instruction::Label(leave_label.clone()));
                 }
                 Variable(v) => {
-                    self.expr_from_ast(&v.expr, module);
+                    let typ = Type::from_ast(&v.typ, module);
+                    // semantic checker ensures local variables have values
+                    let expr = v.expr.as_ref().expect("local variable must have a value");
+                    let value = self.expr_from_ast_as(expr, &typ, module);
+                    if v.name != "_" {
+                        self.variables
+                            .insert(v.name.clone(), LocalVariable::Value(value));
+                    }
+                }
+                Destructure { names, expr } => {
+                    let value = self.expr_from_ast(expr, module);
+                    let element_types = value.type_().element_types();
+                    for (index, (name, typ)) in names.iter().zip(element_types).enumerate() {
+                        if name != "_" {
+                            let element = self.extract_value(value.clone(), index as u64, typ);
+                            self.variables
+                                .insert(name.clone(), LocalVariable::Value(element));
+                        }
+                    }
                 }
             }
         }
     }
-    fn end_with_terminator(&self) -> bool {
+    pub(crate) fn end_with_terminator(&self) -> bool {
         match self.instructions.last() {
             None => false,
             Some(inst) => inst.is_terminator(),
@@ -290,6 +695,7 @@ pub(crate) struct Function {
     pub(crate) name: String,
     pub(crate) parameters: Vec<(String, Type)>,
     pub(crate) ret_typ: Type,
+    pub(crate) variadic: bool,
     pub(crate) body: Option<Body>,
 }
 
@@ -299,22 +705,127 @@ impl Function {
         class_name: Option<String>,
         module: &mut Module,
     ) -> Function {
+        let ret_typ = Type::from_ast(&f.ret_typ, module);
         let body = match &f.body {
-            Some(b) => Some(Body::from_ast(b, module, &f.parameters)),
+            Some(b) => Some(Body::from_ast(b, module, &f.parameters, ret_typ.clone())),
             None => None,
         };
         let function_name = match class_name {
             None => f.name.clone(),
-            Some(class_name) => format!("\"{}::{}\"", class_name, f.name),
+            Some(class_name) => format!("{}::{}", class_name, f.name),
         };
+        let mut func = Function::new(function_name, &f.parameters, ret_typ, body, module);
+        func.variadic = f.tag.is_variadic();
+        func
+    }
+    /// initializer evaluates initializers of global variables and stores the results
+    fn initializer(variables: &[&ast::Variable], module: &mut Module) -> Function {
+        let mut body = Body {
+            instructions: vec![],
+            variables: BTreeMap::new(),
+            ret_typ: Type::Void,
+        };
+        for v in variables {
+            let typ = Type::from_ast(&v.typ, module);
+            let expr = v.expr.as_ref().expect("extern variable has no initializer");
+            let value = body.expr_from_ast_as(expr, &typ, module);
+            let inst = Instruction::Store {
+                destination: Expr::Global(value.type_(), v.name.clone()),
+                source: value,
+            };
+            body.instructions.push(inst);
+        }
+        body.update_ids();
         Function::new(
-            function_name,
-            &f.parameters,
-            Type::from_ast(&f.ret_typ, module),
-            body,
+            "elz.init".to_string(),
+            &vec![],
+            Type::Void,
+            Some(body),
             module,
         )
     }
+    /// enum_constructor generates the function constructs a variant of enum, it stores the tag
+    /// and fields of variant
+    pub(crate) fn enum_constructor(
+        enum_name: &String,
+        variant: &ast::EnumVariant,
+        module: &mut Module,
+    ) -> Function {
+        let name = format!("{}::{}", enum_name, variant.name);
+        let enum_type = module.lookup_type(enum_name).clone();
+        let mut body = Body {
+            instructions: vec![],
+            variables: BTreeMap::new(),
+            ret_typ: enum_type.clone(),
+        };
+        let object = body.allocate(&enum_type);
+        let EnumVariant { tag, payload } = module.enum_variants[&name].clone();
+        let tag_id = ID::new();
+        body.instructions.push(Instruction::GEP {
+            id: tag_id.clone(),
+            load_from: object.clone(),
+            indices: vec![0, 0],
+        });
+        body.instructions.push(Instruction::Store {
+            source: Expr::Int(Type::Int(64), tag),
+            destination: Expr::local_id(Type::Int(64), tag_id),
+        });
+        if let Some(payload_type) = payload {
+            let payload = body.enum_payload(object.clone(), payload_type, module);
+            for (i, field) in variant.fields.iter().enumerate() {
+                let field_id = ID::new();
+                body.instructions.push(Instruction::GEP {
+                    id: field_id.clone(),
+                    load_from: payload.clone(),
+                    indices: vec![0, i as u64],
+                });
+                let typ = Type::from_ast(&field.typ, module);
+                body.instructions.push(Instruction::Store {
+                    source: Expr::Identifier(typ.clone(), field.name.clone()),
+                    destination: Expr::local_id(typ, field_id),
+                });
+            }
+        }
+        body.instructions.push(Instruction::Return(Some(object)));
+        body.update_ids();
+        Function::new(name, &variant.fields, enum_type, Some(body), module)
+    }
+    /// closure_adapter generates the code of closure made from function `name`, it takes the
+    /// environment like lambdas do, but ignores it
+    fn closure_adapter(name: &str, code: &str, typ: &Type, module: &mut Module) -> Function {
+        let (parameter_types, ret_typ) = match typ {
+            Type::Function {
+                parameters,
+                ret_typ,
+            } => (parameters, ret_typ.deref().clone()),
+            _ => unreachable!("closure must have function type"),
+        };
+        let mut parameters = vec![("env".to_string(), Type::env())];
+        for (i, typ) in parameter_types.iter().enumerate() {
+            parameters.push((format!("p{}", i), typ.clone()));
+        }
+        let mut body = Body {
+            instructions: vec![],
+            variables: BTreeMap::new(),
+            ret_typ: ret_typ.clone(),
+        };
+        let args_expr = parameters[1..]
+            .iter()
+            .map(|(name, typ)| Expr::Identifier(typ.clone(), name.clone()))
+            .collect();
+        let v = body.call_function(module, name, args_expr);
+        if ret_typ != Type::Void {
+            body.instructions.push(Instruction::Return(Some(v)));
+        }
+        body.update_ids();
+        Function {
+            name: function_name(code),
+            parameters,
+            ret_typ,
+            variadic: false,
+            body: Some(body),
+        }
+    }
     fn new(
         name: String,
         parsed_params: &Vec<Parameter>,
@@ -328,9 +839,10 @@ impl Function {
             .collect();
         Function {
             // function name need @, e.g. @main
-            name: format!("@{}", name),
+            name: function_name(&name),
             parameters,
             ret_typ,
+            variadic: false,
             body,
         }
     }
@@ -340,6 +852,8 @@ impl Function {
 pub(crate) struct Variable {
     pub(crate) name: GlobalName,
     pub(crate) expr: Expr,
+    // an external variable is defined by linked library, its `expr` only gives the type
+    pub(crate) external: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -353,12 +867,20 @@ impl Variable {
         Variable {
             name: GlobalName::String(format!("@{}", name)),
             expr,
+            external: false,
+        }
+    }
+    pub(crate) fn external(name: String, typ: Type) -> Variable {
+        Variable {
+            external: true,
+            ..Variable::new(name, Expr::Zero(typ))
         }
     }
     pub(crate) fn from_id(id: Rc<RefCell<ID>>, expr: Expr) -> Variable {
         Variable {
             name: GlobalName::ID(id),
             expr,
+            external: false,
         }
     }
 }
@@ -367,11 +889,32 @@ impl Variable {
 pub(crate) enum Type {
     Void,
     Int(usize),
+    // unsigned integer is `iN` in LLVM too, but it's extended and converted differently
+    UInt(usize),
     Float(usize),
     Pointer(Rc<Type>),
-    Array { len: usize, element_type: Rc<Type> },
-    Struct { name: String, fields: Vec<Field> },
+    Array {
+        len: usize,
+        element_type: Rc<Type>,
+    },
+    // a class is a pointer to named struct `%name*`, a struct without name is a tuple, which is
+    // passed by value, e.g. `{ i64, %string* }` for `(int, string)`
+    Struct {
+        name: String,
+        fields: Vec<Field>,
+    },
     Named(String),
+    // value of function type, a closure `{ code, environment }`, the code takes the environment as
+    // the first parameter, e.g. `{ i64 (i8*, i64, i64)*, i8* }` for `(int, int): int`
+    Function {
+        parameters: Vec<Type>,
+        ret_typ: Rc<Type>,
+    },
+    // pointer to function, e.g. `i64 (i64, i64)*`
+    FunctionPointer {
+        parameters: Vec<Type>,
+        ret_typ: Rc<Type>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -382,14 +925,87 @@ pub(crate) struct Field {
 
 impl Type {
     pub(crate) fn from_ast(t: &ast::ParsedType, module: &Module) -> Type {
-        use Type::*;
-        match t.name().as_str() {
-            "void" => Void,
-            "int" => Int(64),
-            "f64" => Float(64),
-            "bool" => Int(1),
-            "_c_string" => Pointer(Int(8).into()),
-            name => module.lookup_type(&name.to_string()).clone(),
+        match t {
+            ast::ParsedType::FunctionType {
+                parameters,
+                ret_typ,
+            } => Type::Function {
+                parameters: parameters
+                    .iter()
+                    .map(|p| Type::from_ast(p, module))
+                    .collect(),
+                ret_typ: Type::from_ast(ret_typ, module).into(),
+            },
+            ast::ParsedType::TupleType(elements) => {
+                Type::tuple(elements.iter().map(|e| Type::from_ast(e, module)).collect())
+            }
+            _ => module.lookup_type(&t.name()).clone(),
+        }
+    }
+
+    /// tuple returns the anonymous struct of tuple, its fields are named by index
+    fn tuple(elements: Vec<Type>) -> Type {
+        Type::Struct {
+            name: String::new(),
+            fields: elements
+                .into_iter()
+                .enumerate()
+                .map(|(i, typ)| Field {
+                    name: i.to_string(),
+                    typ: typ.into(),
+                })
+                .collect(),
+        }
+    }
+
+    /// element_types returns types of elements of tuple type
+    fn element_types(&self) -> Vec<Type> {
+        match self {
+            Type::Struct { name, fields } if name.is_empty() => {
+                fields.iter().map(|f| f.typ.as_ref().clone()).collect()
+            }
+            _ => unreachable!("`{:?}` is not a tuple type", self),
+        }
+    }
+
+    /// env is the type of environment of closure, the code casts it to the struct of captured
+    /// values
+    fn env() -> Type {
+        Type::Pointer(Type::Int(8).into())
+    }
+
+    /// code_type returns the type of code of closure type
+    pub(crate) fn code_type(&self) -> Type {
+        match self {
+            Type::Function {
+                parameters,
+                ret_typ,
+            } => {
+                let mut code_parameters = vec![Type::env()];
+                code_parameters.extend(parameters.iter().cloned());
+                Type::FunctionPointer {
+                    parameters: code_parameters,
+                    ret_typ: ret_typ.clone(),
+                }
+            }
+            _ => unreachable!("`{:?}` is not a function type", self),
+        }
+    }
+
+    /// name returns the name of a class type, other types have no name
+    fn name(&self) -> &str {
+        match self {
+            Type::Struct { name, .. } | Type::Named(name) => name.as_str(),
+            _ => "",
+        }
+    }
+
+    /// is_integer reports whether the type is an integer type, `i1` is `bool` so it's excluded
+    fn is_integer(&self) -> bool {
+        match self {
+            Type::Int(size) => *size > 1,
+            Type::UInt(_) => true,
+            _ => false,
         }
     }
 
@@ -402,11 +1018,42 @@ impl Type {
         }
     }
 
+    /// layout returns the size and alignment in bytes of a value of the type as LLVM lays it out
+    /// on 64-bit targets, a class or enum is a pointer
+    fn layout(&self) -> (usize, usize) {
+        use Type::*;
+        match self {
+            Void => (0, 1),
+            Int(bits) | UInt(bits) | Float(bits) => {
+                let bytes = bits.div_ceil(8).next_power_of_two();
+                (bytes, bytes.min(8))
+            }
+            Pointer(..) | FunctionPointer { .. } => (8, 8),
+            Struct { name, .. } if !name.is_empty() => (8, 8),
+            Function { .. } => (16, 8),
+            Array { len, element_type } => {
+                let (size, align) = element_type.layout();
+                (len * size, align)
+            }
+            Struct { fields, .. } => {
+                let (mut size, mut align): (usize, usize) = (0, 1);
+                for field in fields {
+                    let (field_size, field_align) = field.typ.layout();
+                    size = size.next_multiple_of(field_align) + field_size;
+                    align = align.max(field_align);
+                }
+                (size.next_multiple_of(align), align)
+            }
+            Named(name) => unreachable!("layout of `%{}` is unknown", name),
+        }
+    }
+
     pub(crate) fn size(&self) -> usize {
         use Type::*;
         match self {
-            Int(size) | Float(size) => *size,
-            Pointer(..) => 64,
+            Int(size) | UInt(size) | Float(size) => *size,
+            Pointer(..) | FunctionPointer { .. } => 64,
+            Function { .. } => 128,
             Array { len, element_type } => len * element_type.size(),
             Struct { fields, .. } => {
                 let mut size = 0;
@@ -431,10 +1078,7 @@ impl Body {
                     Expr::CString(string_literal.clone()),
                 ));
                 let str_load_id = ID::new();
-                let array_type = Type::Array {
-                    len: string_literal.len(),
-                    element_type: Type::Int(8).into(),
-                };
+                let array_type = Expr::CString(string_literal.clone()).type_();
                 let inst = Instruction::GEP {
                     id: str_load_id.clone(),
                     load_from: Expr::global_id(Type::Pointer(array_type.into()), str_literal_id),
@@ -442,32 +1086,11 @@ impl Body {
                 };
                 self.instructions.push(inst);
                 let ptr_to_str = Expr::local_id(Type::Pointer(Type::Int(8).into()), str_load_id);
-                let id = ID::new();
-                let ret_type = module.lookup_type(&"string".to_string());
-                let inst = Instruction::FunctionCall {
-                    id: id.clone(),
-                    func_name: format!("@\"string::new\""),
-                    ret_type: ret_type.clone().into(),
-                    args_expr: vec![ptr_to_str],
-                };
-                self.instructions.push(inst);
-                Expr::local_id(ret_type.clone(), id)
+                self.call_function(module, "string::new", vec![ptr_to_str])
             }
             ClassConstruction(class_name, field_inits) => {
-                let alloca_id = ID::new();
                 let class_type = module.lookup_type(class_name).clone();
-                let inst = Instruction::Malloca {
-                    id: alloca_id.clone(),
-                    typ: class_type.clone(),
-                };
-                self.instructions.push(inst);
-                let bitcast_id = ID::new();
-                let inst = Instruction::BitCast {
-                    id: bitcast_id.clone(),
-                    from_id: alloca_id,
-                    target_type: class_type.clone(),
-                };
-                self.instructions.push(inst);
+                let object = self.allocate(&class_type);
 
                 // store value into field
                 let fields =
@@ -480,7 +1103,7 @@ impl Body {
                     let gep_id = ID::new();
                     let inst = Instruction::GEP {
                         id: gep_id.clone(),
-                        load_from: Expr::local_id(class_type.clone(), bitcast_id.clone()),
+                        load_from: object.clone(),
                         indices: vec![0, i as u64],
                     };
                     self.instructions.push(inst);
@@ -491,123 +1114,803 @@ impl Body {
                         )
                         .as_str(),
                     );
-                    let expr = self.expr_from_ast(init_value, module);
+                    let expr = self.expr_from_ast_as(init_value, &field.typ, module);
                     let inst = Instruction::Store {
+                        destination: Expr::local_id(expr.type_(), gep_id),
                         source: expr,
-                        destination: gep_id,
                     };
                     self.instructions.push(inst);
                 }
 
-                Expr::local_id(class_type, bitcast_id)
+                object
             }
             MemberAccess(from, access) => {
                 let v = self.expr_from_ast(from, module);
-                let typ = if let Type::Named(name) = v.type_() {
-                    module.lookup_type(&name).clone()
-                } else {
-                    v.type_()
+                self.load_field(v, access, module)
+            }
+            Binary(l, r, op) => {
+                // integer literal takes the type of another side, e.g. `x == 1` for `x: u8`
+                let (lhs, rhs) = match &l.value {
+                    Int(_) => {
+                        let rhs = self.expr_from_ast(r, module);
+                        (self.expr_from_ast_as(l, &rhs.type_(), module), rhs)
+                    }
+                    _ => {
+                        let lhs = self.expr_from_ast(l, module);
+                        let rhs = self.expr_from_ast_as(r, &lhs.type_(), module);
+                        (lhs, rhs)
+                    }
+                };
+                self.binary(lhs, rhs, op, module)
+            }
+            Match(value, arms) => self.match_expr(value, arms, None, module),
+            If {
+                clauses,
+                else_block,
+            } => self.if_expr(clauses, else_block, None, module),
+            Lambda {
+                parameters,
+                ret_typ,
+                body,
+            } => self.lambda(parameters, ret_typ, body, module),
+            Tuple(elements) => {
+                let values: Vec<Expr> = elements
+                    .iter()
+                    .map(|e| self.expr_from_ast(e, module))
+                    .collect();
+                self.tuple(values)
+            }
+            TupleAccess(tuple, index) => {
+                let value = self.expr_from_ast(tuple, module);
+                let typ = value.type_().element_types()[*index].clone();
+                self.extract_value(value, *index as u64, typ)
+            }
+            Cast(e, typ) => {
+                let value = self.expr_from_ast(e, module);
+                let target_type = Type::from_ast(typ, module);
+                self.cast(value, target_type)
+            }
+            FuncCall(f, args) => {
+                let (name, mut args_expr) = match &f.value {
+                    // method call `foo.bar(1)` is `Foo::bar(foo, 1)`
+                    MemberAccess(from, method) => {
+                        let object = self.expr_from_ast(from, module);
+                        // field of function type, e.g. `handler.on_click(1)`
+                        if module.has_field(&object.type_(), method) {
+                            let callee = self.load_field(object, method, module);
+                            return self.indirect_call(callee, args, module);
+                        }
+                        let class_name = module.class_name(&object.type_());
+                        (format!("{}::{}", class_name, method), vec![object])
+                    }
+                    Identifier(name) if self.is_function_name(name, module) => {
+                        (name.clone(), vec![])
+                    }
+                    // value of function type, e.g. a parameter `f: (int): int`
+                    _ => {
+                        let callee = self.expr_from_ast(f, module);
+                        return self.indirect_call(callee, args, module);
+                    }
                 };
-                match typ {
-                    Type::Struct { fields, .. } => {
-                        let mut i = 0; // get index of field
-                        for field in &fields {
-                            if &field.name == access {
-                                break;
-                            } else {
-                                i += 1;
-                            }
+                let mut arguments = self.arguments(&name, args, module);
+                args_expr.append(&mut arguments);
+                self.call_function(module, name.as_str(), args_expr)
+            }
+            Interpolation(e) => {
+                let v = self.expr_from_ast(e, module);
+                let class_name = module.class_name(&v.type_());
+                if class_name == "string" {
+                    v
+                } else {
+                    let method = format!("{}::to_string", class_name);
+                    self.call_function(module, method.as_str(), vec![v])
+                }
+            }
+            Identifier(name) => match self.local_value(name) {
+                Some(value) => value,
+                None => {
+                    // inside of method, field can be used without `self.`
+                    if let Some(object) = self.self_object() {
+                        if module.has_field(&object.type_(), name) {
+                            return self.load_field(object, name, module);
                         }
-                        let result_type = fields[i].typ.deref().clone();
-                        let gep_id = ID::new();
-                        let inst = Instruction::GEP {
-                            id: gep_id.clone(),
-                            load_from: v.clone(),
-                            indices: vec![0, i as u64],
-                        };
-                        self.instructions.push(inst);
+                    }
+                    if let Some(typ) = module.known_variables.get(name).cloned() {
                         let id = ID::new();
                         let inst = Instruction::Load {
                             id: id.clone(),
-                            load_from: Expr::local_id(result_type.clone(), gep_id),
+                            load_from: Expr::Global(typ.clone(), name.clone()),
                         };
                         self.instructions.push(inst);
-                        Expr::local_id(result_type, id)
+                        return Expr::local_id(typ, id);
+                    }
+                    // variant without fields is a value, e.g. `Shape::Empty`
+                    if let Some(EnumVariant { payload: None, .. }) = module.enum_variants.get(name)
+                    {
+                        return self.call_function(module, name, vec![]);
                     }
-                    _ => unreachable!(
-                        "access member on non-class type which unlikely happen: from `{:?}`",
-                        from
-                    ),
+                    module.function_value(name).unwrap_or_else(|| panic!("no variable named: `{}` which unlikely happened, semantic module must have a bug there!", name))
                 }
+            },
+            _ => Expr::from_ast(expr),
+        }
+    }
+}
+
+impl Body {
+    /// expr_from_ast_as is `expr_from_ast` with the type expected by context, it follows
+    /// `TypeEnv::type_of_expr_against` to give integer literal the expected integer type
+    fn expr_from_ast_as(&mut self, expr: &ast::Expr, typ: &Type, module: &mut Module) -> Expr {
+        use ast::ExprVariant::*;
+        match &expr.value {
+            Int(i) if typ.is_integer() => Expr::Int(typ.clone(), *i),
+            Binary(l, r, op @ Operator::Plus) if typ.is_integer() => {
+                let lhs = self.expr_from_ast_as(l, typ, module);
+                let rhs = self.expr_from_ast_as(r, typ, module);
+                self.binary(lhs, rhs, op, module)
             }
-            Binary(lhs, rhs, op) => {
-                let id = ID::new();
-                let lhs = self.expr_from_ast(lhs, module);
-                let rhs = self.expr_from_ast(rhs, module);
-                let result_typ = lhs.type_();
-                let op_name = match op {
-                    Operator::Plus => "add",
+            Match(value, arms) => self.match_expr(value, arms, Some(typ), module),
+            If {
+                clauses,
+                else_block,
+            } => self.if_expr(clauses, else_block, Some(typ), module),
+            Tuple(elements) if matches!(typ, Type::Struct { name, .. } if name.is_empty()) => {
+                let values: Vec<Expr> = elements
+                    .iter()
+                    .zip(typ.element_types())
+                    .map(|(e, typ)| self.expr_from_ast_as(e, &typ, module))
+                    .collect();
+                self.tuple(values)
+            }
+            _ => self.expr_from_ast(expr, module),
+        }
+    }
+    /// match_expr lowers match to a chain of branches, each arm tests its pattern and jumps to
+    /// the next arm if it's not matched. The last arm needs no test since the semantic checker
+    /// ensures arms are exhaustive. Arms store their results into a stack slot, which is loaded
+    /// after all arms.
+    fn match_expr(
+        &mut self,
+        value: &ast::Expr,
+        arms: &[ast::MatchArm],
+        expected: Option<&Type>,
+        module: &mut Module,
+    ) -> Expr {
+        let value = self.expr_from_ast(value, module);
+        // enum is matched by its tag
+        let tag = if arms
+            .iter()
+            .any(|arm| matches!(arm.pattern, Pattern::Variant(..)))
+        {
+            Some(self.load_field(value.clone(), &"tag".to_string(), module))
+        } else {
+            None
+        };
+        let slot_id = ID::new();
+        let slot_index = self.instructions.len();
+        let mut result_type = expected.cloned();
+        let mut result = None;
+        let leave_label = Label::new(ID::new());
+        for (i, arm) in arms.iter().enumerate() {
+            let cond = if i == arms.len() - 1 {
+                None
+            } else {
+                self.pattern_condition(&arm.pattern, &value, &tag, module)
+            };
+            let next_label = match cond {
+                Some(cond) => {
+                    let then_label = Label::new(ID::new());
+                    let next_label = Label::new(ID::new());
+                    self.instructions.push(Instruction::Branch {
+                        cond,
+                        if_true: then_label.clone(),
+                        if_false: next_label.clone(),
+                    });
+                    self.instructions.push(Instruction::Label(then_label));
+                    Some(next_label)
                 }
-                .to_string();
-                let inst = Instruction::BinaryOperation {
-                    id: id.clone(),
-                    op_name,
-                    lhs,
-                    rhs,
-                };
-                self.instructions.push(inst);
-                Expr::local_id(result_typ, id)
+                None => None,
+            };
+            // names bound by pattern are only visible in the arm
+            let variables = self.variables.clone();
+            self.bind_pattern(&arm.pattern, &value, module);
+            let v = self.branch_value(&arm.expr, &mut result_type, module);
+            self.variables = variables;
+            result = Some(self.leave_branch(v, &slot_id, &leave_label));
+            match next_label {
+                Some(label) => self.instructions.push(Instruction::Label(label)),
+                // arms after an arm matches any value are rejected by the semantic checker
+                None => break,
             }
-            FuncCall(f, args) => {
-                let id = self.expr_from_ast(f, module);
-                let name = match id {
-                    Expr::Identifier(_, name) => name,
-                    e => unreachable!("call on a non-function expression: {:#?}", e),
-                };
-                match module.known_functions.get(&name).cloned() {
-                    Some(ret_type) => {
-                        let args_expr: Vec<Expr> = args
-                            .iter()
-                            .map(|arg| self.expr_from_ast(&arg.expr, module))
-                            .collect();
-                        let id = ID::new();
-                        let inst = Instruction::FunctionCall{
-                            id: id.clone(),
-                            func_name: format!("@{}", name),
-                            ret_type: ret_type.clone().into(),
-                            args_expr,
-                        };
-                        self.instructions.push(inst);
-                        Expr::local_id(ret_type.clone(), id)
+        }
+        self.instructions.push(Instruction::Label(leave_label));
+        self.branches_result(slot_index, slot_id, result_type, result)
+    }
+    /// if_expr lowers if expression like if statement, but branches store their values into a
+    /// stack slot, which is loaded after all branches
+    fn if_expr(
+        &mut self,
+        clauses: &Vec<(ast::Expr, ValueBlock)>,
+        else_block: &ValueBlock,
+        expected: Option<&Type>,
+        module: &mut Module,
+    ) -> Expr {
+        let slot_id = ID::new();
+        let slot_index = self.instructions.len();
+        let mut result_type = expected.cloned();
+        let leave_label = Label::new(ID::new());
+        for (cond, then_block) in clauses {
+            let if_then_label = Label::new(ID::new());
+            let else_then_label = Label::new(ID::new());
+            let inst = Instruction::Branch {
+                cond: self.expr_from_ast(cond, module),
+                if_true: if_then_label.clone(),
+                if_false: else_then_label.clone(),
+            };
+            self.instructions.push(inst);
+            self.instructions.push(Instruction::Label(if_then_label));
+            let v = self.value_block(then_block, &mut result_type, module);
+            self.leave_branch(v, &slot_id, &leave_label);
+            self.instructions.push(Instruction::Label(else_then_label));
+        }
+        let v = self.value_block(else_block, &mut result_type, module);
+        let result = self.leave_branch(v, &slot_id, &leave_label);
+        self.instructions.push(Instruction::Label(leave_label));
+        self.branches_result(slot_index, slot_id, result_type, Some(result))
+    }
+    /// value_block generates statements of block, then returns the value of block, variables
+    /// defined in the block are only visible in it
+    fn value_block(
+        &mut self,
+        b: &ValueBlock,
+        result_type: &mut Option<Type>,
+        module: &mut Module,
+    ) -> Expr {
+        let variables = self.variables.clone();
+        self.generate_instructions(&b.block.statements, module);
+        let v = self.branch_value(&b.value, result_type, module);
+        self.variables = variables;
+        v
+    }
+    /// branch_value is the value of a branch, the first branch decides the type of result unless
+    /// the context expects one
+    fn branch_value(
+        &mut self,
+        expr: &ast::Expr,
+        result_type: &mut Option<Type>,
+        module: &mut Module,
+    ) -> Expr {
+        match result_type {
+            Some(typ) => self.expr_from_ast_as(expr, typ, module),
+            None => {
+                let v = self.expr_from_ast(expr, module);
+                *result_type = Some(v.type_());
+                v
+            }
+        }
+    }
+    /// leave_branch stores the value of branch into the stack slot of result, then jumps to the
+    /// end of branches
+    fn leave_branch(&mut self, v: Expr, slot_id: &Rc<RefCell<ID>>, leave: &Rc<Label>) -> Expr {
+        if v.type_() != Type::Void {
+            self.instructions.push(Instruction::Store {
+                source: v.clone(),
+                destination: Expr::local_id(v.type_(), slot_id.clone()),
+            });
+        }
+        self.goto(leave);
+        v
+    }
+    /// branches_result allocates the stack slot before branches at `slot_index`, it's only known
+    /// after branches are generated since the first branch can decide the type, then loads the
+    /// result from the slot
+    fn branches_result(
+        &mut self,
+        slot_index: usize,
+        slot_id: Rc<RefCell<ID>>,
+        result_type: Option<Type>,
+        result: Option<Expr>,
+    ) -> Expr {
+        match result_type {
+            Some(typ) if typ != Type::Void => {
+                self.instructions.insert(
+                    slot_index,
+                    Instruction::Alloca {
+                        id: slot_id.clone(),
+                        typ: typ.clone(),
                     },
-                    None => unreachable!("no function named: `{}` which unlikely happened, semantic module must have a bug there!", name),
-                }
+                );
+                let id = ID::new();
+                self.instructions.push(Instruction::Load {
+                    id: id.clone(),
+                    load_from: Expr::local_id(typ.clone(), slot_id),
+                });
+                Expr::local_id(typ, id)
+            }
+            // void has no value, the result would be dropped
+            _ => result.expect("branches must have a result"),
+        }
+    }
+    /// pattern_condition returns the condition of pattern matches the value, patterns match any
+    /// value have no condition
+    fn pattern_condition(
+        &mut self,
+        pattern: &Pattern,
+        value: &Expr,
+        tag: &Option<Expr>,
+        module: &mut Module,
+    ) -> Option<Expr> {
+        let literal = match pattern {
+            Pattern::Wildcard | Pattern::Binding(_) => return None,
+            Pattern::Int(i) => Expr::Int(value.type_(), *i),
+            Pattern::Bool(b) => Expr::Bool(*b),
+            Pattern::String(s) => {
+                let s = ast::Expr::string(Location::none(), s);
+                self.expr_from_ast(&s, module)
             }
-            Identifier(name) => match self.lookup_variable(name) {
-                Some(local_var) => match local_var {
-                    LocalVariable::Name { name, typ } => {
-                        Expr::Identifier(typ.clone(), name.clone())
+            Pattern::Variant(name, _) => {
+                let variant_tag = module.enum_variants[name].tag;
+                let tag = tag.clone().expect("enum must be matched by its tag");
+                return Some(self.binary(
+                    tag,
+                    Expr::Int(Type::Int(64), variant_tag),
+                    &Operator::Equal,
+                    module,
+                ));
+            }
+        };
+        Some(self.binary(value.clone(), literal, &Operator::Equal, module))
+    }
+    /// bind_pattern defines names bound by pattern as local variables
+    fn bind_pattern(&mut self, pattern: &Pattern, value: &Expr, module: &mut Module) {
+        match pattern {
+            Pattern::Binding(name) => {
+                self.variables
+                    .insert(name.clone(), LocalVariable::Value(value.clone()));
+            }
+            Pattern::Variant(name, bindings) if !bindings.is_empty() => {
+                let payload_type = module.enum_variants[name]
+                    .payload
+                    .clone()
+                    .expect("variant with fields must have payload");
+                let payload = self.enum_payload(value.clone(), payload_type.clone(), module);
+                let fields = module.lookup_fields(&payload_type);
+                for (binding, field) in bindings.iter().zip(fields.iter()) {
+                    if binding != "_" {
+                        let v = self.load_field(payload.clone(), &field.name, module);
+                        self.variables
+                            .insert(binding.clone(), LocalVariable::Value(v));
                     }
-                },
+                }
+            }
+            _ => (),
+        }
+    }
+    /// cast converts a number to another number type, e.g. `x as u8` is `trunc i64 %x to i8`
+    fn cast(&mut self, value: Expr, target_type: Type) -> Expr {
+        use Type::*;
+        let op_name = match (value.type_(), &target_type) {
+            (Int(from) | UInt(from), Int(to) | UInt(to)) if from > *to => "trunc",
+            (Int(from), Int(to) | UInt(to)) if from < *to => "sext",
+            (UInt(from), Int(to) | UInt(to)) if from < *to => "zext",
+            (Int(_), Float(_)) => "sitofp",
+            (UInt(_), Float(_)) => "uitofp",
+            (Float(_), Int(_)) => "fptosi",
+            (Float(_), UInt(_)) => "fptoui",
+            (Float(from), Float(to)) if from > *to => "fptrunc",
+            (Float(from), Float(to)) if from < *to => "fpext",
+            // same size, e.g. `i8` to `u8`, only the type is changed
+            _ => "bitcast",
+        };
+        let id = ID::new();
+        let inst = Instruction::Convert {
+            id: id.clone(),
+            op_name: op_name.to_string(),
+            value,
+            target_type: target_type.clone(),
+        };
+        self.instructions.push(inst);
+        Expr::local_id(target_type, id)
+    }
+    fn binary(&mut self, lhs: Expr, rhs: Expr, op: &Operator, module: &mut Module) -> Expr {
+        let typ = lhs.type_();
+        if typ == *module.lookup_type(&"string".to_string()) {
+            // operators of string are provided by the runtime in prelude
+            let method = match op {
+                Operator::Plus => "string::concat",
+                Operator::Equal => "string::equal",
+            };
+            return self.call_function(module, method, vec![lhs, rhs]);
+        }
+        let (op_name, result_typ) = match (op, &typ) {
+            (Operator::Plus, Type::Float(..)) => ("fadd", typ.clone()),
+            (Operator::Plus, _) => ("add", typ.clone()),
+            (Operator::Equal, Type::Float(..)) => ("fcmp oeq", Type::Int(1)),
+            (Operator::Equal, _) => ("icmp eq", Type::Int(1)),
+        };
+        let id = ID::new();
+        let inst = Instruction::BinaryOperation {
+            id: id.clone(),
+            op_name: op_name.to_string(),
+            lhs,
+            rhs,
+        };
+        self.instructions.push(inst);
+        Expr::local_id(result_typ, id)
+    }
+    /// allocate allocates an object of struct type on heap
+    fn allocate(&mut self, typ: &Type) -> Expr {
+        let alloca_id = ID::new();
+        let inst = Instruction::Malloca {
+            id: alloca_id.clone(),
+            typ: typ.clone(),
+        };
+        self.instructions.push(inst);
+        let bitcast_id = ID::new();
+        let inst = Instruction::BitCast {
+            id: bitcast_id.clone(),
+            from_id: alloca_id,
+            target_type: typ.clone(),
+        };
+        self.instructions.push(inst);
+        Expr::local_id(typ.clone(), bitcast_id)
+    }
+    /// enum_payload gets the payload of enum object as the payload struct of a variant
+    fn enum_payload(&mut self, object: Expr, payload_type: Type, module: &Module) -> Expr {
+        let words = module.lookup_fields(&object.type_())[1].typ.deref().clone();
+        let gep_id = ID::new();
+        let inst = Instruction::GEP {
+            id: gep_id.clone(),
+            load_from: object,
+            indices: vec![0, 1],
+        };
+        self.instructions.push(inst);
+        let id = ID::new();
+        let inst = Instruction::Convert {
+            id: id.clone(),
+            op_name: "bitcast".to_string(),
+            value: Expr::local_id(Type::Pointer(words.into()), gep_id),
+            target_type: payload_type.clone(),
+        };
+        self.instructions.push(inst);
+        Expr::local_id(payload_type, id)
+    }
+    fn load_field(&mut self, object: Expr, field_name: &String, module: &Module) -> Expr {
+        let fields = module.lookup_fields(&object.type_());
+        let i = fields
+            .iter()
+            .position(|field| &field.name == field_name)
+            .unwrap_or_else(|| panic!("no field named: `{}`", field_name));
+        let result_type = fields[i].typ.deref().clone();
+        let gep_id = ID::new();
+        let inst = Instruction::GEP {
+            id: gep_id.clone(),
+            load_from: object,
+            indices: vec![0, i as u64],
+        };
+        self.instructions.push(inst);
+        let id = ID::new();
+        let inst = Instruction::Load {
+            id: id.clone(),
+            load_from: Expr::local_id(result_type.clone(), gep_id),
+        };
+        self.instructions.push(inst);
+        Expr::local_id(result_type, id)
+    }
+    /// is_function_name reports whether name refers to a function, local variables and fields
+    /// of `self` hide functions
+    fn is_function_name(&self, name: &String, module: &Module) -> bool {
+        if self.lookup_variable(name).is_some() {
+            return false;
+        }
+        if let Some(object) = self.self_object() {
+            if module.has_field(&object.type_(), name) {
+                return false;
+            }
+        }
+        module.known_functions.contains_key(name)
+    }
+    /// indirect_call calls a value of function type, the code of closure is called with its
+    /// environment
+    fn indirect_call(&mut self, callee: Expr, args: &[Argument], module: &mut Module) -> Expr {
+        let typ = callee.type_();
+        let (parameters, ret_typ) = match &typ {
+            Type::Function {
+                parameters,
+                ret_typ,
+            } => (parameters, ret_typ),
+            typ => unreachable!("call on non-function type: `{:?}`", typ),
+        };
+        let code = self.extract_value(callee.clone(), 0, typ.code_type());
+        let env = self.extract_value(callee, 1, Type::env());
+        let mut args_expr = vec![env];
+        for (arg, typ) in args.iter().zip(parameters.iter()) {
+            let arg_expr = self.expr_from_ast_as(&arg.expr, typ, module);
+            args_expr.push(arg_expr);
+        }
+        let id = ID::new();
+        let inst = Instruction::FunctionCall {
+            id: id.clone(),
+            callee: code,
+            ret_type: ret_typ.deref().clone().into(),
+            variadic_parameters: None,
+            args_expr,
+        };
+        self.instructions.push(inst);
+        Expr::local_id(ret_typ.deref().clone(), id)
+    }
+    /// arguments generates arguments of call to function `name` in the order of its parameters.
+    /// Arguments are evaluated in source order, then a named argument is moved to its parameter,
+    /// and an omitted argument is the default value of parameter, which is evaluated after given
+    /// arguments. Extra arguments of variadic function are at the end.
+    fn arguments(&mut self, name: &str, args: &Vec<Argument>, module: &mut Module) -> Vec<Expr> {
+        let parameters = module.parameters.get(name).cloned().unwrap_or_default();
+        let parameter_types = module
+            .parameter_types
+            .get(name)
+            .cloned()
+            .unwrap_or_default();
+        let mut given: Vec<Option<Expr>> = vec![None; parameters.len()];
+        let mut extra = vec![];
+        let mut next = 0;
+        for arg in args {
+            let i = match &arg.name {
+                Some(name) => parameters
+                    .iter()
+                    .position(|p| &p.name == name)
+                    .expect("semantic checker ensures the parameter exists"),
                 None => {
-                    let ret_type = module.known_functions.get(name).expect(format!("no variable named: `{}` which unlikely happened, semantic module must have a bug there!", name).as_str());
-                    Expr::Identifier(ret_type.clone(), name.clone())
+                    next += 1;
+                    next - 1
                 }
-            },
-            _ => Expr::from_ast(expr),
+            };
+            match (given.get_mut(i), parameter_types.get(i)) {
+                (Some(slot), Some(typ)) => {
+                    *slot = Some(self.expr_from_ast_as(&arg.expr, typ, module))
+                }
+                _ => extra.push(self.expr_from_ast(&arg.expr, module)),
+            }
+        }
+        let mut args_expr = vec![];
+        for ((arg, p), typ) in given
+            .into_iter()
+            .zip(parameters.iter())
+            .zip(parameter_types.iter())
+        {
+            let arg_expr = match arg {
+                Some(e) => e,
+                None => {
+                    // default value only refers to definitions of module, local variables of the
+                    // caller must not hide them
+                    let variables = std::mem::take(&mut self.variables);
+                    let default = p
+                        .default
+                        .as_ref()
+                        .expect("semantic checker ensures omitted argument has default value");
+                    let v = self.expr_from_ast_as(default, typ, module);
+                    self.variables = variables;
+                    v
+                }
+            };
+            args_expr.push(arg_expr);
+        }
+        args_expr.extend(extra);
+        args_expr
+    }
+    /// tuple inserts values one by one into the anonymous struct of tuple
+    fn tuple(&mut self, values: Vec<Expr>) -> Expr {
+        let typ = Type::tuple(values.iter().map(|v| v.type_()).collect());
+        let mut aggregate = Expr::Zero(typ.clone());
+        for (index, element) in values.into_iter().enumerate() {
+            let id = ID::new();
+            self.instructions.push(Instruction::InsertValue {
+                id: id.clone(),
+                aggregate,
+                element,
+                index: index as u64,
+            });
+            aggregate = Expr::local_id(typ.clone(), id);
+        }
+        aggregate
+    }
+    fn extract_value(&mut self, aggregate: Expr, index: u64, typ: Type) -> Expr {
+        let id = ID::new();
+        self.instructions.push(Instruction::ExtractValue {
+            id: id.clone(),
+            aggregate,
+            index,
+        });
+        Expr::local_id(typ, id)
+    }
+    /// lambda generates the body of lambda as function `elz.lambda.N`, which takes the
+    /// environment of closure as the first parameter. Local variables used by the body are
+    /// captured, their values are copied into the environment allocated on heap, so the closure
+    /// can outlive the scope. A lambda captures nothing has no environment.
+    fn lambda(
+        &mut self,
+        parameters: &Vec<Parameter>,
+        ret_typ: &ParsedType,
+        body: &ast::Expr,
+        module: &mut Module,
+    ) -> Expr {
+        let name = format!("elz.lambda.{}", module.lambda_count);
+        module.lambda_count += 1;
+        let captures = self.captures(parameters, body, module);
+        let env_type = Type::Struct {
+            name: format!("{}.env", name),
+            fields: captures
+                .iter()
+                .map(|(name, v)| Field {
+                    name: name.clone(),
+                    typ: v.type_().into(),
+                })
+                .collect(),
+        };
+        let ret_typ = Type::from_ast(ret_typ, module);
+        let mut code_parameters = vec![("env".to_string(), Type::env())];
+        let mut lambda_body = Body {
+            instructions: vec![],
+            variables: BTreeMap::new(),
+            ret_typ: ret_typ.clone(),
+        };
+        for p in parameters {
+            let typ = Type::from_ast(&p.typ, module);
+            code_parameters.push((p.name.clone(), typ.clone()));
+            lambda_body.variables.insert(
+                p.name.clone(),
+                LocalVariable::from_name(p.name.clone(), typ),
+            );
+        }
+        if !captures.is_empty() {
+            module.types.push(env_type.clone());
+            let env = Expr::Identifier(Type::env(), "env".to_string());
+            let env = lambda_body.cast(env, env_type.clone());
+            for (name, _) in &captures {
+                let v = lambda_body.load_field(env.clone(), name, module);
+                lambda_body
+                    .variables
+                    .insert(name.clone(), LocalVariable::Value(v));
+            }
+        }
+        let v = lambda_body.expr_from_ast_as(body, &ret_typ, module);
+        if ret_typ != Type::Void {
+            lambda_body.instructions.push(Instruction::Return(Some(v)));
+        }
+        lambda_body.update_ids();
+        let typ = Type::Function {
+            parameters: code_parameters[1..]
+                .iter()
+                .map(|(_, typ)| typ.clone())
+                .collect(),
+            ret_typ: ret_typ.clone().into(),
+        };
+        module.push_function(Function {
+            name: function_name(&name),
+            parameters: code_parameters,
+            ret_typ,
+            variadic: false,
+            body: Some(lambda_body),
+        });
+        let closure = Expr::closure(typ.clone(), &name, Expr::Zero(Type::env()));
+        if captures.is_empty() {
+            return closure;
+        }
+        let env_id = ID::new();
+        self.instructions.push(Instruction::Malloca {
+            id: env_id.clone(),
+            typ: env_type.clone(),
+        });
+        let env = Expr::local_id(Type::env(), env_id);
+        let object = self.cast(env.clone(), env_type);
+        for (i, (_, v)) in captures.into_iter().enumerate() {
+            let gep_id = ID::new();
+            self.instructions.push(Instruction::GEP {
+                id: gep_id.clone(),
+                load_from: object.clone(),
+                indices: vec![0, i as u64],
+            });
+            self.instructions.push(Instruction::Store {
+                destination: Expr::local_id(v.type_(), gep_id),
+                source: v,
+            });
+        }
+        let id = ID::new();
+        self.instructions.push(Instruction::InsertValue {
+            id: id.clone(),
+            aggregate: closure,
+            element: env,
+            index: 1,
+        });
+        Expr::local_id(typ, id)
+    }
+    /// captures returns local variables used by the body of lambda with their values, a field of
+    /// `self` used without `self.` captures `self`
+    fn captures(
+        &self,
+        parameters: &[Parameter],
+        body: &ast::Expr,
+        module: &Module,
+    ) -> Vec<(String, Expr)> {
+        let mut captures: Vec<(String, Expr)> = vec![];
+        for name in free_names(parameters, body) {
+            let name = match self.self_object() {
+                Some(object)
+                    if self.lookup_variable(&name).is_none()
+                        && module.has_field(&object.type_(), &name) =>
+                {
+                    "self".to_string()
+                }
+                _ => name,
+            };
+            if captures.iter().any(|(captured, _)| captured == &name) {
+                continue;
+            }
+            if let Some(v) = self.local_value(&name) {
+                captures.push((name, v));
+            }
+        }
+        captures
+    }
+    /// local_value returns the value of local variable
+    fn local_value(&self, name: &String) -> Option<Expr> {
+        match self.lookup_variable(name)? {
+            LocalVariable::Name { name, typ } => Some(Expr::Identifier(typ.clone(), name.clone())),
+            LocalVariable::Value(value) => Some(value.clone()),
+        }
+    }
+    /// self_object returns `self` inside of method
+    fn self_object(&self) -> Option<Expr> {
+        self.local_value(&"self".to_string())
+    }
+    fn call_function(&mut self, module: &Module, name: &str, args_expr: Vec<Expr>) -> Expr {
+        if let Some(op_name) = module.builtin_functions.get(name) {
+            let target_type = module.known_functions[name].clone();
+            let id = ID::new();
+            let inst = Instruction::Convert {
+                id: id.clone(),
+                op_name: op_name.clone(),
+                value: args_expr[0].clone(),
+                target_type: target_type.clone(),
+            };
+            self.instructions.push(inst);
+            return Expr::local_id(target_type, id);
+        }
+        match module.known_functions.get(name).cloned() {
+            Some(ret_type) => {
+                let id = ID::new();
+                let inst = Instruction::FunctionCall {
+                    id: id.clone(),
+                    callee: module.function(name),
+                    ret_type: ret_type.clone().into(),
+                    variadic_parameters: module.variadic_functions.get(name).cloned(),
+                    args_expr,
+                };
+                self.instructions.push(inst);
+                Expr::local_id(ret_type, id)
+            }
+            None => unreachable!("no function named: `{}` which unlikely happened, semantic module must have a bug there!", name),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum Expr {
-    I64(i64),
+    /// integer literal, it's `i64` unless the context expects another integer type
+    Int(Type, u64),
     F64(f64),
     Bool(bool),
     CString(String),
     Identifier(Type, String),
     LocalIdentifier(Type, Rc<RefCell<ID>>),
     GlobalIdentifier(Type, Rc<RefCell<ID>>),
+    /// global variable `@x`
+    Global(Type, String),
+    /// zero value of type, e.g. `0` for `i64` and `null` for pointer
+    Zero(Type),
+    /// function `@foo`
+    Function(Type, String),
+    /// constant struct, e.g. `{ i64 (i8*, i64)* @foo.closure, i8* zeroinitializer }`
+    Struct(Type, Vec<Expr>),
 }
 
 impl Expr {
@@ -615,7 +1918,7 @@ impl Expr {
         use ExprVariant::*;
         match &a.value {
             F64(f) => Expr::F64(*f),
-            Int(i) => Expr::I64(*i),
+            Int(i) => Expr::Int(Type::Int(64), *i),
             Bool(b) => Expr::Bool(*b),
             String(s) => Expr::CString(s.clone()),
             expr => unimplemented!("codegen: expr {:#?}", expr),
@@ -623,19 +1926,45 @@ impl Expr {
     }
     pub(crate) fn type_(&self) -> Type {
         match self {
-            Expr::I64(..) => Type::Int(64),
+            Expr::Int(typ, ..) => typ.clone(),
             Expr::F64(..) => Type::Float(64),
             Expr::Bool(..) => Type::Int(1),
+            // string literal is ended with `\00`, length is counted in bytes of UTF-8
             Expr::CString(s) => Type::Array {
-                len: s.len(),
+                len: s.len() + 1,
                 element_type: Type::Int(8).into(),
             },
             Expr::Identifier(typ, ..) => typ.clone(),
             Expr::LocalIdentifier(typ, ..) => typ.clone(),
             Expr::GlobalIdentifier(typ, ..) => typ.clone(),
+            Expr::Global(typ, ..) => typ.clone(),
+            Expr::Zero(typ) => typ.clone(),
+            Expr::Function(typ, ..) => typ.clone(),
+            Expr::Struct(typ, ..) => typ.clone(),
         }
     }
 
+    /// typed_as gives integer constant the integer type `typ`, elements of constant tuple are
+    /// typed by the element types, other expressions are unchanged
+    fn typed_as(self, typ: &Type) -> Expr {
+        match self {
+            Expr::Int(_, i) if typ.is_integer() => Expr::Int(typ.clone(), i),
+            Expr::Struct(Type::Struct { name, .. }, elements) if name.is_empty() => {
+                let elements = elements
+                    .into_iter()
+                    .zip(typ.element_types())
+                    .map(|(e, typ)| e.typed_as(&typ))
+                    .collect();
+                Expr::Struct(typ.clone(), elements)
+            }
+            e => e,
+        }
+    }
+    /// closure returns the closure of function type `typ` with code `code` and environment `env`
+    fn closure(typ: Type, code: &str, env: Expr) -> Expr {
+        let code = Expr::Function(typ.code_type(), code.to_string());
+        Expr::Struct(typ, vec![code, env])
+    }
     fn local_id(typ: Type, id: Rc<RefCell<ID>>) -> Expr {
         Expr::LocalIdentifier(typ, id)
     }
@@ -7,19 +7,36 @@ pub trait LLVMValue {
 impl LLVMValue for ir::Module {
     fn llvm_represent(&self) -> String {
         let mut s = String::new();
-        for (_, t) in &self.types {
-            s.push_str(t.llvm_def().as_str());
+        for definition in self.definitions() {
+            s.push_str(definition.as_str());
             s.push_str("\n");
         }
+        s
+    }
+}
+
+impl ir::Module {
+    /// definitions returns types, variables and functions of module in LLVM IR
+    fn definitions(&self) -> Vec<String> {
+        let mut definitions = vec![];
+        for t in &self.types {
+            definitions.push(t.llvm_def());
+        }
         for v in &self.variables {
-            s.push_str(v.llvm_represent().as_str());
-            s.push_str("\n");
+            definitions.push(v.llvm_represent());
         }
-        for (_, f) in &self.functions {
-            s.push_str(f.llvm_represent().as_str());
-            s.push_str("\n");
+        for f in &self.functions {
+            definitions.push(f.llvm_represent());
         }
-        s
+        if let Some(initializer) = &self.initializer {
+            definitions.push(initializer.llvm_represent());
+            // run initializer before `main`
+            definitions.push(format!(
+                "@llvm.global_ctors = appending global [1 x {{ i32, void ()*, i8* }}] [{{ i32, void ()*, i8* }} {{ i32 65535, void ()* {}, i8* null }}]",
+                initializer.name
+            ));
+        }
+        definitions
     }
 }
 
@@ -111,8 +128,9 @@ impl LLVMValue for ir::Instruction {
             }
             FunctionCall {
                 id,
-                func_name,
+                callee,
                 ret_type,
+                variadic_parameters,
                 args_expr,
             } => {
                 let mut s = String::new();
@@ -121,7 +139,15 @@ impl LLVMValue for ir::Instruction {
                 }
                 s.push_str("call ");
                 s.push_str(format!("{} ", ret_type.llvm_represent()).as_str());
-                s.push_str(func_name.as_str());
+                // call to variadic function must provide the function type, e.g. `i32 (i8*, ...)`
+                if let Some(parameters) = variadic_parameters {
+                    s.push('(');
+                    for typ in parameters {
+                        s.push_str(format!("{}, ", typ.llvm_represent()).as_str());
+                    }
+                    s.push_str("...) ");
+                }
+                s.push_str(callee.llvm_represent().as_str());
                 s.push_str("(");
                 for (index, arg_expr) in args_expr.iter().enumerate() {
                     s.push_str(arg_expr.type_().llvm_represent().as_str());
@@ -134,6 +160,11 @@ impl LLVMValue for ir::Instruction {
                 s.push_str(")");
                 s
             }
+            Alloca { id, typ } => format!(
+                "%{id} = alloca {typ}",
+                id = id.borrow(),
+                typ = typ.llvm_represent()
+            ),
             Malloca { id, typ } => format!(
                 "%{id} = call i8* @malloc(i64 {type_size})",
                 id = id.borrow(),
@@ -149,15 +180,53 @@ impl LLVMValue for ir::Instruction {
                 from = from_id.borrow(),
                 target_type = target_type.llvm_represent()
             ),
+            Convert {
+                id,
+                op_name,
+                value,
+                target_type,
+            } => format!(
+                "%{id} = {op_name} {typ} {value} to {target_type}",
+                id = id.borrow(),
+                op_name = op_name,
+                typ = value.type_().llvm_represent(),
+                value = value.llvm_represent(),
+                target_type = target_type.llvm_represent()
+            ),
+            ExtractValue {
+                id,
+                aggregate,
+                index,
+            } => format!(
+                "%{id} = extractvalue {typ} {aggregate}, {index}",
+                id = id.borrow(),
+                typ = aggregate.type_().llvm_represent(),
+                aggregate = aggregate.llvm_represent(),
+                index = index
+            ),
+            InsertValue {
+                id,
+                aggregate,
+                element,
+                index,
+            } => format!(
+                "%{id} = insertvalue {typ} {aggregate}, {element_type} {element}, {index}",
+                id = id.borrow(),
+                typ = aggregate.type_().llvm_represent(),
+                aggregate = aggregate.llvm_represent(),
+                element_type = element.type_().llvm_represent(),
+                element = element.llvm_represent(),
+                index = index
+            ),
             Store {
                 source,
                 destination,
             } => format!(
-                "store {} {}, {} %{}",
+                "store {} {}, {} {}",
                 source.type_().llvm_represent(),
                 source.llvm_represent(),
                 (ir::Type::Pointer(source.type_().into())).llvm_represent(),
-                destination.borrow()
+                destination.llvm_represent()
             ),
             Branch {
                 cond,
@@ -220,15 +289,25 @@ impl LLVMValue for ir::Function {
                 s.push_str(", ");
             }
         }
+        if self.variadic {
+            if !self.parameters.is_empty() {
+                s.push_str(", ");
+            }
+            s.push_str("...");
+        }
         s.push_str(")");
         match &self.body {
             Some(b) => {
                 s.push_str(" {\n");
                 s.push_str(b.llvm_represent().as_str());
                 match self.ret_typ {
-                    ir::Type::Void => {
+                    ir::Type::Void if !b.end_with_terminator() => {
                         s.push_str("  ret void\n");
                     }
+                    // e.g. all branches of if returned, the leave block cannot be reached
+                    _ if !b.end_with_terminator() => {
+                        s.push_str("  unreachable\n");
+                    }
                     _ => {}
                 }
                 s.push_str("}");
@@ -244,6 +323,11 @@ impl LLVMValue for ir::Variable {
         let mut s = String::new();
         s.push_str(self.name.llvm_represent().as_str());
         s.push_str(" = ");
+        if self.external {
+            s.push_str("external global ");
+            s.push_str(self.expr.type_().llvm_represent().as_str());
+            return s;
+        }
         s.push_str("global ");
         s.push_str(self.expr.type_().llvm_represent().as_str());
         s.push_str(" ");
@@ -256,13 +340,32 @@ impl LLVMValue for ir::Type {
     fn llvm_represent(&self) -> String {
         use ir::Type::*;
         match self {
-            Void => format!("void"),
+            Void => "void".to_string(),
+            Float(32) => "float".to_string(),
+            Float(64) => "double".to_string(),
             Float(n) => format!("f{}", n),
-            Int(n) => format!("i{}", n),
+            Int(n) | UInt(n) => format!("i{}", n),
             Pointer(typ) => format!("{}*", typ.llvm_represent()),
             Array { len, element_type } => format!("[{} x {}]", len, element_type.llvm_represent()),
+            Struct { name, fields } if name.is_empty() => {
+                let fields: Vec<String> = fields.iter().map(|f| f.typ.llvm_represent()).collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
             Struct { name, .. } => format!("%{}*", name),
             Named(name) => format!("%{}", name),
+            Function { .. } => format!(
+                "{{ {}, {} }}",
+                self.code_type().llvm_represent(),
+                Pointer(Int(8).into()).llvm_represent()
+            ),
+            FunctionPointer {
+                parameters,
+                ret_typ,
+            } => {
+                let parameters: Vec<String> =
+                    parameters.iter().map(|p| p.llvm_represent()).collect();
+                format!("{} ({})*", ret_typ.llvm_represent(), parameters.join(", "))
+            }
         }
     }
 }
@@ -278,7 +381,7 @@ impl ir::Type {
                 for (index, field) in fields.iter().enumerate() {
                     s.push_str(field.typ.llvm_represent().as_str());
                     if index < fields.len() - 1 {
-                        s.push_str(" ");
+                        s.push_str(", ");
                     }
                 }
                 s.push_str(" }");
@@ -293,13 +396,38 @@ impl LLVMValue for ir::Expr {
     fn llvm_represent(&self) -> String {
         use ir::Expr;
         match self {
-            Expr::F64(f) => format!("{}", f),
-            Expr::I64(i) => format!("{}", i),
+            // LLVM requires a float constant be exactly representable, hex form always is
+            Expr::F64(f) => format!("0x{:016X}", f.to_bits()),
+            Expr::Int(_, i) => format!("{}", i),
             Expr::Bool(b) => format!("{}", b),
-            Expr::CString(s_l) => format!("c\"{}\"", s_l),
+            Expr::CString(s_l) => format!("c\"{}\\00\"", escape_c_string(s_l)),
             Expr::Identifier(_, name) => format!("%{}", name),
             Expr::LocalIdentifier(_, id) => format!("%{}", id.borrow()),
             Expr::GlobalIdentifier(_, id) => format!("@{}", id.borrow()),
+            Expr::Global(_, name) => format!("@{}", name),
+            Expr::Zero(..) => "zeroinitializer".to_string(),
+            Expr::Function(_, name) => ir::function_name(name),
+            Expr::Struct(_, elements) => {
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|e| format!("{} {}", e.type_().llvm_represent(), e.llvm_represent()))
+                    .collect();
+                format!("{{ {} }}", elements.join(", "))
+            }
+        }
+    }
+}
+
+/// escape_c_string escapes bytes that cannot be put into LLVM string constant directly, e.g. `"`
+/// and newline, to `\XX` form
+fn escape_c_string(s: &str) -> String {
+    let mut escaped = String::new();
+    for b in s.bytes() {
+        match b {
+            b'"' | b'\\' => escaped.push_str(format!("\\{:02X}", b).as_str()),
+            b' '..=b'~' => escaped.push(b as char),
+            _ => escaped.push_str(format!("\\{:02X}", b).as_str()),
         }
     }
+    escaped
 }
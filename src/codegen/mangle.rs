@@ -0,0 +1,379 @@
+use crate::ast::*;
+use crate::codegen::tag::CodegenTag;
+use std::collections::{HashMap, HashSet};
+
+/// mangle_program merges modules into one list of definitions, names defined in a module are
+/// prefixed with the module name, e.g. `foo` in module `lib.math` is `lib.math.foo`, so modules
+/// can define the same name. References to imported names are renamed to the same form.
+///
+/// Names of `prelude`, the entry function `main` of module `main`, extern functions and extern
+/// variables keep their names, the code generator and linker refer to them directly. Other
+/// definitions of module `main` are mangled as well, so they cannot clash with extern symbols.
+pub(crate) fn mangle_program(modules: &Vec<Module>) -> Vec<TopAst> {
+    let mut program = vec![];
+    for module in modules {
+        let scope = Scope::of_module(module, modules);
+        for top in &module.top_list {
+            program.push(scope.top(top));
+        }
+    }
+    program
+}
+
+fn mangle(module_name: &str, name: &str) -> String {
+    match module_name {
+        "prelude" => name.to_string(),
+        _ => format!("{}.{}", module_name, name),
+    }
+}
+
+/// mangled_name returns the mangled name of definition `name` of module
+fn mangled_name(module: &Module, name: &str) -> String {
+    let keeps_name = module.top_list.iter().any(|top| match top {
+        TopAst::Function(f) => {
+            f.name == name && ((module.name == "main" && f.name == "main") || f.tag.is_extern())
+        }
+        TopAst::Variable(v) => v.name == name && v.tag.is_extern(),
+        _ => false,
+    });
+    if keeps_name {
+        name.to_string()
+    } else {
+        mangle(&module.name, name)
+    }
+}
+
+/// Scope maps names can be used in a module to their mangled names
+struct Scope {
+    names: HashMap<String, String>,
+}
+
+impl Scope {
+    fn of_module(module: &Module, modules: &[Module]) -> Scope {
+        let mut names = HashMap::new();
+        for top in &module.top_list {
+            match top {
+                TopAst::Import(i) => {
+                    // semantic checker ensures the module and imported names exist
+                    let imported_module = match modules.iter().find(|m| m.name == i.import_path) {
+                        Some(m) => m,
+                        None => continue,
+                    };
+                    let qualifier = i.alias.as_ref().unwrap_or(&i.import_path);
+                    for name in imported_module.definitions() {
+                        names.insert(
+                            format!("{}.{}", qualifier, name),
+                            mangled_name(imported_module, name),
+                        );
+                    }
+                    for component in &i.imported_component {
+                        match component {
+                            ImportedComponent::All(_) => {
+                                for name in imported_module.public_definitions() {
+                                    names.insert(
+                                        name.to_string(),
+                                        mangled_name(imported_module, name),
+                                    );
+                                }
+                            }
+                            ImportedComponent::Name { name, alias, .. } => {
+                                names.insert(
+                                    alias.as_ref().unwrap_or(name).clone(),
+                                    mangled_name(imported_module, name),
+                                );
+                            }
+                        }
+                    }
+                }
+                TopAst::Function(f) => {
+                    names.insert(f.name.clone(), mangled_name(module, &f.name));
+                }
+                TopAst::Variable(v) => {
+                    names.insert(v.name.clone(), mangled_name(module, &v.name));
+                }
+                TopAst::Class(c) => {
+                    names.insert(c.name.clone(), mangle(&module.name, &c.name));
+                }
+                TopAst::Trait(t) => {
+                    names.insert(t.name.clone(), mangle(&module.name, &t.name));
+                }
+                TopAst::Enum(e) => {
+                    names.insert(e.name.clone(), mangle(&module.name, &e.name));
+                }
+            }
+        }
+        Scope { names }
+    }
+
+    /// resolve returns the mangled name of `name`, a static method `Foo::new` or an enum variant
+    /// `Shape::Circle` is resolved by its class or enum
+    fn resolve(&self, name: &str) -> String {
+        let mut parts = name.splitn(2, "::");
+        let head = parts.next().unwrap();
+        let head = self
+            .names
+            .get(head)
+            .cloned()
+            .unwrap_or_else(|| head.to_string());
+        match parts.next() {
+            Some(rest) => format!("{}::{}", head, rest),
+            None => head,
+        }
+    }
+
+    fn top(&self, top: &TopAst) -> TopAst {
+        match top {
+            TopAst::Import(i) => TopAst::Import(i.clone()),
+            TopAst::Function(f) => {
+                let mut f = self.function(f, &HashSet::new());
+                f.name = self.resolve(&f.name);
+                TopAst::Function(f)
+            }
+            TopAst::Variable(v) => {
+                let mut v = v.clone();
+                v.name = self.resolve(&v.name);
+                v.typ = self.parsed_type(&v.typ);
+                v.expr = v.expr.as_ref().map(|e| self.expr(e, &HashSet::new()));
+                TopAst::Variable(v)
+            }
+            TopAst::Class(c) => {
+                let mut c = c.clone();
+                c.name = self.resolve(&c.name);
+                // fields can be used without `self.` in methods
+                let fields: HashSet<String> = c
+                    .members
+                    .iter()
+                    .filter_map(|member| match member {
+                        ClassMember::Field(f) => Some(f.name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                for member in c.members.iter_mut() {
+                    match member {
+                        ClassMember::Field(f) => {
+                            f.typ = self.parsed_type(&f.typ);
+                            f.expr = f.expr.as_ref().map(|e| self.expr(e, &HashSet::new()));
+                        }
+                        ClassMember::Method(f) => *f = self.function(f, &fields),
+                        ClassMember::StaticMethod(f) => *f = self.function(f, &HashSet::new()),
+                    }
+                }
+                TopAst::Class(c)
+            }
+            TopAst::Trait(t) => {
+                let mut t = t.clone();
+                t.name = self.resolve(&t.name);
+                TopAst::Trait(t)
+            }
+            TopAst::Enum(e) => {
+                let mut e = e.clone();
+                e.name = self.resolve(&e.name);
+                for variant in e.variants.iter_mut() {
+                    for field in variant.fields.iter_mut() {
+                        *field = self.parameter(field);
+                    }
+                }
+                TopAst::Enum(e)
+            }
+        }
+    }
+
+    fn function(&self, f: &Function, bound: &HashSet<String>) -> Function {
+        let mut f = f.clone();
+        let mut bound = bound.clone();
+        for p in f.parameters.iter_mut() {
+            *p = self.parameter(p);
+            bound.insert(p.name.clone());
+        }
+        f.ret_typ = self.parsed_type(&f.ret_typ);
+        f.body = match &f.body {
+            Some(Body::Expr(e)) => Some(Body::Expr(self.expr(e, &bound))),
+            Some(Body::Block(b)) => Some(Body::Block(self.block(b, &bound))),
+            None => None,
+        };
+        f
+    }
+
+    /// parameter renames the type and default value of parameter, default value is evaluated
+    /// where the function is called, so it cannot refer to local names
+    fn parameter(&self, p: &Parameter) -> Parameter {
+        let mut p = p.clone();
+        p.typ = self.parsed_type(&p.typ);
+        p.default = p.default.map(|e| self.expr(&e, &HashSet::new()));
+        p
+    }
+
+    fn block(&self, b: &Block, bound: &HashSet<String>) -> Block {
+        self.statements(b, &mut bound.clone())
+    }
+
+    fn value_block(&self, b: &ValueBlock, bound: &HashSet<String>) -> ValueBlock {
+        let mut bound = bound.clone();
+        let block = self.statements(&b.block, &mut bound);
+        ValueBlock::new(block, self.expr(&b.value, &bound))
+    }
+
+    /// statements renames statements of block, variables defined by them are added to `bound`
+    fn statements(&self, b: &Block, bound: &mut HashSet<String>) -> Block {
+        let mut block = b.clone();
+        for stmt in block.statements.iter_mut() {
+            use StatementVariant::*;
+            stmt.value = match &stmt.value {
+                Return(e) => Return(e.as_ref().map(|e| self.expr(e, bound))),
+                Expression(e) => Expression(self.expr(e, bound)),
+                Variable(v) => {
+                    let mut v = v.clone();
+                    v.typ = self.parsed_type(&v.typ);
+                    v.expr = v.expr.as_ref().map(|e| self.expr(e, bound));
+                    bound.insert(v.name.clone());
+                    Variable(v)
+                }
+                Destructure { names, expr } => {
+                    let expr = self.expr(expr, bound);
+                    bound.extend(names.iter().cloned());
+                    Destructure {
+                        names: names.clone(),
+                        expr,
+                    }
+                }
+                IfBlock {
+                    clauses,
+                    else_block,
+                } => IfBlock {
+                    clauses: clauses
+                        .iter()
+                        .map(|(cond, block)| (self.expr(cond, bound), self.block(block, bound)))
+                        .collect(),
+                    else_block: self.block(else_block, bound),
+                },
+            };
+        }
+        block
+    }
+
+    fn expr(&self, e: &Expr, bound: &HashSet<String>) -> Expr {
+        use ExprVariant::*;
+        let value = match &e.value {
+            Identifier(name) => {
+                if bound.contains(name) {
+                    Identifier(name.clone())
+                } else {
+                    Identifier(self.resolve(name))
+                }
+            }
+            Binary(lhs, rhs, op) => Binary(
+                self.expr(lhs, bound).into(),
+                self.expr(rhs, bound).into(),
+                op.clone(),
+            ),
+            FuncCall(f, args) => FuncCall(
+                self.expr(f, bound).into(),
+                args.iter()
+                    .map(|arg| {
+                        let mut arg = arg.clone();
+                        arg.expr = self.expr(&arg.expr, bound);
+                        arg
+                    })
+                    .collect(),
+            ),
+            MemberAccess(from, member) => match e.access_path() {
+                // qualified name `io.println`
+                Some(path)
+                    if !bound.contains(path.split('.').next().unwrap())
+                        && self.names.contains_key(&path) =>
+                {
+                    Identifier(self.resolve(&path))
+                }
+                _ => MemberAccess(self.expr(from, bound).into(), member.clone()),
+            },
+            Interpolation(from) => Interpolation(self.expr(from, bound).into()),
+            Cast(from, typ) => Cast(self.expr(from, bound).into(), self.parsed_type(typ)),
+            List(exprs) => List(exprs.iter().map(|e| self.expr(e, bound)).collect()),
+            Tuple(exprs) => Tuple(exprs.iter().map(|e| self.expr(e, bound)).collect()),
+            TupleAccess(tuple, index) => TupleAccess(self.expr(tuple, bound).into(), *index),
+            ClassConstruction(class_name, field_inits) => ClassConstruction(
+                self.resolve(class_name),
+                field_inits
+                    .iter()
+                    .map(|(name, e)| (name.clone(), self.expr(e, bound)))
+                    .collect(),
+            ),
+            Match(value, arms) => Match(
+                self.expr(value, bound).into(),
+                arms.iter()
+                    .map(|arm| {
+                        let mut arm = arm.clone();
+                        let mut bound = bound.clone();
+                        match &mut arm.pattern {
+                            Pattern::Binding(name) => {
+                                bound.insert(name.clone());
+                            }
+                            Pattern::Variant(name, bindings) => {
+                                *name = self.resolve(name);
+                                bound.extend(bindings.iter().cloned());
+                            }
+                            _ => (),
+                        }
+                        arm.expr = self.expr(&arm.expr, &bound);
+                        arm
+                    })
+                    .collect(),
+            ),
+            If {
+                clauses,
+                else_block,
+            } => If {
+                clauses: clauses
+                    .iter()
+                    .map(|(cond, block)| (self.expr(cond, bound), self.value_block(block, bound)))
+                    .collect(),
+                else_block: self.value_block(else_block, bound),
+            },
+            Lambda {
+                parameters,
+                ret_typ,
+                body,
+            } => {
+                let mut bound = bound.clone();
+                bound.extend(parameters.iter().map(|p| p.name.clone()));
+                Lambda {
+                    parameters: parameters.iter().map(|p| self.parameter(p)).collect(),
+                    ret_typ: self.parsed_type(ret_typ),
+                    body: self.expr(body, &bound).into(),
+                }
+            }
+            v @ F64(..) | v @ Int(..) | v @ Bool(..) | v @ String(..) => v.clone(),
+        };
+        Expr {
+            id: e.id,
+            location: e.location.clone(),
+            value,
+        }
+    }
+
+    fn parsed_type(&self, typ: &ParsedType) -> ParsedType {
+        match typ {
+            ParsedType::TypeName(name) => ParsedType::TypeName(self.resolve(name)),
+            ParsedType::GenericType {
+                name,
+                type_parameters,
+            } => ParsedType::GenericType {
+                name: self.resolve(name),
+                type_parameters: type_parameters
+                    .iter()
+                    .map(|t| self.parsed_type(t))
+                    .collect(),
+            },
+            ParsedType::FunctionType {
+                parameters,
+                ret_typ,
+            } => ParsedType::function_type(
+                parameters.iter().map(|t| self.parsed_type(t)).collect(),
+                self.parsed_type(ret_typ),
+            ),
+            ParsedType::TupleType(elements) => {
+                ParsedType::tuple_type(elements.iter().map(|t| self.parsed_type(t)).collect())
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use crate::ast::*;
+use crate::codegen::mangle::mangle_program;
 use crate::codegen::tag::CodegenTag;
+use crate::semantic::initialization_order;
 
 pub mod formatter;
 pub mod ir;
 pub mod llvm;
+mod mangle;
 mod tag;
 
 pub struct CodeGenerator {}
@@ -13,19 +16,67 @@ impl CodeGenerator {
         CodeGenerator {}
     }
 
+    /// generate_program generates one module from all modules of program
+    pub fn generate_program(&self, modules: &Vec<Module>) -> ir::Module {
+        self.generate_module(&mangle_program(modules))
+    }
+
     pub fn generate_module(&self, asts: &Vec<TopAst>) -> ir::Module {
         let mut module = ir::Module::new();
+        // types first, so functions and fields can refer to any class
+        let mut classes: Vec<&Class> = vec![];
+        let mut enums: Vec<&Enum> = vec![];
+        for top in asts {
+            match top {
+                TopAst::Class(c) if c.tag.is_builtin() => {
+                    // a builtin class without LLVM type, e.g. `@builtin class List[T] {}`, can't
+                    // be used by generated code yet
+                    if let Some(typ) = c.tag.builtin_type() {
+                        module.declare_builtin_type(&c.name, typ);
+                    }
+                }
+                TopAst::Class(c) => classes.push(c),
+                TopAst::Enum(e) => enums.push(e),
+                _ => (),
+            }
+        }
+        for c in &classes {
+            module.declare_type(&c.name);
+        }
+        for e in &enums {
+            module.declare_type(&e.name);
+        }
+        for c in &classes {
+            module.push_type(&c.name, &c.members);
+        }
+        for e in &enums {
+            module.push_enum(e);
+        }
         for top in asts {
             use TopAst::*;
             match &top {
                 Import(_) => {}
                 Function(f) => {
-                    module.remember_function(f);
+                    module.remember_function(f.name.clone(), f);
                 }
                 Variable(v) => {
                     module.remember_variable(v);
                 }
-                Class(_) => {}
+                Class(c) => {
+                    for member in &c.members {
+                        match member {
+                            ClassMember::StaticMethod(method) | ClassMember::Method(method) => {
+                                module.remember_function(
+                                    format!("{}::{}", c.name, method.name),
+                                    method,
+                                );
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                // constructors of variants are remembered by `push_enum`
+                Enum(_) => {}
                 Trait(_) => unimplemented!(),
             }
         }
@@ -40,22 +91,9 @@ impl CodeGenerator {
                     let func = ir::Function::from_ast(f, None, &mut module);
                     module.push_function(func);
                 }
-                Variable(v) => {
-                    let var = ir::Variable::new(v.name.clone(), ir::Expr::from_ast(&v.expr));
-                    module.push_variable(var);
-                }
+                Variable(_) => (),
                 Class(c) => {
-                    match c.name.as_str() {
-                        // FIXME: provide a tag, e.g.
-                        // ```
-                        // @Codegen(Omit)
-                        // class int {}
-                        // ```
-                        "void" | "int" | "f64" | "bool" | "_c_string" | "List" => continue,
-                        _ => {}
-                    }
-                    module.push_type(&c.name, &c.members);
-
+                    // builtin type has no struct, but its methods still need to be generated
                     for member in &c.members {
                         match member {
                             ClassMember::StaticMethod(static_method) => {
@@ -83,9 +121,18 @@ impl CodeGenerator {
                         }
                     }
                 }
+                Enum(e) => {
+                    for variant in &e.variants {
+                        let func = ir::Function::enum_constructor(&e.name, variant, &mut module);
+                        module.push_function(func);
+                    }
+                }
                 Trait(_) => unimplemented!(),
             }
         }
+        let variables = initialization_order(asts)
+            .expect("initialization cycle must be reported by semantic checker");
+        module.push_global_variables(variables);
         module
     }
 }
@@ -1,7 +1,12 @@
 use crate::ast::Tag;
+use crate::codegen::ir::Type;
 
 pub(crate) trait CodegenTag {
     fn is_builtin(&self) -> bool;
+    fn builtin_type(&self) -> Option<Type>;
+    fn builtin_instruction(&self) -> Option<String>;
+    fn is_extern(&self) -> bool;
+    fn is_variadic(&self) -> bool;
 }
 
 impl CodegenTag for Option<Tag> {
@@ -11,4 +16,46 @@ impl CodegenTag for Option<Tag> {
             None => false,
         }
     }
+    /// builtin_type returns the LLVM type of builtin class, e.g. `@builtin(i64)`, unsigned integer
+    /// is `@builtin(u8)`, and a pointer type is `@builtin(i8, pointer)`
+    fn builtin_type(&self) -> Option<Type> {
+        let properties = match self {
+            Some(tag) if tag.name == "builtin" => &tag.properties,
+            _ => return None,
+        };
+        let primitive = properties.first()?;
+        let typ = match primitive.as_str() {
+            "void" => Type::Void,
+            p if p.starts_with('i') => Type::Int(p[1..].parse().ok()?),
+            p if p.starts_with('u') => Type::UInt(p[1..].parse().ok()?),
+            p if p.starts_with('f') => Type::Float(p[1..].parse().ok()?),
+            _ => return None,
+        };
+        if properties.get(1).map(|p| p.as_str()) == Some("pointer") {
+            Some(Type::Pointer(typ.into()))
+        } else {
+            Some(typ)
+        }
+    }
+    /// builtin_instruction returns the LLVM instruction of builtin function, e.g. a call to
+    /// `@builtin(sitofp) to_f64(x: int): f64;` is `sitofp i64 %x to double`
+    fn builtin_instruction(&self) -> Option<String> {
+        match self {
+            Some(tag) if tag.name == "builtin" => tag.properties.first().cloned(),
+            _ => None,
+        }
+    }
+    fn is_extern(&self) -> bool {
+        match self {
+            Some(tag) => tag.name == "extern",
+            None => false,
+        }
+    }
+    fn is_variadic(&self) -> bool {
+        match self {
+            // `@extern(c, variadic)`
+            Some(tag) => tag.name == "extern" && tag.properties.contains(&"variadic".to_string()),
+            None => false,
+        }
+    }
 }
@@ -7,7 +7,7 @@ fn test_codegen_main() {
     let code = "main(): void {}";
     let module = gen_code(code);
     assert_eq!(
-        module.functions.get("@main").unwrap().llvm_represent(),
+        module.lookup_function("@main").unwrap().llvm_represent(),
         "define void @main() {
   ret void
 }"
@@ -18,7 +18,10 @@ fn test_codegen_main() {
 fn global_variable() {
     let code = "x: int = 1;";
     let module = gen_code(code);
-    assert_eq!(module.variables[0].llvm_represent(), "@x = global i64 1");
+    assert_eq!(
+        module.variables.last().unwrap().llvm_represent(),
+        "@x = global i64 1"
+    );
 }
 
 #[test]
@@ -26,7 +29,7 @@ fn test_return_value() {
     let code = "foo(): int = 1;";
     let module = gen_code(code);
     assert_eq!(
-        module.functions.get("@foo").unwrap().llvm_represent(),
+        module.lookup_function("@foo").unwrap().llvm_represent(),
         "define i64 @foo() {
   ret i64 1
 }"
@@ -38,7 +41,7 @@ fn test_function_declaration_with_parameter() {
     let code = "add(x: int, y: int): int;";
     let module = gen_code(code);
     assert_eq!(
-        module.functions.get("@add").unwrap().llvm_represent(),
+        module.lookup_function("@add").unwrap().llvm_represent(),
         "declare i64 @add(i64 %x, i64 %y)"
     )
 }
@@ -48,7 +51,7 @@ fn test_function_define_with_parameter() {
     let code = "const(x: int): int = 1;";
     let module = gen_code(code);
     assert_eq!(
-        module.functions.get("@const").unwrap().llvm_represent(),
+        module.lookup_function("@const").unwrap().llvm_represent(),
         "define i64 @const(i64 %x) {
   ret i64 1
 }"
@@ -64,7 +67,7 @@ fn test_function_call() {
     foo(x: int): void {}";
     let module = gen_code(code);
     assert_eq!(
-        module.functions.get("@main").unwrap().llvm_represent(),
+        module.lookup_function("@main").unwrap().llvm_represent(),
         "define void @main() {
   call void @foo(i64 1)
   ret void
@@ -79,7 +82,7 @@ fn binary_expr() {
     ";
     let module = gen_code(code);
     assert_eq!(
-        module.functions.get("@foo").unwrap().llvm_represent(),
+        module.lookup_function("@foo").unwrap().llvm_represent(),
         "define i64 @foo() {
   %1 = add i64 1, 2
   ret i64 %1
@@ -97,21 +100,19 @@ fn test_class_define() {
     }";
     let module = gen_code(code);
     assert_eq!(
-        module.types.get("Foo").unwrap().llvm_def(),
+        module.lookup_type(&"Foo".to_string()).llvm_def(),
         "%Foo = type { i64 }"
     );
     assert_eq!(
         module
-            .functions
-            .get("@\"Foo::new\"")
+            .lookup_function("@\"Foo::new\"")
             .unwrap()
             .llvm_represent(),
         "declare %Foo* @\"Foo::new\"()"
     );
     assert_eq!(
         module
-            .functions
-            .get("@\"Foo::bar\"")
+            .lookup_function("@\"Foo::bar\"")
             .unwrap()
             .llvm_represent(),
         "define void @\"Foo::bar\"(%Foo* %self) {
@@ -133,7 +134,7 @@ fn llvm_if_else() {
     ";
     let module = gen_code(code);
     assert_eq!(
-        module.functions.get("@foo").unwrap().llvm_represent(),
+        module.lookup_function("@foo").unwrap().llvm_represent(),
         "define void @foo() {
   br i1 true, label %1, label %2
 ; <label>:1:
@@ -150,6 +151,807 @@ fn llvm_if_else() {
     )
 }
 
+#[test]
+fn equal_expr() {
+    let code = "
+    foo(x: int): bool = x == 1;
+    ";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i1 @foo(i64 %x) {
+  %1 = icmp eq i64 %x, 1
+  ret i1 %1
+}"
+    )
+}
+
+#[test]
+fn local_variable() {
+    let code = "
+    foo(): int {
+      x: int = 1 + 2;
+      return x + 3;
+    }
+    ";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i64 @foo() {
+  %1 = add i64 1, 2
+  %2 = add i64 %1, 3
+  ret i64 %2
+}"
+    )
+}
+
+#[test]
+fn string_operators_call_runtime() {
+    let code = "
+    foo(a: string, b: string): bool = a + b == a;
+    ";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i1 @foo(%string* %a, %string* %b) {
+  %1 = call %string* @\"string::concat\"(%string* %a, %string* %b)
+  %2 = call i1 @\"string::equal\"(%string* %1, %string* %a)
+  ret i1 %2
+}"
+    )
+}
+
+#[test]
+fn method_call_and_field_access_in_method() {
+    let code = "
+    class Foo {
+      x: int;
+      get(): int = x;
+    }
+    foo(f: Foo): int = f.get();
+    ";
+    let module = gen_code(code);
+    assert_eq!(
+        module
+            .lookup_function("@\"Foo::get\"")
+            .unwrap()
+            .llvm_represent(),
+        "define i64 @\"Foo::get\"(%Foo* %self) {
+  %1 = getelementptr %Foo, %Foo* %self, i32 0, i32 0
+  %2 = load i64, i64* %1
+  ret i64 %2
+}"
+    );
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i64 @foo(%Foo* %f) {
+  %1 = call i64 @\"Foo::get\"(%Foo* %f)
+  ret i64 %1
+}"
+    );
+}
+
+#[test]
+fn void_function_call_does_not_take_a_number() {
+    let code = "
+    foo(): void {}
+    bar(): int {
+      foo();
+      return 1 + 2;
+    }
+    ";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@bar").unwrap().llvm_represent(),
+        "define i64 @bar() {
+  call void @foo()
+  %1 = add i64 1, 2
+  ret i64 %1
+}"
+    )
+}
+
+#[test]
+fn variadic_function() {
+    let code = "
+    @extern(c, variadic)
+    printf(format: _c_string): int;
+    foo(format: _c_string): int = printf(format, 1);
+    ";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@printf").unwrap().llvm_represent(),
+        "declare i64 @printf(i8* %format, ...)"
+    );
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i64 @foo(i8* %format) {
+  %1 = call i64 (i8*, ...) @printf(i8* %format, i64 1)
+  ret i64 %1
+}"
+    )
+}
+
+#[test]
+fn interpolation_calls_to_string() {
+    let code = "
+    foo(x: int, b: bool): string = \"{x}{b}\";
+    ";
+    let module = gen_code(code);
+    let foo = module.lookup_function("@foo").unwrap().llvm_represent();
+    assert!(foo.contains("call %string* @\"int::to_string\"(i64 %x)"));
+    assert!(foo.contains("call %string* @\"bool::to_string\"(i1 %b)"));
+}
+
+#[test]
+fn f64_is_double() {
+    let code = "foo(x: f64): f64 = x;";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define double @foo(double %x) {
+  ret double %x
+}"
+    )
+}
+
+#[test]
+fn string_literal_is_escaped() {
+    let code = "x: string = \"\\\"é\\n\";";
+    let module = gen_code(code);
+    assert!(module
+        .llvm_represent()
+        .contains("global [5 x i8] c\"\\22\\C3\\A9\\0A\\00\""))
+}
+
+#[test]
+fn global_variable_is_folded_to_constant() {
+    let code = "
+    y: int = x + 2;
+    x: int = 1;
+    b: bool = y == 3;
+    ";
+    let module = gen_code(code);
+    // string literals in prelude are anonymous
+    let variables: Vec<String> = module
+        .variables
+        .iter()
+        .filter(|v| matches!(v.name, ir::GlobalName::String(..)))
+        .map(|v| v.llvm_represent())
+        .collect();
+    assert_eq!(
+        variables,
+        vec![
+            "@x = global i64 1",
+            "@y = global i64 3",
+            "@b = global i1 true"
+        ]
+    );
+    assert_eq!(module.initializer, None);
+}
+
+#[test]
+fn global_variable_initialized_at_runtime() {
+    let code = "
+    x: int = foo() + y;
+    y: int = 1;
+    foo(): int = 1;
+    ";
+    let module = gen_code(code);
+    assert_eq!(
+        module.variables.last().unwrap().llvm_represent(),
+        "@x = global i64 zeroinitializer"
+    );
+    assert_eq!(
+        module.initializer.unwrap().llvm_represent(),
+        "define void @elz.init() {
+  %1 = call i64 @foo()
+  %2 = load i64, i64* @y
+  %3 = add i64 %1, %2
+  store i64 %3, i64* @x
+  ret void
+}"
+    );
+}
+
+#[test]
+fn definitions_are_in_source_order() {
+    let code = "
+    class B {}
+    class A {}
+    z(): void {}
+    a(): void {}
+    m(): void {}
+    ";
+    let module = gen_code(code);
+    let types: Vec<String> = module.types.iter().map(|t| t.llvm_def()).collect();
+    assert_eq!(
+        types[types.len() - 2..].to_vec(),
+        vec!["%B = type {  }", "%A = type {  }"]
+    );
+    let functions: Vec<&str> = module.functions.iter().map(|f| f.name.as_str()).collect();
+    // prelude first
+    assert_eq!(functions[0], "@\"int::to_string\"");
+    assert_eq!(
+        functions[functions.len() - 3..].to_vec(),
+        vec!["@z", "@a", "@m"]
+    );
+}
+
+#[test]
+fn output_is_reproducible() {
+    let code = "
+    class Foo {
+      a: int;
+      b: string;
+      ::new(): Foo = Foo {b: \"{x}\", a: 1};
+    }
+    x: int = y();
+    y(): int = 1;
+    main(): void {}
+    ";
+    let output = gen_code(code).llvm_represent();
+    for _ in 0..10 {
+        assert_eq!(gen_code(code).llvm_represent(), output);
+    }
+}
+
+#[test]
+fn names_of_other_modules_are_mangled() {
+    let lib = "module lib.math
+    helper(): int = 1;
+    class Point {
+      x: int;
+      ::new(x: int): Point = Point {x: helper() + x};
+    }
+    ";
+    let main = "module main
+    import lib.math (Point)
+    helper(): int = 2;
+    main(): void {
+      p: Point = Point::new(helper());
+    }
+    ";
+    let modules = vec![
+        crate::parser::parse_prelude(),
+        crate::parser::Parser::parse_program("", lib).unwrap(),
+        crate::parser::Parser::parse_program("", main).unwrap(),
+    ];
+    let module = CodeGenerator::new().generate_program(&modules);
+    assert!(module.lookup_function("@lib.math.helper").is_some());
+    assert!(module.lookup_function("@main.helper").is_some());
+    assert_eq!(
+        module.lookup_type(&"lib.math.Point".to_string()).llvm_def(),
+        "%lib.math.Point = type { i64 }"
+    );
+    let output = module.llvm_represent();
+    assert!(output.contains("call i64 @lib.math.helper()"));
+    assert!(output.contains("call %lib.math.Point* @\"lib.math.Point::new\"(i64 %1)"));
+}
+
+#[test]
+fn qualified_access_and_aliases_refer_to_mangled_names() {
+    let lib = "module lib
+    one(): int = 1;
+    ";
+    let main = "module main
+    import lib as l (one as uno)
+    main(): void {
+      x: int = l.one();
+      y: int = uno();
+    }
+    ";
+    let modules = vec![
+        crate::parser::parse_prelude(),
+        crate::parser::Parser::parse_program("", lib).unwrap(),
+        crate::parser::Parser::parse_program("", main).unwrap(),
+    ];
+    let module = CodeGenerator::new().generate_program(&modules);
+    let main = module.lookup_function("@main").unwrap().llvm_represent();
+    assert_eq!(main.matches("call i64 @lib.one()").count(), 2);
+}
+
+#[test]
+fn builtin_class_is_llvm_type_of_its_tag() {
+    let code = "@builtin(i32)
+    class i32 {
+      twice(): i32 = self;
+    }
+    foo(x: i32): i32 = x.twice();";
+    let module = gen_code(code);
+    // no struct for builtin class
+    assert!(!module.llvm_represent().contains("%i32 = type"));
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i32 @foo(i32 %x) {
+  %1 = call i32 @\"i32::twice\"(i32 %x)
+  ret i32 %1
+}"
+    )
+}
+
+#[test]
+fn imported_extern_function_keeps_its_name() {
+    let lib = "module lib
+    @extern(c)
+    pub sqrt(x: f64): f64;
+    ";
+    let main = "module main
+    import lib as l (sqrt)
+    main(): void {
+      x: f64 = sqrt(l.sqrt(4.0));
+    }
+    ";
+    let modules = vec![
+        crate::parser::parse_prelude(),
+        crate::parser::Parser::parse_program("", lib).unwrap(),
+        crate::parser::Parser::parse_program("", main).unwrap(),
+    ];
+    let module = CodeGenerator::new().generate_program(&modules);
+    let main = module.lookup_function("@main").unwrap().llvm_represent();
+    assert_eq!(main.matches("call double @sqrt(").count(), 2);
+}
+
+#[test]
+fn definitions_of_main_module_do_not_clash_with_extern_functions() {
+    let lib = "module lib
+    @extern(c)
+    pub close(fd: i32): i32;
+    pub close_all(): i32 = close(3);
+    ";
+    let main = "module main
+    import lib (close_all)
+    @extern(c)
+    malloc(size: u64): _c_string;
+    close(x: int): int = x;
+    main(): void {
+      x: int = close(close_all() as int);
+    }
+    ";
+    let modules = vec![
+        crate::parser::parse_prelude(),
+        crate::parser::Parser::parse_program("", lib).unwrap(),
+        crate::parser::Parser::parse_program("", main).unwrap(),
+    ];
+    let module = CodeGenerator::new().generate_program(&modules);
+    assert!(module.lookup_function("@main.close").is_some());
+    let output = module.llvm_represent();
+    assert_eq!(output.matches("declare i32 @close(").count(), 1);
+    // prelude declares `malloc` as well
+    assert_eq!(output.matches("declare i8* @malloc(").count(), 1);
+    assert!(output.contains("call i64 @main.close("));
+}
+
+#[test]
+fn call_to_builtin_function_is_an_instruction() {
+    let code = "@builtin(sitofp)
+    to_f64(x: int): f64;
+    foo(x: int): f64 = to_f64(x);";
+    let module = gen_code(code);
+    assert!(module.lookup_function("@to_f64").is_none());
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define double @foo(i64 %x) {
+  %1 = sitofp i64 %x to double
+  ret double %1
+}"
+    )
+}
+
+#[test]
+fn integer_literal_has_expected_type() {
+    let code = "@extern(c)
+    putchar(c: i32): i32;
+    foo(x: u8): u8 {
+      _: i32 = putchar(65);
+      return x + 1;
+    }";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i8 @foo(i8 %x) {
+  %1 = call i32 @putchar(i32 65)
+  %2 = add i8 %x, 1
+  ret i8 %2
+}"
+    )
+}
+
+#[test]
+fn cast_is_converting_instruction() {
+    let code = "foo(x: int, y: u8, z: f64): f64 {
+      a: u8 = x as u8;
+      b: i32 = y as i32;
+      c: i16 = z as i16;
+      d: u8 = a as u8;
+      return b as f64;
+    }";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define double @foo(i64 %x, i8 %y, double %z) {
+  %1 = trunc i64 %x to i8
+  %2 = zext i8 %y to i32
+  %3 = fptosi double %z to i16
+  %4 = bitcast i8 %1 to i8
+  %5 = sitofp i32 %2 to double
+  ret double %5
+}"
+    )
+}
+
+#[test]
+fn enum_is_tagged_union() {
+    let code = "enum Shape {
+      Circle(r: f64),
+      Rect(w: int, h: int),
+      Empty,
+    }
+    foo(): Shape = Shape::Empty;";
+    let module = gen_code(code);
+    let ir = module.llvm_represent();
+    assert!(ir.contains("%Shape = type { i64, [16 x i8] }"));
+    assert!(ir.contains("%Shape.Circle = type { double }"));
+    assert!(ir.contains("%Shape.Rect = type { i64, i64 }"));
+    assert!(!ir.contains("%Shape.Empty = type"));
+    assert_eq!(
+        module
+            .lookup_function("@\"Shape::Rect\"")
+            .unwrap()
+            .llvm_represent(),
+        "define %Shape* @\"Shape::Rect\"(i64 %w, i64 %h) {
+  %1 = call i8* @malloc(i64 192)
+  %2 = bitcast i8* %1 to %Shape*
+  %3 = getelementptr %Shape, %Shape* %2, i32 0, i32 0
+  store i64 1, i64* %3
+  %4 = getelementptr %Shape, %Shape* %2, i32 0, i32 1
+  %5 = bitcast [16 x i8]* %4 to %Shape.Rect*
+  %6 = getelementptr %Shape.Rect, %Shape.Rect* %5, i32 0, i32 0
+  store i64 %w, i64* %6
+  %7 = getelementptr %Shape.Rect, %Shape.Rect* %5, i32 0, i32 1
+  store i64 %h, i64* %7
+  ret %Shape* %2
+}"
+    );
+    // variant without fields is a call to its constructor
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define %Shape* @foo() {
+  %1 = call %Shape* @\"Shape::Empty\"()
+  ret %Shape* %1
+}"
+    )
+}
+
+#[test]
+fn enum_payload_is_as_large_as_largest_variant() {
+    let code = "enum Shape {
+      Pair(p: (int, int), name: string),
+      Call(f: (): int),
+      Flag(a: bool, b: u8),
+    }";
+    let ir = gen_code(code).llvm_represent();
+    // `{ { i64, i64 }, %string* }` is 24 bytes, the closure is 16 bytes
+    assert!(ir.contains("%Shape = type { i64, [24 x i8] }"));
+    assert!(ir.contains("%Shape.Pair = type { { i64, i64 }, %string* }"));
+    assert!(ir.contains("%Shape.Call = type { { i64 (i8*)*, i8* } }"));
+}
+
+#[test]
+fn match_is_branch_chain() {
+    let code = "foo(x: u8): u8 = match x {
+      0 => 1,
+      1 => 2,
+      _ => x,
+    };";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i8 @foo(i8 %x) {
+  %1 = alloca i8
+  %2 = icmp eq i8 %x, 0
+  br i1 %2, label %3, label %4
+; <label>:3:
+  store i8 1, i8* %1
+  br label %8
+; <label>:4:
+  %5 = icmp eq i8 %x, 1
+  br i1 %5, label %6, label %7
+; <label>:6:
+  store i8 2, i8* %1
+  br label %8
+; <label>:7:
+  store i8 %x, i8* %1
+  br label %8
+; <label>:8:
+  %9 = load i8, i8* %1
+  ret i8 %9
+}"
+    )
+}
+
+#[test]
+fn match_enum_compares_tag_and_loads_fields() {
+    let code = "enum Shape {
+      Circle(r: int),
+      Empty,
+    }
+    area(s: Shape): int = match s {
+      Shape::Circle(r) => r,
+      Shape::Empty => 0,
+    };";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@area").unwrap().llvm_represent(),
+        "define i64 @area(%Shape* %s) {
+  %1 = getelementptr %Shape, %Shape* %s, i32 0, i32 0
+  %2 = load i64, i64* %1
+  %3 = alloca i64
+  %4 = icmp eq i64 %2, 0
+  br i1 %4, label %5, label %10
+; <label>:5:
+  %6 = getelementptr %Shape, %Shape* %s, i32 0, i32 1
+  %7 = bitcast [8 x i8]* %6 to %Shape.Circle*
+  %8 = getelementptr %Shape.Circle, %Shape.Circle* %7, i32 0, i32 0
+  %9 = load i64, i64* %8
+  store i64 %9, i64* %3
+  br label %11
+; <label>:10:
+  store i64 0, i64* %3
+  br label %11
+; <label>:11:
+  %12 = load i64, i64* %3
+  ret i64 %12
+}"
+    )
+}
+
+#[test]
+fn if_expression_stores_branches_into_slot() {
+    let code = "foo(a: bool, b: bool): u8 = if a { 1 } else if b { 2 } else { 3 };";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i8 @foo(i1 %a, i1 %b) {
+  %1 = alloca i8
+  br i1 %a, label %2, label %3
+; <label>:2:
+  store i8 1, i8* %1
+  br label %6
+; <label>:3:
+  br i1 %b, label %4, label %5
+; <label>:4:
+  store i8 2, i8* %1
+  br label %6
+; <label>:5:
+  store i8 3, i8* %1
+  br label %6
+; <label>:6:
+  %7 = load i8, i8* %1
+  ret i8 %7
+}"
+    )
+}
+
+#[test]
+fn void_if_expression_has_no_slot() {
+    let code = "a(): void {}
+    b(): void {}
+    foo(x: bool): void = if x { a() } else { b() };";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define void @foo(i1 %x) {
+  br i1 %x, label %1, label %2
+; <label>:1:
+  call void @a()
+  br label %3
+; <label>:2:
+  call void @b()
+  br label %3
+; <label>:3:
+  ret void
+}"
+    )
+}
+
+#[test]
+fn call_through_value_of_function_type() {
+    let code = "add(a: int, b: int): int = a + b;
+    op: (int, int): int = add;
+    apply(f: (int, int): int, x: u8): int = f(x as int, 1);
+    foo(): int = apply(add, 2) + op(1, 2);";
+    let module = gen_code(code);
+    assert!(module.llvm_represent().contains(
+        "@op = global { i64 (i8*, i64, i64)*, i8* } { i64 (i8*, i64, i64)* @add.closure, i8* zeroinitializer }"
+    ));
+    assert_eq!(
+        module
+            .lookup_function("@add.closure")
+            .unwrap()
+            .llvm_represent(),
+        "define i64 @add.closure(i8* %env, i64 %p0, i64 %p1) {
+  %1 = call i64 @add(i64 %p0, i64 %p1)
+  ret i64 %1
+}"
+    );
+    assert_eq!(
+        module.lookup_function("@apply").unwrap().llvm_represent(),
+        "define i64 @apply({ i64 (i8*, i64, i64)*, i8* } %f, i8 %x) {
+  %1 = extractvalue { i64 (i8*, i64, i64)*, i8* } %f, 0
+  %2 = extractvalue { i64 (i8*, i64, i64)*, i8* } %f, 1
+  %3 = zext i8 %x to i64
+  %4 = call i64 %1(i8* %2, i64 %3, i64 1)
+  ret i64 %4
+}"
+    );
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i64 @foo() {
+  %1 = call i64 @apply({ i64 (i8*, i64, i64)*, i8* } { i64 (i8*, i64, i64)* @add.closure, i8* zeroinitializer }, i8 2)
+  %2 = load { i64 (i8*, i64, i64)*, i8* }, { i64 (i8*, i64, i64)*, i8* }* @op
+  %3 = extractvalue { i64 (i8*, i64, i64)*, i8* } %2, 0
+  %4 = extractvalue { i64 (i8*, i64, i64)*, i8* } %2, 1
+  %5 = call i64 %3(i8* %4, i64 1, i64 2)
+  %6 = add i64 %1, %5
+  ret i64 %6
+}"
+    )
+}
+
+#[test]
+fn call_void_callback() {
+    let code = "hello(): void {}
+    run(f: (): void): void = f();
+    foo(): void = run(hello);";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@run").unwrap().llvm_represent(),
+        "define void @run({ void (i8*)*, i8* } %f) {
+  %1 = extractvalue { void (i8*)*, i8* } %f, 0
+  %2 = extractvalue { void (i8*)*, i8* } %f, 1
+  call void %1(i8* %2)
+  ret void
+}"
+    );
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define void @foo() {
+  call void @run({ void (i8*)*, i8* } { void (i8*)* @hello.closure, i8* zeroinitializer })
+  ret void
+}"
+    )
+}
+
+#[test]
+fn lambda_captures_local_variables_into_environment() {
+    let code = "adder(n: int): (int): int = (x: int): int => x + n;
+    inc(): (int): int = (x: int): int => x + 1;";
+    let module = gen_code(code);
+    assert!(module
+        .llvm_represent()
+        .contains("%elz.lambda.0.env = type { i64 }"));
+    assert_eq!(
+        module.lookup_function("@adder").unwrap().llvm_represent(),
+        "define { i64 (i8*, i64)*, i8* } @adder(i64 %n) {
+  %1 = call i8* @malloc(i64 64)
+  %2 = bitcast i8* %1 to %elz.lambda.0.env*
+  %3 = getelementptr %elz.lambda.0.env, %elz.lambda.0.env* %2, i32 0, i32 0
+  store i64 %n, i64* %3
+  %4 = insertvalue { i64 (i8*, i64)*, i8* } { i64 (i8*, i64)* @elz.lambda.0, i8* zeroinitializer }, i8* %1, 1
+  ret { i64 (i8*, i64)*, i8* } %4
+}"
+    );
+    assert_eq!(
+        module
+            .lookup_function("@elz.lambda.0")
+            .unwrap()
+            .llvm_represent(),
+        "define i64 @elz.lambda.0(i8* %env, i64 %x) {
+  %1 = bitcast i8* %env to %elz.lambda.0.env*
+  %2 = getelementptr %elz.lambda.0.env, %elz.lambda.0.env* %1, i32 0, i32 0
+  %3 = load i64, i64* %2
+  %4 = add i64 %x, %3
+  ret i64 %4
+}"
+    );
+    // nothing is captured, so there is no environment
+    assert_eq!(
+        module.lookup_function("@inc").unwrap().llvm_represent(),
+        "define { i64 (i8*, i64)*, i8* } @inc() {
+  ret { i64 (i8*, i64)*, i8* } { i64 (i8*, i64)* @elz.lambda.1, i8* zeroinitializer }
+}"
+    );
+}
+
+#[test]
+fn arguments_are_arranged_by_parameters() {
+    let code = "base: int = 100;
+    offset(x: int, by: u8 = 1, scale: int = base): int = x;
+    foo(base: int): int = offset(by: 2, x: base);";
+    let module = gen_code(code);
+    // default value refers to the global variable, not the parameter of caller
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i64 @foo(i64 %base) {
+  %1 = load i64, i64* @base
+  %2 = call i64 @offset(i64 %base, i8 2, i64 %1)
+  ret i64 %2
+}"
+    );
+}
+
+#[test]
+fn arguments_are_evaluated_in_source_order() {
+    let code = "one(): int = 1;
+    two(): u8 = 2;
+    offset(x: int, by: u8 = 1): int = x;
+    foo(): int = offset(by: two(), x: one());";
+    let module = gen_code(code);
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i64 @foo() {
+  %1 = call i8 @two()
+  %2 = call i64 @one()
+  %3 = call i64 @offset(i64 %2, i8 %1)
+  ret i64 %3
+}"
+    );
+}
+
+#[test]
+fn tuple_is_anonymous_struct_passed_by_value() {
+    let code = "origin: (int, u8) = (0, 1);
+    swap(p: (int, bool)): (bool, int) = (p.1, p.0);
+    first(p: (int, bool)): int {
+      (_, x) := swap(p);
+      return x;
+    }";
+    let module = gen_code(code);
+    assert_eq!(
+        module.variables.last().unwrap().llvm_represent(),
+        "@origin = global { i64, i8 } { i64 0, i8 1 }"
+    );
+    assert_eq!(
+        module.lookup_function("@swap").unwrap().llvm_represent(),
+        "define { i1, i64 } @swap({ i64, i1 } %p) {
+  %1 = extractvalue { i64, i1 } %p, 1
+  %2 = extractvalue { i64, i1 } %p, 0
+  %3 = insertvalue { i1, i64 } zeroinitializer, i1 %1, 0
+  %4 = insertvalue { i1, i64 } %3, i64 %2, 1
+  ret { i1, i64 } %4
+}"
+    );
+    assert_eq!(
+        module.lookup_function("@first").unwrap().llvm_represent(),
+        "define i64 @first({ i64, i1 } %p) {
+  %1 = call { i1, i64 } @swap({ i64, i1 } %p)
+  %2 = extractvalue { i1, i64 } %1, 1
+  ret i64 %2
+}"
+    );
+}
+
+#[test]
+fn extern_variable_is_external_global() {
+    let code = "
+    @extern(c)
+    errno: i32;
+    foo(): i32 = errno;";
+    let module = gen_code(code);
+    assert_eq!(
+        module.variables.last().unwrap().llvm_represent(),
+        "@errno = external global i32"
+    );
+    assert_eq!(
+        module.lookup_function("@foo").unwrap().llvm_represent(),
+        "define i32 @foo() {
+  %1 = load i32, i32* @errno
+  ret i32 %1
+}"
+    );
+}
+
 // helpers, must put tests before this line
 fn gen_code(code: &'static str) -> ir::Module {
     let mut parser = crate::parser::Parser::new("", code);
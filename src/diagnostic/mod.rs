@@ -2,50 +2,98 @@ use crate::lexer::Location;
 use codespan::Files;
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term::emit;
-use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use codespan_reporting::term::termcolor::{Buffer, ColorChoice, StandardStream};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
 
+/// Reporter reports errors with the source code around them, an error is reported by the file of
+/// its location, so the file must be added first
 pub struct Reporter {
     files: Files<String>,
+    file_ids: HashMap<String, codespan::FileId>,
+    // diagnostics are written into buffer instead of stderr when it exists
+    buffer: Option<RefCell<Buffer>>,
 }
 
 impl Reporter {
     pub fn new() -> Reporter {
         Reporter {
             files: Files::new(),
+            file_ids: HashMap::new(),
+            buffer: None,
         }
     }
-
-    pub(crate) fn for_file<T: Into<String>>(&mut self, file_name: T, source: T) -> FileID {
-        FileID {
-            value: self.files.add(file_name.into(), source.into()),
-            diagnostics: vec![],
+    /// buffered creates a reporter keeps diagnostics as text without color, see `output`
+    #[cfg(test)]
+    pub(crate) fn buffered() -> Reporter {
+        Reporter {
+            buffer: Some(RefCell::new(Buffer::no_color())),
+            ..Reporter::new()
+        }
+    }
+    /// output returns diagnostics reported by a buffered reporter
+    #[cfg(test)]
+    pub(crate) fn output(&self) -> String {
+        match &self.buffer {
+            Some(buffer) => String::from_utf8_lossy(buffer.borrow().as_slice()).to_string(),
+            None => String::new(),
         }
     }
-}
 
-#[derive(Clone)]
-pub(crate) struct FileID {
-    value: codespan::FileId,
-    diagnostics: Vec<Diagnostic>,
-}
+    pub(crate) fn add_file<T: Into<String>>(&mut self, file_name: T, source: T) {
+        let file_name = file_name.into();
+        let file_id = self.files.add(file_name.clone(), source.into());
+        self.file_ids.insert(file_name, file_id);
+    }
 
-impl FileID {
-    pub(crate) fn add_diagnostic(
-        &mut self,
+    pub(crate) fn report(&self, location: Location, long_message: String, message: String) {
+        self.report_with_related(location, long_message, message, vec![])
+    }
+
+    /// report_with_related reports an error with other related locations, e.g. the definition of
+    /// a private name
+    pub(crate) fn report_with_related(
+        &self,
         location: Location,
         long_message: String,
         message: String,
+        related: Vec<(Location, String)>,
     ) {
-        self.diagnostics.push(Diagnostic::new_error(
+        let file_id = match self.file_ids.get(location.file_name()) {
+            Some(file_id) => *file_id,
+            // e.g. error in prelude, which is not an input file
+            None => {
+                match &self.buffer {
+                    Some(buffer) => {
+                        writeln!(buffer.borrow_mut(), "error: {}", long_message).unwrap()
+                    }
+                    None => eprintln!("error: {}", long_message),
+                }
+                return;
+            }
+        };
+        let secondary_labels: Vec<Label> = related
+            .into_iter()
+            .filter_map(|(location, message)| {
+                let file_id = self.file_ids.get(location.file_name())?;
+                Some(Label::new(*file_id, location.start..location.end, message))
+            })
+            .collect();
+        let diagnostic = Diagnostic::new_error(
             long_message,
-            Label::new(self.value, location.start..location.end, message),
-        ));
-    }
-    pub(crate) fn report(&self, reporter: &Reporter) {
-        let writer = StandardStream::stderr(ColorChoice::Auto);
+            Label::new(file_id, location.start..location.end, message),
+        )
+        .with_secondary_labels(secondary_labels);
         let config = codespan_reporting::term::Config::default();
-        for diagnostic in &self.diagnostics {
-            emit(&mut writer.lock(), &config, &reporter.files, &diagnostic).unwrap();
+        match &self.buffer {
+            Some(buffer) => {
+                emit(&mut *buffer.borrow_mut(), &config, &self.files, &diagnostic).unwrap()
+            }
+            None => {
+                let writer = StandardStream::stderr(ColorChoice::Auto);
+                emit(&mut writer.lock(), &config, &self.files, &diagnostic).unwrap();
+            }
         }
     }
 }
@@ -9,23 +9,43 @@ pub enum TkType {
     Identifier,
     #[strum(serialize = "<integer>")]
     Integer,
+    #[strum(serialize = "<float>")]
+    Float,
     #[strum(serialize = "<string>")]
     String,
+    #[strum(serialize = "<raw string>")]
+    RawString,
+    // string template `"a {x} b {y} c"` is lexed as
+    // TemplateHead(`"a {`) x TemplateMiddle(`} b {`) y TemplateTail(`} c"`)
+    #[strum(serialize = "<template head>")]
+    TemplateHead,
+    #[strum(serialize = "<template middle>")]
+    TemplateMiddle,
+    #[strum(serialize = "<template tail>")]
+    TemplateTail,
     // keyword
     #[strum(serialize = "module")]
     Module,
     #[strum(serialize = "import")]
     Import,
+    #[strum(serialize = "as")]
+    As,
+    #[strum(serialize = "pub")]
+    Pub,
     #[strum(serialize = "return")]
     Return,
     #[strum(serialize = "class")]
     Class,
     #[strum(serialize = "trait")]
     Trait,
+    #[strum(serialize = "enum")]
+    Enum,
     #[strum(serialize = "if")]
     If,
     #[strum(serialize = "else")]
     Else,
+    #[strum(serialize = "match")]
+    Match,
     #[strum(serialize = "true")]
     True,
     #[strum(serialize = "false")]
@@ -43,6 +63,10 @@ pub enum TkType {
     Comma,
     #[strum(serialize = "=")]
     Equal,
+    #[strum(serialize = "==")]
+    EqualEqual,
+    #[strum(serialize = "=>")]
+    FatArrow,
     #[strum(serialize = "(")]
     OpenParen,
     #[strum(serialize = ")")]
@@ -59,6 +83,8 @@ pub enum TkType {
     Colon,
     #[strum(serialize = "::")]
     Accessor,
+    #[strum(serialize = ":=")]
+    ColonEqual,
     #[strum(serialize = ";")]
     Semicolon,
     #[strum(serialize = ".")]
@@ -103,6 +129,24 @@ impl Location {
             end,
         }
     }
+    pub fn file_name(&self) -> &str {
+        self.file_name.as_str()
+    }
+    /// shift returns the location after `text`, which starts at this location
+    pub fn shift(&self, text: &[char]) -> Location {
+        let mut location = self.clone();
+        for c in text {
+            if *c == '\n' {
+                location.line += 1;
+                location.column = 0;
+            } else {
+                location.column += 1;
+            }
+        }
+        location.start += text.len() as u32;
+        location.end = location.start;
+        location
+    }
 }
 
 impl PartialEq for Location {
@@ -159,6 +203,9 @@ struct Lexer {
     // (line, pos) represent the position for user
     pos: u32,
     line: u32,
+    // depth of braces of each unclosed interpolation of string template, `}` at depth 0 closes
+    // the interpolation
+    template_braces: Vec<u32>,
 }
 
 impl Lexer {
@@ -172,11 +219,20 @@ impl Lexer {
             offset: 0,
             pos: 0,
             line: 1,
+            template_braces: vec![],
         }
     }
 
     fn ignore(&mut self) {
-        self.pos += (self.offset - self.start) as u32;
+        // token like multi-line string can contain newline
+        for c in &self.code[self.start..self.offset] {
+            if *c == '\n' {
+                self.pos = 0;
+                self.line += 1;
+            } else {
+                self.pos += 1;
+            }
+        }
         self.start = self.offset;
     }
     fn peek(&self) -> Option<char> {
@@ -207,13 +263,17 @@ impl Lexer {
         let tok = match s.as_str() {
             "module" => self.new_token(TkType::Module, s),
             "import" => self.new_token(TkType::Import, s),
+            "as" => self.new_token(TkType::As, s),
+            "pub" => self.new_token(TkType::Pub, s),
             "return" => self.new_token(TkType::Return, s),
             "true" => self.new_token(TkType::True, s),
             "false" => self.new_token(TkType::False, s),
             "class" => self.new_token(TkType::Class, s),
             "trait" => self.new_token(TkType::Trait, s),
+            "enum" => self.new_token(TkType::Enum, s),
             "if" => self.new_token(TkType::If, s),
             "else" => self.new_token(TkType::Else, s),
+            "match" => self.new_token(TkType::Match, s),
             _ => self.new_token(token_type.clone(), s),
         };
         match token_type {
@@ -245,7 +305,15 @@ fn whitespace(lexer: &mut Lexer) -> State {
         Some(_c @ '0'..='9') => State::Fn(number),
         Some('=') => {
             lexer.next();
-            lexer.emit(TkType::Equal);
+            if lexer.peek() == Some('=') {
+                lexer.next();
+                lexer.emit(TkType::EqualEqual);
+            } else if lexer.peek() == Some('>') {
+                lexer.next();
+                lexer.emit(TkType::FatArrow);
+            } else {
+                lexer.emit(TkType::Equal);
+            }
             State::Fn(whitespace)
         }
         Some(',') => {
@@ -307,11 +375,22 @@ fn whitespace(lexer: &mut Lexer) -> State {
             State::Fn(whitespace)
         }
         Some('{') => {
+            if let Some(depth) = lexer.template_braces.last_mut() {
+                *depth += 1;
+            }
             lexer.next();
             lexer.emit(TkType::OpenBrace);
             State::Fn(whitespace)
         }
         Some('}') => {
+            if lexer.template_braces.last() == Some(&0) {
+                // end of interpolation, back to string
+                lexer.template_braces.pop();
+                return State::Fn(string);
+            }
+            if let Some(depth) = lexer.template_braces.last_mut() {
+                *depth -= 1;
+            }
             lexer.next();
             lexer.emit(TkType::CloseBrace);
             State::Fn(whitespace)
@@ -336,6 +415,9 @@ fn whitespace(lexer: &mut Lexer) -> State {
             if lexer.peek() == Some(':') {
                 lexer.next();
                 lexer.emit(TkType::Accessor);
+            } else if lexer.peek() == Some('=') {
+                lexer.next();
+                lexer.emit(TkType::ColonEqual);
             } else {
                 lexer.emit(TkType::Colon);
             }
@@ -352,6 +434,7 @@ fn whitespace(lexer: &mut Lexer) -> State {
             State::Fn(whitespace)
         }
         Some('"') => State::Fn(string),
+        Some('r') if is_raw_string_start(lexer) => State::Fn(raw_string),
         Some(c) => {
             if in_identifier_set(c) {
                 State::Fn(ident)
@@ -377,27 +460,109 @@ fn ident(lexer: &mut Lexer) -> State {
     State::Fn(whitespace)
 }
 
+/// string lexes from `"` or the `}` closing an interpolation, to `"` or the `{` starting an
+/// interpolation, the expression in interpolation is lexed as usual
 fn string(lexer: &mut Lexer) -> State {
+    let from_quote = lexer.peek() == Some('"');
+    let mut end_type = if from_quote {
+        TkType::String
+    } else {
+        TkType::TemplateTail
+    };
     while let Some(c) = lexer.next() {
         if c == '\\' {
-            lexer.next();
+            // `{` of `\u{...}` is not an interpolation
+            if lexer.next() == Some('u') && lexer.code.get(lexer.offset + 1) == Some(&'{') {
+                while !matches!(lexer.peek(), Some('}') | Some('"') | None) {
+                    lexer.next();
+                }
+            }
+            continue;
         }
         if c == '"' {
             break;
         }
+        if c == '{' {
+            end_type = if from_quote {
+                TkType::TemplateHead
+            } else {
+                TkType::TemplateMiddle
+            };
+            lexer.template_braces.push(0);
+            break;
+        }
+    }
+    // unterminated string ends at EOF
+    lexer.offset = std::cmp::min(lexer.offset + 1, lexer.code.len());
+    lexer.emit(end_type);
+    State::Fn(whitespace)
+}
+
+/// is_raw_string_start checks `r"` or `r#"`, the number of `#` is not limited
+fn is_raw_string_start(lexer: &Lexer) -> bool {
+    let mut offset = lexer.offset + 1;
+    while lexer.code.get(offset) == Some(&'#') {
+        offset += 1;
+    }
+    lexer.code.get(offset) == Some(&'"')
+}
+
+/// raw_string lexes `r"..."`, or `r#"..."#` when the string contains `"`, nothing would be escaped
+fn raw_string(lexer: &mut Lexer) -> State {
+    let mut hashes = 0;
+    while lexer.next() == Some('#') {
+        hashes += 1;
+    }
+    while let Some(c) = lexer.next() {
+        if c == '"' && (1..=hashes).all(|n| lexer.code.get(lexer.offset + n) == Some(&'#')) {
+            lexer.offset += hashes;
+            break;
+        }
     }
     lexer.next();
-    lexer.emit(TkType::String);
+    lexer.emit(TkType::RawString);
     State::Fn(whitespace)
 }
 
 fn number(lexer: &mut Lexer) -> State {
+    // `0xFF`, `0o17` and `0b1010`, the digits are checked by parser, so `0b12` is an error
+    // instead of two tokens
+    if lexer.peek() == Some('0')
+        && matches!(lexer.code.get(lexer.offset + 1), Some('x' | 'o' | 'b'))
+    {
+        lexer.next();
+        while let Some(c) = lexer.next() {
+            if !c.is_ascii_alphanumeric() && c != '_' {
+                break;
+            }
+        }
+        lexer.emit(TkType::Integer);
+        return State::Fn(whitespace);
+    }
+    // `_` separates digits, e.g. `1_000_000`
+    while let Some(c) = lexer.next() {
+        if !c.is_ascii_digit() && c != '_' {
+            break;
+        }
+    }
+    // `1.5` is a float, but `1.to_string()` is a method call on integer, and `t.0.1` accesses
+    // element of tuple twice
+    let after_dot = lexer.tokens.last().map(|tok| tok.tk_type()) == Some(&TkType::Dot);
+    let is_fraction = match lexer.code.get(lexer.offset + 1) {
+        Some(c) => !after_dot && lexer.peek() == Some('.') && c.is_ascii_digit(),
+        None => false,
+    };
+    if !is_fraction {
+        lexer.emit(TkType::Integer);
+        return State::Fn(whitespace);
+    }
+    lexer.next();
     while let Some(c) = lexer.next() {
-        if !c.is_digit(10) {
+        if !c.is_ascii_digit() && c != '_' {
             break;
         }
     }
-    lexer.emit(TkType::Integer);
+    lexer.emit(TkType::Float);
     State::Fn(whitespace)
 }
 
@@ -3,7 +3,7 @@ use super::*;
 
 #[test]
 fn test_symbols() {
-    let code = "+ - * / , = ( ) [ ] { } : :: ; . <: @";
+    let code = "+ - * / , = == => ( ) [ ] { } : :: := ; . <: @";
 
     let tokens = lex("", code);
     let tk_types: Vec<_> = tokens.iter().map(|tok| tok.tk_type()).collect();
@@ -17,6 +17,8 @@ fn test_symbols() {
             &Divide,
             &Comma,
             &Equal,
+            &EqualEqual,
+            &FatArrow,
             &OpenParen,
             &CloseParen,
             &OpenBracket,
@@ -25,6 +27,7 @@ fn test_symbols() {
             &CloseBrace,
             &Colon,
             &Accessor,
+            &ColonEqual,
             &Semicolon,
             &Dot,
             &IsSubTypeOf,
@@ -36,14 +39,17 @@ fn test_symbols() {
 
 #[test]
 fn test_keywords() {
-    let code = "module import return class trait true false if else";
+    let code = "module import return class trait enum true false if else match";
 
     let tokens = lex("", code);
     let tk_types: Vec<_> = tokens.iter().map(|tok| tok.tk_type()).collect();
     use TkType::*;
     assert_eq!(
         tk_types,
-        vec![&Module, &Import, &Return, &Class, &Trait, &True, &False, &If, &Else, &EOF]
+        vec![
+            &Module, &Import, &Return, &Class, &Trait, &Enum, &True, &False, &If, &Else, &Match,
+            &EOF
+        ]
     )
 }
 
@@ -77,6 +83,54 @@ fn get_number_tokens() {
     );
 }
 
+#[test]
+fn get_float_tokens() {
+    let ts = lex("", "1.5 2.to_string");
+    assert_eq!(
+        ts,
+        vec![
+            Token(Location::from(1, 0), Float, "1.5".to_string()),
+            Token(Location::from(1, 4), Integer, "2".to_string()),
+            Token(Location::from(1, 5), Dot, ".".to_string()),
+            Token(Location::from(1, 6), Identifier, "to_string".to_string()),
+            Token(Location::from(1, 15), EOF, "".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn element_index_of_tuple_is_not_float() {
+    let ts = lex("", "t.0.1");
+    assert_eq!(
+        ts,
+        vec![
+            Token(Location::from(1, 0), Identifier, "t".to_string()),
+            Token(Location::from(1, 1), Dot, ".".to_string()),
+            Token(Location::from(1, 2), Integer, "0".to_string()),
+            Token(Location::from(1, 3), Dot, ".".to_string()),
+            Token(Location::from(1, 4), Integer, "1".to_string()),
+            Token(Location::from(1, 5), EOF, "".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn get_integer_tokens_with_prefix_and_separator() {
+    let ts = lex("", "0xFF_ff 0o17 0b10_1 1_000 0b12 1_0.5");
+    assert_eq!(
+        ts,
+        vec![
+            Token(Location::from(1, 0), Integer, "0xFF_ff".to_string()),
+            Token(Location::from(1, 8), Integer, "0o17".to_string()),
+            Token(Location::from(1, 13), Integer, "0b10_1".to_string()),
+            Token(Location::from(1, 20), Integer, "1_000".to_string()),
+            Token(Location::from(1, 26), Integer, "0b12".to_string()),
+            Token(Location::from(1, 31), Float, "1_0.5".to_string()),
+            Token(Location::from(1, 36), EOF, "".to_string()),
+        ]
+    );
+}
+
 #[test]
 fn get_ident_tokens() {
     let ts = lex("", " abc6");
@@ -101,6 +155,76 @@ fn get_escape_char_in_string() {
     )
 }
 
+#[test]
+fn raw_string() {
+    let ts = lex("", "r\"\\n\" r#\"a \"quoted\" b\"# r");
+    assert_eq!(
+        ts,
+        vec![
+            Token(Location::from(1, 0), RawString, "r\"\\n\"".to_string()),
+            Token(
+                Location::from(1, 6),
+                RawString,
+                "r#\"a \"quoted\" b\"#".to_string()
+            ),
+            Token(Location::from(1, 24), Identifier, "r".to_string()),
+            Token(Location::from(1, 25), EOF, "".to_string()),
+        ]
+    )
+}
+
+#[test]
+fn multi_line_string_updates_line() {
+    let ts = lex("", "\"a\nbc\" 1");
+    assert_eq!(
+        ts,
+        vec![
+            Token(Location::from(1, 0), String, "\"a\nbc\"".to_string()),
+            Token(Location::from(2, 4), Integer, "1".to_string()),
+            Token(Location::from(2, 5), EOF, "".to_string()),
+        ]
+    )
+}
+
+#[test]
+fn string_template() {
+    let ts = lex("", "\"a {f(\"{x}\")} b {Foo {}}\"");
+    let tk_types: Vec<_> = ts.iter().map(|tok| tok.tk_type()).collect();
+    assert_eq!(
+        tk_types,
+        vec![
+            &TemplateHead,
+            &Identifier,
+            &OpenParen,
+            &TemplateHead,
+            &Identifier,
+            &TemplateTail,
+            &CloseParen,
+            &TemplateMiddle,
+            &Identifier,
+            &OpenBrace,
+            &CloseBrace,
+            &TemplateTail,
+            &EOF,
+        ]
+    );
+    assert_eq!(
+        ts[1],
+        Token(Location::from(1, 4), Identifier, "f".to_string())
+    );
+    assert_eq!(
+        ts[7],
+        Token(Location::from(1, 12), TemplateMiddle, "} b {".to_string())
+    );
+}
+
+#[test]
+fn escaped_brace_is_not_interpolation() {
+    let ts = lex("", "\"\\{x\\} \\u{41}\"");
+    let tk_types: Vec<_> = ts.iter().map(|tok| tok.tk_type()).collect();
+    assert_eq!(tk_types, vec![&String, &EOF]);
+}
+
 #[test]
 fn comment_would_be_discard() {
     let ts = lex("", "//\n1");
@@ -1,8 +1,14 @@
 pub mod ast;
+pub mod ast_printer;
 pub mod cmd;
 pub mod codegen;
 pub mod diagnostic;
 pub mod lexer;
+pub mod loader;
 pub mod parser;
 pub mod prelude;
+pub mod project;
 pub mod semantic;
+
+#[cfg(test)]
+mod temp_dir;
@@ -0,0 +1,127 @@
+use crate::lexer::Location;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("{location} {err}")]
+pub struct LoadError {
+    location: Location,
+    err: LoadErrorVariant,
+}
+
+#[derive(Debug, Error)]
+enum LoadErrorVariant {
+    #[error("cannot find module `{}`, searched: {}", .module_name, .searched.join(", "))]
+    ModuleNotFound {
+        module_name: String,
+        searched: Vec<String>,
+    },
+    #[error("file `{}` is module `{}` but imported as `{}`", .file_name, .found, .expected)]
+    ModuleNameMismatch {
+        file_name: String,
+        expected: String,
+        found: String,
+    },
+    #[error("import cycle: {}", ShowImportChain(.0.to_vec()))]
+    ImportCycle(Vec<(String, Location)>),
+    #[error("module `{}` is defined twice, also defined at {}", .module_name, .other_definition)]
+    ModuleDefinedTwice {
+        module_name: String,
+        other_definition: Location,
+    },
+}
+
+impl LoadError {
+    fn new(location: &Location, err: LoadErrorVariant) -> LoadError {
+        LoadError {
+            location: location.clone(),
+            err,
+        }
+    }
+    pub fn location(&self) -> Location {
+        self.location.clone()
+    }
+    pub fn message(&self) -> String {
+        use LoadErrorVariant::*;
+        match self.err {
+            ModuleNotFound { .. } => "module not found",
+            ModuleNameMismatch { .. } => "module name mismatched",
+            ImportCycle(..) => "import cycle",
+            ModuleDefinedTwice { .. } => "module defined twice",
+        }
+        .to_string()
+    }
+    /// related returns other locations should be shown with the error
+    pub fn related(&self) -> Vec<(Location, String)> {
+        match &self.err {
+            LoadErrorVariant::ModuleDefinedTwice {
+                other_definition, ..
+            } => vec![(other_definition.clone(), "also defined here".to_string())],
+            _ => vec![],
+        }
+    }
+
+    pub fn module_not_found(
+        location: &Location,
+        module_name: impl ToString,
+        searched: Vec<String>,
+    ) -> LoadError {
+        LoadError::new(
+            location,
+            LoadErrorVariant::ModuleNotFound {
+                module_name: module_name.to_string(),
+                searched,
+            },
+        )
+    }
+    pub fn module_name_mismatch(
+        location: &Location,
+        file_name: impl ToString,
+        expected: impl ToString,
+        found: impl ToString,
+    ) -> LoadError {
+        LoadError::new(
+            location,
+            LoadErrorVariant::ModuleNameMismatch {
+                file_name: file_name.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            },
+        )
+    }
+    /// import_cycle reports at the last import, `chain` is the imported modules with the
+    /// location of their import, the first module imports the second one and so on
+    pub fn import_cycle(location: &Location, chain: Vec<(String, Location)>) -> LoadError {
+        LoadError::new(location, LoadErrorVariant::ImportCycle(chain))
+    }
+    pub fn module_defined_twice(
+        location: &Location,
+        module_name: impl ToString,
+        other_definition: Location,
+    ) -> LoadError {
+        LoadError::new(
+            location,
+            LoadErrorVariant::ModuleDefinedTwice {
+                module_name: module_name.to_string(),
+                other_definition,
+            },
+        )
+    }
+}
+
+struct ShowImportChain(Vec<(String, Location)>);
+impl std::fmt::Display for ShowImportChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (index, (module_name, location)) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, " -> ")?;
+            }
+            // input files are loaded without import
+            if location.file_name().is_empty() {
+                write!(f, "`{}`", module_name)?;
+            } else {
+                write!(f, "`{}` (imported at {})", module_name, location)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+use crate::ast::{Import, ImportedComponent, Module, TopAst};
+use crate::diagnostic::Reporter;
+use crate::lexer::Location;
+use crate::parser::{parse_prelude, Parser};
+use crate::prelude::Std;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+mod error;
+#[cfg(test)]
+mod tests;
+
+pub use error::LoadError;
+
+pub const ELZ_PATH: &str = "ELZ_PATH";
+/// modules under `std` are the standard library embedded in compiler, e.g. `std.io`
+pub const STD: &str = "std";
+
+/// Loader loads input files and the modules they import, a module `a.b.c` is the file
+/// `a/b/c.elz` under one of search paths
+pub struct Loader {
+    search_paths: Vec<PathBuf>,
+}
+
+impl Loader {
+    pub fn new(search_paths: Vec<PathBuf>) -> Loader {
+        Loader { search_paths }
+    }
+    /// for_files searches modules in the directory of the first input file, then `lib_dirs`,
+    /// then the directories in environment variable `ELZ_PATH`
+    pub fn for_files(files: &[&str], lib_dirs: Vec<&str>) -> Loader {
+        let mut search_paths = vec![];
+        if let Some(file) = files.first() {
+            let source_root = match Path::new(file).parent() {
+                Some(dir) => dir.to_path_buf(),
+                None => PathBuf::new(),
+            };
+            search_paths.push(source_root);
+        }
+        for dir in lib_dirs {
+            search_paths.push(PathBuf::from(dir));
+        }
+        if let Some(paths) = std::env::var_os(ELZ_PATH) {
+            search_paths.extend(std::env::split_paths(&paths));
+        }
+        Loader::new(search_paths)
+    }
+
+    /// load parses input files and all modules imported by them, the prelude is the first module
+    /// and a module comes after modules it imports. Errors were reported by `reporter`.
+    pub fn load(
+        &self,
+        reporter: &mut Reporter,
+        files: Vec<&str>,
+    ) -> std::result::Result<Vec<Module>, Box<dyn std::error::Error>> {
+        let mut loading = Loading {
+            loader: self,
+            reporter,
+            modules: HashMap::new(),
+            visiting: vec![],
+            visited: HashSet::new(),
+            inputs: HashMap::new(),
+            order: vec![parse_prelude()],
+        };
+        let mut input_modules = vec![];
+        for file_name in files {
+            input_modules.push(loading.add_input(Path::new(file_name))?);
+        }
+        for module_name in input_modules {
+            loading.visit(&module_name, &Location::none())?;
+        }
+        Ok(loading.order)
+    }
+
+    /// find returns the source of module, or the paths were searched when not found
+    fn find(&self, module_name: &str) -> std::result::Result<Source, Vec<String>> {
+        if let Some(std_module) = module_name.strip_prefix(&format!("{}.", STD)) {
+            let relative_path = module_path(std_module);
+            let file_name = Path::new(STD).join(&relative_path).display().to_string();
+            return match Std::get(&relative_path.to_string_lossy()) {
+                Some(content) => Ok(Source::Std {
+                    file_name,
+                    code: String::from_utf8_lossy(content.as_ref()).to_string(),
+                }),
+                None => Err(vec![file_name]),
+            };
+        }
+        let relative_path = module_path(module_name);
+        let mut searched = vec![];
+        for dir in &self.search_paths {
+            let path = dir.join(&relative_path);
+            if path.is_file() {
+                return Ok(Source::File(path));
+            }
+            searched.push(path.display().to_string());
+        }
+        Err(searched)
+    }
+}
+
+/// module_path returns the path of module file relative to a search path, e.g. `a/b.elz` for
+/// module `a.b`
+pub(crate) fn module_path(module_name: &str) -> PathBuf {
+    let mut path: PathBuf = module_name.split('.').collect();
+    path.set_extension("elz");
+    path
+}
+
+enum Source {
+    File(PathBuf),
+    // module of standard library, it has no file
+    Std { file_name: String, code: String },
+}
+
+struct Loading<'a> {
+    loader: &'a Loader,
+    reporter: &'a mut Reporter,
+    // parsed modules, by module name
+    modules: HashMap<String, Module>,
+    // modules are loading, with the location they were imported at
+    visiting: Vec<(String, Location)>,
+    visited: HashSet<String>,
+    // input modules are not imported yet, with their files and locations
+    inputs: HashMap<String, (PathBuf, Location)>,
+    order: Vec<Module>,
+}
+
+impl<'a> Loading<'a> {
+    fn visit(
+        &mut self,
+        module_name: &String,
+        location: &Location,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        if self.visited.contains(module_name) {
+            return Ok(());
+        }
+        if let Some(start) = self.visiting.iter().position(|(m, _)| m == module_name) {
+            let mut chain = self.visiting[start..].to_vec();
+            chain.push((module_name.clone(), location.clone()));
+            return self.report(LoadError::import_cycle(location, chain));
+        }
+        if !self.modules.contains_key(module_name) {
+            let module = self.load_module(module_name, location)?;
+            self.modules.insert(module_name.clone(), module);
+        }
+        let imports: Vec<Import> = self.modules[module_name]
+            .top_list
+            .iter()
+            .filter_map(|top| match top {
+                TopAst::Import(i) if i.import_path != "prelude" => Some(i.clone()),
+                _ => None,
+            })
+            .collect();
+        self.visiting.push((module_name.clone(), location.clone()));
+        for import in imports {
+            if let Some((path, location)) = self.inputs.remove(&import.import_path) {
+                self.check_shadowed_module(&import.import_path, &path, &location)?;
+            }
+            self.visit(&import.import_path, &import.location)?;
+        }
+        self.visiting.pop();
+        self.visited.insert(module_name.clone());
+        let module = self.modules.remove(module_name).unwrap();
+        self.order.push(module);
+        Ok(())
+    }
+
+    /// add_input parses an input file and returns its module name, the name cannot be used by
+    /// another input file
+    fn add_input(
+        &mut self,
+        path: &Path,
+    ) -> std::result::Result<String, Box<dyn std::error::Error>> {
+        let module = self.parse_file(path)?;
+        if let Some(other) = self.modules.get(&module.name) {
+            return self.report(LoadError::module_defined_twice(
+                &module.location,
+                &module.name,
+                other.location.clone(),
+            ));
+        }
+        let module_name = module.name.clone();
+        self.inputs.insert(
+            module_name.clone(),
+            (path.to_path_buf(), module.location.clone()),
+        );
+        self.modules.insert(module_name.clone(), module);
+        Ok(module_name)
+    }
+
+    /// check_shadowed_module reports an imported input module when the import also can find the
+    /// module in search paths, since it's unclear which one is imported. An input module is not
+    /// checked until it's imported, e.g. many files of `module main` can be in one directory.
+    fn check_shadowed_module(
+        &mut self,
+        module_name: &str,
+        path: &Path,
+        location: &Location,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let other = match self.loader.find(module_name) {
+            Ok(Source::File(other_path)) if !same_file(path, &other_path) => {
+                self.parse_file(&other_path)?
+            }
+            Ok(Source::Std { file_name, code }) => self.parse_source(&file_name, code)?,
+            _ => return Ok(()),
+        };
+        if other.name == module_name {
+            return self.report(LoadError::module_defined_twice(
+                location,
+                module_name,
+                other.location,
+            ));
+        }
+        Ok(())
+    }
+
+    fn load_module(
+        &mut self,
+        module_name: &str,
+        location: &Location,
+    ) -> std::result::Result<Module, Box<dyn std::error::Error>> {
+        let (file_name, module) = match self.loader.find(module_name) {
+            Ok(Source::File(path)) => (path.display().to_string(), self.parse_file(&path)?),
+            Ok(Source::Std { file_name, code }) => {
+                let module = self.parse_source(&file_name, code)?;
+                (file_name, module)
+            }
+            Err(searched) => {
+                return self.report(LoadError::module_not_found(location, module_name, searched))
+            }
+        };
+        if module.name != module_name {
+            return self.report(LoadError::module_name_mismatch(
+                location,
+                file_name,
+                module_name,
+                &module.name,
+            ));
+        }
+        Ok(module)
+    }
+
+    fn parse_file(
+        &mut self,
+        path: &Path,
+    ) -> std::result::Result<Module, Box<dyn std::error::Error>> {
+        let file_name = path.display().to_string();
+        let code = std::fs::read_to_string(path)
+            .map_err(|err| format!("cannot read {}: {}", file_name, err))?;
+        self.parse_source(&file_name, code)
+    }
+
+    fn parse_source(
+        &mut self,
+        file_name: &str,
+        code: String,
+    ) -> std::result::Result<Module, Box<dyn std::error::Error>> {
+        self.reporter.add_file(file_name, code.as_str());
+        let mut module = match Parser::parse_program(file_name, code.as_str()) {
+            Ok(module) => module,
+            Err(err) => {
+                self.reporter
+                    .report(err.location(), format!("{}", err), err.message());
+                return Err(err.into());
+            }
+        };
+        // every module imports all `pub` definitions of prelude, before its own imports, so its
+        // own definitions and imports can hide them
+        module.top_list.insert(
+            0,
+            TopAst::Import(Import {
+                location: Location::none(),
+                import_path: "prelude".to_string(),
+                alias: None,
+                imported_component: vec![ImportedComponent::All(Location::none())],
+            }),
+        );
+        Ok(module)
+    }
+
+    fn report<T>(&self, err: LoadError) -> std::result::Result<T, Box<dyn std::error::Error>> {
+        self.reporter.report_with_related(
+            err.location(),
+            format!("{}", err),
+            err.message(),
+            err.related(),
+        );
+        Err(err.into())
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,241 @@
+use super::*;
+use crate::temp_dir::TempDir;
+
+#[test]
+fn load_imported_modules_transitively() {
+    let root = source_root(
+        "transitively",
+        vec![
+            ("main.elz", "module main\nimport a (x)\n"),
+            ("a.elz", "module a\nimport b.c (y)\nx: int = y;\n"),
+            ("b/c.elz", "module b.c\ny: int = 1;\n"),
+        ],
+    );
+    let modules = load(&root, vec![], "main.elz").unwrap();
+    let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["prelude", "b.c", "a", "main"]);
+}
+
+#[test]
+fn search_modules_in_library_directories() {
+    let root = source_root(
+        "library",
+        vec![("main.elz", "module main\nimport io (x)\n")],
+    );
+    let lib = source_root("library_lib", vec![("io.elz", "module io\nx: int = 1;\n")]);
+    let modules = load(&root, vec![lib.to_path_buf()], "main.elz").unwrap();
+    assert_eq!(modules[1].name, "io");
+}
+
+#[test]
+fn module_not_found() {
+    let root = source_root(
+        "not_found",
+        vec![("main.elz", "module main\nimport io (x)\n")],
+    );
+    let (result, output) = load_files(vec![root.to_path_buf()], vec![root.join("main.elz")]);
+    let err = result.unwrap_err();
+    assert_eq!(err.message(), "module not found");
+    assert!(output.contains("cannot find module `io`"));
+    assert_eq!(
+        err.location().to_string(),
+        format!("{}:2:0", root.join("main.elz").display())
+    );
+}
+
+#[test]
+fn module_header_must_match_import_path() {
+    let root = source_root(
+        "mismatch",
+        vec![
+            ("main.elz", "module main\nimport io (x)\n"),
+            ("io.elz", "module other\nx: int = 1;\n"),
+        ],
+    );
+    let err = load(&root, vec![], "main.elz").unwrap_err();
+    assert_eq!(err.message(), "module name mismatched");
+}
+
+#[test]
+fn import_cycle_shows_chain_of_imports() {
+    let root = source_root(
+        "cycle",
+        vec![
+            ("main.elz", "module main\nimport a (x)\n"),
+            ("a.elz", "module a\nimport b (y)\nx: int = 1;\n"),
+            ("b.elz", "module b\nimport a (x)\ny: int = 1;\n"),
+        ],
+    );
+    let err = load(&root, vec![], "main.elz").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{b}:2:0 import cycle: `a` (imported at {main}:2:0) -> `b` (imported at {a}:2:0) -> `a` (imported at {b}:2:0)",
+            main = root.join("main.elz").display(),
+            a = root.join("a.elz").display(),
+            b = root.join("b.elz").display(),
+        )
+    );
+}
+
+#[test]
+fn modules_import_public_names_of_prelude_by_default() {
+    let root = source_root(
+        "prelude",
+        vec![("main.elz", "module main\nprintln(s: string): void {}\n")],
+    );
+    let modules = load(&root, vec![], "main.elz").unwrap();
+    assert_eq!(
+        modules[1].top_list[0],
+        TopAst::Import(Import {
+            location: Location::none(),
+            import_path: "prelude".to_string(),
+            alias: None,
+            imported_component: vec![ImportedComponent::All(Location::none())],
+        })
+    );
+    let mut checker = crate::semantic::SemanticChecker::new();
+    assert!(checker.check_program(&modules).is_ok());
+}
+
+#[test]
+fn standard_library_is_embedded() {
+    let root = source_root(
+        "std",
+        vec![(
+            "main.elz",
+            "module main\nimport std.io (print)\nimport std.math (sqrt, to_f64)\nmain(): void {\n  print(\"{sqrt(to_f64(2))}\");\n}\n",
+        )],
+    );
+    let modules = load(&root, vec![], "main.elz").unwrap();
+    let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["prelude", "std.io", "std.math", "main"]);
+    let mut checker = crate::semantic::SemanticChecker::new();
+    assert!(checker.check_program(&modules).is_ok());
+}
+
+#[test]
+fn standard_library_module_not_found() {
+    let root = source_root(
+        "std_not_found",
+        vec![("main.elz", "module main\nimport std.nope (x)\n")],
+    );
+    let err = load(&root, vec![], "main.elz").unwrap_err();
+    assert_eq!(err.message(), "module not found");
+}
+
+#[test]
+fn input_files_define_the_same_module() {
+    let root = source_root(
+        "twice",
+        vec![
+            ("main.elz", "module main\n"),
+            ("other.elz", "module main\n"),
+        ],
+    );
+    let (result, output) = load_files(
+        vec![root.to_path_buf()],
+        vec![root.join("main.elz"), root.join("other.elz")],
+    );
+    let err = result.unwrap_err();
+    assert_eq!(err.message(), "module defined twice");
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{other}:1:0 module `main` is defined twice, also defined at {main}:1:0",
+            main = root.join("main.elz").display(),
+            other = root.join("other.elz").display(),
+        )
+    );
+    assert!(output.contains("also defined here"));
+    // the same file twice
+    let (result, _) = load_files(
+        vec![root.to_path_buf()],
+        vec![root.join("main.elz"), root.join("main.elz")],
+    );
+    assert_eq!(result.unwrap_err().message(), "module defined twice");
+}
+
+#[test]
+fn imported_input_module_clashes_with_module_in_search_path() {
+    let root = source_root(
+        "clash",
+        vec![
+            ("src/main.elz", "module main\nimport util\n"),
+            ("src/utility.elz", "module util\n"),
+        ],
+    );
+    let lib = source_root("clash_lib", vec![("util.elz", "module util\n")]);
+    let inputs = vec![root.join("src/utility.elz"), root.join("src/main.elz")];
+    let (result, _) = load_files(vec![root.join("src")], inputs.clone());
+    assert!(result.is_ok());
+    let (result, output) = load_files(vec![root.join("src"), lib.to_path_buf()], inputs);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        format!(
+            "{input}:1:0 module `util` is defined twice, also defined at {lib}:1:0",
+            input = root.join("src/utility.elz").display(),
+            lib = lib.join("util.elz").display(),
+        )
+    );
+    assert!(output.contains("also defined here"));
+    let std = source_root(
+        "clash_std",
+        vec![
+            ("main.elz", "module main\nimport std.io\n"),
+            ("io.elz", "module std.io\n"),
+        ],
+    );
+    let (result, _) = load_files(
+        vec![std.to_path_buf()],
+        vec![std.join("io.elz"), std.join("main.elz")],
+    );
+    assert_eq!(result.unwrap_err().message(), "module defined twice");
+}
+
+#[test]
+fn input_module_is_not_checked_until_imported() {
+    // many programs of `module main` in one directory
+    let root = source_root(
+        "programs",
+        vec![
+            ("main.elz", "module main\nmain(): void {}\n"),
+            ("a.elz", "module main\nmain(): void {}\n"),
+        ],
+    );
+    let modules = load(&root, vec![], "a.elz").unwrap();
+    assert_eq!(
+        modules[1].location.file_name(),
+        root.join("a.elz").display().to_string()
+    );
+    let std = source_root("own_std", vec![("io.elz", "module std.io\n")]);
+    assert!(load(&std, vec![], "io.elz").is_ok());
+}
+
+// helpers, must put tests before this line
+fn source_root(name: &str, files: Vec<(&str, &str)>) -> TempDir {
+    TempDir::with_files(&format!("loader-{}", name), files)
+}
+
+fn load(root: &Path, lib_dirs: Vec<PathBuf>, file_name: &str) -> Result<Vec<Module>, LoadError> {
+    let mut search_paths = vec![root.to_path_buf()];
+    search_paths.extend(lib_dirs);
+    load_files(search_paths, vec![root.join(file_name)]).0
+}
+
+/// load_files loads input files, and returns the result with diagnostics were reported
+fn load_files(
+    search_paths: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+) -> (Result<Vec<Module>, LoadError>, String) {
+    let loader = Loader::new(search_paths);
+    let file_names: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+    let mut reporter = Reporter::buffered();
+    let result = loader
+        .load(
+            &mut reporter,
+            file_names.iter().map(|f| f.as_str()).collect(),
+        )
+        .map_err(|err| *err.downcast::<LoadError>().unwrap());
+    (result, reporter.output())
+}
@@ -1,5 +1,8 @@
-use clap::{App, Arg, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
 use elz::cmd;
+use elz::loader::LoadError;
+use elz::parser::ParseError;
+use elz::semantic::SemanticError;
 
 fn main() {
     let matches = App::new("elz")
@@ -12,6 +15,76 @@ fn main() {
                         .help("input file to compile")
                         .required(true)
                         .min_values(1),
+                )
+                .arg(
+                    Arg::with_name("lib-dir")
+                        .long("lib-dir")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("DIR")
+                        .help("search imported modules in DIR"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("write output into FILE instead of stdout"),
+                )
+                .arg(
+                    Arg::with_name("emit")
+                        .long("emit")
+                        .takes_value(true)
+                        .value_name("STAGE")
+                        .possible_values(cmd::compile::Emit::STAGES)
+                        .default_value("llvm")
+                        .help("stage of compiler to output"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::check::CMD_NAME)
+                .about("parse and check input files without generating code")
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("input files to check")
+                        .required(true)
+                        .min_values(1),
+                )
+                .arg(
+                    Arg::with_name("lib-dir")
+                        .long("lib-dir")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("DIR")
+                        .help("search imported modules in DIR"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::build::CMD_NAME)
+                .about("compile the project described by elz.toml")
+                .arg(
+                    Arg::with_name("DIR")
+                        .help("directory of project")
+                        .default_value("."),
+                )
+                .arg(
+                    Arg::with_name("out-dir")
+                        .long("out-dir")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help("write output into DIR instead of build directory of project"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::new::CMD_NAME)
+                .about("create a new project")
+                .arg(
+                    Arg::with_name("PATH")
+                        .help("directory of project, the project is named by its last component")
+                        .required(true),
                 ),
         )
         .subcommand(
@@ -28,15 +101,49 @@ fn main() {
 
     if let Some(compile_args) = matches.subcommand_matches(cmd::compile::CMD_NAME) {
         let files: Vec<_> = compile_args.values_of("INPUT").unwrap().collect();
-        match cmd::compile::compile(files) {
-            Ok(..) => (),
-            Err(..) => println!("compile failed"),
-        }
-    } else if let Some(compile_args) = matches.subcommand_matches(cmd::fmt::CMD_NAME) {
-        let files: Vec<_> = compile_args.values_of("INPUT").unwrap().collect();
-        match cmd::fmt::format(files) {
-            Ok(..) => (),
-            Err(..) => println!("format failed"),
+        let lib_dirs = lib_dirs(compile_args);
+        let output = compile_args.value_of("output");
+        // clap already rejected unknown stages
+        let emit = compile_args.value_of("emit").unwrap().parse().unwrap();
+        exit_on_error(
+            "compile",
+            cmd::compile::compile(files, lib_dirs, output, emit),
+        );
+    } else if let Some(check_args) = matches.subcommand_matches(cmd::check::CMD_NAME) {
+        let files: Vec<_> = check_args.values_of("INPUT").unwrap().collect();
+        exit_on_error("check", cmd::check::check(files, lib_dirs(check_args)));
+    } else if let Some(build_args) = matches.subcommand_matches(cmd::build::CMD_NAME) {
+        let dir = build_args.value_of("DIR").unwrap();
+        let out_dir = build_args.value_of("out-dir");
+        exit_on_error("build", cmd::build::build(dir, out_dir));
+    } else if let Some(new_args) = matches.subcommand_matches(cmd::new::CMD_NAME) {
+        let path = new_args.value_of("PATH").unwrap();
+        exit_on_error("new", cmd::new::new(path));
+    } else if let Some(fmt_args) = matches.subcommand_matches(cmd::fmt::CMD_NAME) {
+        let files: Vec<_> = fmt_args.values_of("INPUT").unwrap().collect();
+        exit_on_error("format", cmd::fmt::format(files));
+    }
+}
+
+fn lib_dirs<'a>(args: &'a ArgMatches) -> Vec<&'a str> {
+    match args.values_of("lib-dir") {
+        Some(dirs) => dirs.collect(),
+        None => vec![],
+    }
+}
+
+/// exit_on_error exits with non-zero code when command failed, the detail of parse, load or
+/// semantic errors was already reported by the command, so only other errors (e.g. I/O errors)
+/// are shown here
+fn exit_on_error(command: &str, result: Result<(), Box<dyn std::error::Error>>) {
+    if let Err(err) = result {
+        if !reported(err.as_ref()) {
+            eprintln!("{} failed: {}", command, err);
         }
+        std::process::exit(1);
     }
 }
+
+fn reported(err: &(dyn std::error::Error + 'static)) -> bool {
+    err.is::<ParseError>() || err.is::<LoadError>() || err.is::<SemanticError>()
+}
@@ -16,6 +16,14 @@ pub enum ParseErrorVariant {
     NotExpectedToken(Vec<TkType>, Token),
     #[error("meet eof when parsing")]
     EOF,
+    #[error("invalid escape sequence `\\{}` in string", .0)]
+    InvalidEscape(String),
+    #[error("escape sequence `\\{}` is NUL, which would end the string", .0)]
+    NulEscape(String),
+    #[error("invalid integer literal `{}`", .0)]
+    InvalidInteger(String),
+    #[error("integer literal `{}` is larger than the maximum integer `{}`", .0, u64::MAX)]
+    IntegerTooLarge(String),
 }
 
 impl ParseError {
@@ -32,6 +40,30 @@ impl ParseError {
             err: ParseErrorVariant::EOF,
         }
     }
+    pub fn invalid_escape(location: &Location, sequence: String) -> ParseError {
+        ParseError {
+            location: location.clone(),
+            err: ParseErrorVariant::InvalidEscape(sequence),
+        }
+    }
+    pub fn nul_escape(location: &Location, sequence: String) -> ParseError {
+        ParseError {
+            location: location.clone(),
+            err: ParseErrorVariant::NulEscape(sequence),
+        }
+    }
+    pub fn invalid_integer(location: &Location, literal: String) -> ParseError {
+        ParseError {
+            location: location.clone(),
+            err: ParseErrorVariant::InvalidInteger(literal),
+        }
+    }
+    pub fn integer_too_large(location: &Location, literal: String) -> ParseError {
+        ParseError {
+            location: location.clone(),
+            err: ParseErrorVariant::IntegerTooLarge(literal),
+        }
+    }
 
     pub fn location(&self) -> Location {
         self.location.clone()
@@ -41,6 +73,10 @@ impl ParseError {
         match self.err {
             NotExpectedToken(..) => "not expected token",
             EOF => "eof",
+            InvalidEscape(..) => "invalid escape sequence",
+            NulEscape(..) => "NUL in string",
+            InvalidInteger(..) => "invalid integer",
+            IntegerTooLarge(..) => "integer too large",
         }
         .to_string()
     }
@@ -8,9 +8,10 @@ mod error;
 mod tests;
 
 use crate::lexer::Location;
-use error::ParseError;
+pub use error::ParseError;
 use error::Result;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::num::IntErrorKind;
 
 pub(crate) fn parse_prelude() -> Module {
     let prelude_file = Asset::get("prelude.elz").unwrap();
@@ -24,13 +25,18 @@ pub struct Parser {
     file_name: String,
     tokens: Vec<Token>,
     offset: usize,
+    // flag
+    // `if a {}` is a condition `a` with a block, not a class construction `a {}`
+    in_condition: bool,
 }
 
 impl Parser {
     pub fn parse_module(&mut self, end_token_type: TkType) -> Result<Module> {
+        let location = self.peek(0)?.location();
         self.consume(vec![TkType::Module])?;
         let name = self.parse_module_path()?;
         Ok(Module {
+            location,
             name,
             top_list: self.parse_top_list(end_token_type)?,
         })
@@ -65,12 +71,19 @@ impl Parser {
             Ok(None)
         }
     }
+    /// parse_visibility:
+    ///
+    /// `pub` or nothing
+    fn parse_visibility(&mut self) -> bool {
+        self.consume(vec![TkType::Pub]).is_ok()
+    }
     pub fn parse_top_ast(&mut self) -> Result<TopAst> {
         let tag = self.parse_tag()?;
+        let public = self.parse_visibility();
         let tok = self.peek(0)?;
         use TopAst::*;
         match tok.tk_type() {
-            TkType::Import => {
+            TkType::Import if !public => {
                 let i = self.parse_import()?;
                 Ok(Import(i))
             }
@@ -80,25 +93,39 @@ impl Parser {
                     .predict(vec![TkType::Identifier, TkType::Colon])
                     .is_ok()
                 {
-                    let v = self.parse_variable(tag)?;
+                    let mut v = self.parse_variable(tag)?;
+                    v.public = public;
                     self.consume(vec![TkType::Semicolon])?;
                     Ok(Variable(v))
                 } else {
                     // else we just seems it as a function to parse
-                    let f = self.parse_function(tag)?;
+                    let mut f = self.parse_function(tag)?;
+                    f.public = public;
                     Ok(Function(f))
                 }
             }
             TkType::Class => {
-                let c = self.parse_class(tag)?;
+                let mut c = self.parse_class(tag)?;
+                c.public = public;
                 Ok(Class(c))
             }
             TkType::Trait => {
-                let t = self.parse_trait(tag)?;
+                let mut t = self.parse_trait(tag)?;
+                t.public = public;
                 Ok(Trait(t))
             }
+            TkType::Enum => {
+                let mut e = self.parse_enum(tag)?;
+                e.public = public;
+                Ok(Enum(e))
+            }
             _ => {
-                self.predict_one_of(vec![TkType::Identifier, TkType::Class, TkType::Trait])?;
+                self.predict_one_of(vec![
+                    TkType::Identifier,
+                    TkType::Class,
+                    TkType::Trait,
+                    TkType::Enum,
+                ])?;
                 unreachable!();
             }
         }
@@ -107,18 +134,43 @@ impl Parser {
         let location = self.peek(0)?.location();
         self.consume(vec![TkType::Import])?;
         let import_path = self.parse_module_path()?;
+        let alias = self.parse_alias()?;
         let imported_component = self.parse_many_if_has_open_token(
             TkType::OpenParen,
             TkType::CloseParen,
             TkType::Comma,
-            |parser| Ok(parser.parse_identifier()?),
+            |parser| parser.parse_imported_component(),
         )?;
         Ok(Import {
             location,
             import_path,
+            alias,
             imported_component,
         })
     }
+    /// parse_imported_component:
+    ///
+    /// `*` | `<identifier>` | `<identifier> as <identifier>`
+    fn parse_imported_component(&mut self) -> Result<ImportedComponent> {
+        let location = self.peek(0)?.location();
+        if self.consume(vec![TkType::Multiple]).is_ok() {
+            return Ok(ImportedComponent::All(location));
+        }
+        let name = self.parse_identifier()?;
+        let alias = self.parse_alias()?;
+        Ok(ImportedComponent::Name {
+            location,
+            name,
+            alias,
+        })
+    }
+    fn parse_alias(&mut self) -> Result<Option<String>> {
+        if self.consume(vec![TkType::As]).is_ok() {
+            Ok(Some(self.parse_identifier()?))
+        } else {
+            Ok(None)
+        }
+    }
     /// parse_class:
     ///
     /// handle:
@@ -174,20 +226,23 @@ impl Parser {
     fn parse_class_members(&mut self) -> Result<Vec<ClassMember>> {
         let mut members = vec![];
         while self.peek(0)?.tk_type() != &TkType::CloseBrace {
+            let tag = self.parse_tag()?;
+            let public = self.parse_visibility();
             if self
                 .predict(vec![TkType::Identifier, TkType::Colon])
                 .is_ok()
             {
-                let v = self.parse_class_field()?;
+                let mut v = self.parse_class_field()?;
+                v.public = public;
                 members.push(ClassMember::Field(v));
+            } else if self.consume(vec![TkType::Accessor]).is_ok() {
+                let mut static_method = self.parse_function(tag)?;
+                static_method.public = public;
+                members.push(ClassMember::StaticMethod(static_method));
             } else {
-                let tag = self.parse_tag()?;
-                if self.consume(vec![TkType::Accessor]).is_ok() {
-                    members.push(ClassMember::StaticMethod(self.parse_function(tag)?));
-                } else {
-                    let method = self.parse_function(tag)?;
-                    members.push(ClassMember::Method(method));
-                }
+                let mut method = self.parse_function(tag)?;
+                method.public = public;
+                members.push(ClassMember::Method(method));
             }
         }
         Ok(members)
@@ -218,6 +273,30 @@ impl Parser {
             Ok(Field::new(loc, var_name, typ, None))
         }
     }
+    /// parse_enum:
+    ///
+    /// `enum Shape { Circle(r: f64), Rect(w: f64, h: f64), Empty }`
+    pub fn parse_enum(&mut self, tag: Option<Tag>) -> Result<Enum> {
+        let location = self.peek(0)?.location();
+        self.consume(vec![TkType::Enum])?;
+        let enum_name = self.parse_identifier()?;
+        let variants = self.parse_many(
+            TkType::OpenBrace,
+            TkType::CloseBrace,
+            TkType::Comma,
+            |parser| {
+                let location = parser.peek(0)?.location();
+                let name = parser.parse_identifier()?;
+                let fields = if parser.predict(vec![TkType::OpenParen]).is_ok() {
+                    parser.parse_parameters(true)?
+                } else {
+                    vec![]
+                };
+                Ok(EnumVariant::new(location, name, fields))
+            },
+        )?;
+        Ok(Enum::new(location, tag, enum_name, variants))
+    }
     /// parse_trait:
     ///
     /// handle:
@@ -269,6 +348,8 @@ impl Parser {
     /// parse_variable:
     ///
     /// handle `x: int = 1;`
+    /// or declaration
+    /// `x: int;`
     pub fn parse_variable(&mut self, tag: Option<Tag>) -> Result<Variable> {
         let loc = self.peek(0)?.location();
         // x: int = 1;
@@ -277,6 +358,9 @@ impl Parser {
         self.consume(vec![TkType::Colon])?;
         // int = 1;
         let typ = self.parse_type()?;
+        if self.predict(vec![TkType::Semicolon]).is_ok() {
+            return Ok(Variable::new_declaration(loc, tag, var_name, typ));
+        }
         // = 1;
         self.consume(vec![TkType::Equal])?;
         let expr = self.parse_expression(None, None)?;
@@ -297,7 +381,7 @@ impl Parser {
         // (): void
         let tok = self.peek(0)?;
         if tok.tk_type() == &TkType::OpenParen {
-            let params = self.parse_parameters()?;
+            let params = self.parse_parameters(true)?;
             // : void
             self.consume(vec![TkType::Colon])?;
             // void
@@ -329,7 +413,8 @@ impl Parser {
     ///
     /// ()
     /// (x: int, y: int)
-    fn parse_parameters(&mut self) -> Result<Vec<Parameter>> {
+    /// (x: int, y: int = 0)
+    fn parse_parameters(&mut self, allow_default: bool) -> Result<Vec<Parameter>> {
         self.consume(vec![TkType::OpenParen])?;
         let mut params = vec![];
         while self.peek(0)?.tk_type() != &TkType::CloseParen {
@@ -337,7 +422,12 @@ impl Parser {
             let param_name = self.take()?.value();
             self.take()?;
             let typ = self.parse_type()?;
-            params.push(Parameter::new(param_name, typ));
+            if allow_default && self.consume(vec![TkType::Equal]).is_ok() {
+                let default = self.parse_expression(None, None)?;
+                params.push(Parameter::with_default(param_name, typ, default));
+            } else {
+                params.push(Parameter::new(param_name, typ));
+            }
             let tok = self.peek(0)?;
             match tok.tk_type() {
                 TkType::Comma => {
@@ -395,10 +485,33 @@ impl Parser {
     ///
     /// `<identifier>`
     /// | `<identifier> [ <applied-type-parameters> ]`
+    /// | `<module>.<identifier>`
+    /// | `( <type>, ... ): <type>`
+    /// | `( <type>, <type>, ... )`
     pub fn parse_type(&mut self) -> Result<ParsedType> {
+        if self.predict(vec![TkType::OpenParen]).is_ok() {
+            let parameters = self.parse_many(
+                TkType::OpenParen,
+                TkType::CloseParen,
+                TkType::Comma,
+                |parser| parser.parse_type(),
+            )?;
+            // without `:` it's a tuple type, which has at least two elements
+            if parameters.len() >= 2 && self.predict(vec![TkType::Colon]).is_err() {
+                return Ok(ParsedType::tuple_type(parameters));
+            }
+            self.consume(vec![TkType::Colon])?;
+            let ret_typ = self.parse_type()?;
+            return Ok(ParsedType::function_type(parameters, ret_typ));
+        }
         // ensure is <identifier>
         self.predict(vec![TkType::Identifier])?;
-        let type_name = self.parse_access_identifier()?;
+        let mut type_name = self.parse_access_identifier()?;
+        // `m.Point` is `Point` of imported module `m`
+        while self.predict(vec![TkType::Dot, TkType::Identifier]).is_ok() {
+            self.consume(vec![TkType::Dot])?;
+            type_name = format!("{}.{}", type_name, self.parse_access_identifier()?);
+        }
         if self.predict(vec![TkType::OpenBracket]).is_ok() {
             let list = self.parse_many(
                 TkType::OpenBracket,
@@ -439,7 +552,7 @@ impl Parser {
                     let var = self.parse_variable(None)?;
                     self.consume(vec![TkType::Semicolon])?;
                     Ok(Statement::variable(tok.location(), var))
-                } else if vec![TkType::OpenParen, TkType::Dot].contains(self.peek(1)?.tk_type()) {
+                } else if [TkType::OpenParen, TkType::Dot].contains(self.peek(1)?.tk_type()) {
                     let unary = self.parse_unary()?;
                     let expr = self.parse_primary(unary)?;
                     self.consume(vec![TkType::Semicolon])?;
@@ -465,12 +578,12 @@ impl Parser {
             TkType::If => {
                 self.take()?;
                 let mut clauses = vec![];
-                clauses.push((self.parse_expression(None, None)?, self.parse_block()?));
+                clauses.push((self.parse_condition()?, self.parse_block()?));
                 while self.consume(vec![TkType::Else]).is_ok() {
                     // and remember that else block was optional, so failed at this condition was fine
                     if self.consume(vec![TkType::If]).is_ok() {
                         // else if
-                        clauses.push((self.parse_expression(None, None)?, self.parse_block()?));
+                        clauses.push((self.parse_condition()?, self.parse_block()?));
                         continue;
                     } else {
                         // else
@@ -487,6 +600,25 @@ impl Parser {
                     Block::new(tok.location()),
                 ))
             }
+            // `(a, b) := f();`
+            TkType::OpenParen => {
+                let names = self.parse_many(
+                    TkType::OpenParen,
+                    TkType::CloseParen,
+                    TkType::Comma,
+                    |parser| parser.parse_identifier(),
+                )?;
+                self.consume(vec![TkType::ColonEqual])?;
+                let expr = self.parse_expression(None, None)?;
+                self.consume(vec![TkType::Semicolon])?;
+                Ok(Statement::destructure(tok.location(), names, expr))
+            }
+            // `match x { ... }`, `;` is optional like the end of if block
+            TkType::Match => {
+                let expr = self.parse_expression(None, None)?;
+                let _ = self.consume(vec![TkType::Semicolon]);
+                Ok(Statement::expression(tok.location(), expr))
+            }
             _ => unimplemented!("{}", tok),
         }
     }
@@ -494,6 +626,15 @@ impl Parser {
 
 // for expression
 impl Parser {
+    /// parse_condition:
+    ///
+    /// the expression after `if`, which cannot be a class construction
+    fn parse_condition(&mut self) -> Result<Expr> {
+        self.in_condition = true;
+        let condition = self.parse_expression(None, None);
+        self.in_condition = false;
+        condition
+    }
     /// parse_expression:
     ///
     /// 1 + 2
@@ -531,15 +672,45 @@ impl Parser {
     /// parse_primary:
     ///
     /// foo()
+    /// | foo.bar
+    /// | foo.0
+    /// | foo as u8
+    /// | m.Foo::new
     pub fn parse_primary(&mut self, unary: Expr) -> Result<Expr> {
         let tok = self.peek(0)?;
         match tok.tk_type() {
-            TkType::OpenParen => self.parse_function_call(unary),
+            TkType::OpenParen => {
+                let func_call = self.parse_function_call(unary)?;
+                self.parse_primary(func_call)
+            }
             TkType::Dot => {
                 self.consume(vec![TkType::Dot])?;
+                if self.predict(vec![TkType::Integer]).is_ok() {
+                    let index = self.take()?;
+                    let index = parse_integer(&index.location(), index.value())?;
+                    return self.parse_primary(Expr::tuple_access(
+                        tok.location(),
+                        unary,
+                        index as usize,
+                    ));
+                }
                 let field_name = self.parse_identifier()?;
                 self.parse_primary(Expr::member_access(tok.location(), unary, field_name))
             }
+            TkType::As => {
+                self.consume(vec![TkType::As])?;
+                let typ = self.parse_type()?;
+                self.parse_primary(Expr::cast(tok.location(), unary, typ))
+            }
+            // static method or enum variant of a class in imported module
+            TkType::Accessor => match unary.access_path() {
+                Some(path) => {
+                    self.consume(vec![TkType::Accessor])?;
+                    let name = format!("{}::{}", path, self.parse_access_identifier()?);
+                    self.parse_primary(Expr::identifier(unary.location, name))
+                }
+                None => Ok(unary),
+            },
             _ => Ok(unary),
         }
     }
@@ -548,31 +719,39 @@ impl Parser {
     /// <integer>
     /// | <float64>
     /// | <string_literal>
+    /// | <raw_string_literal>
     /// | <access_identifier>
     /// | <bool>
     /// | <list>
+    /// | <match>
+    /// | <if>
+    /// | <lambda>
+    /// | <tuple>
     pub fn parse_unary(&mut self) -> Result<Expr> {
         let tok = self.peek(0)?;
         match tok.tk_type() {
-            // FIXME: lexer should emit int & float token directly
             TkType::Integer => {
                 let num = self.take()?.value();
-                if num.parse::<i64>().is_ok() {
-                    Ok(Expr::int(tok.location(), num.parse::<i64>().unwrap()))
-                } else if num.parse::<f64>().is_ok() {
-                    Ok(Expr::f64(tok.location(), num.parse::<f64>().unwrap()))
-                } else {
-                    panic!(
-                        "lexing bug causes a number token can't be convert to number: {:?}",
+                Ok(Expr::int(
+                    tok.location(),
+                    parse_integer(&tok.location(), num)?,
+                ))
+            }
+            TkType::Float => {
+                let num = self.take()?.value().replace('_', "");
+                match num.parse::<f64>() {
+                    Ok(f) => Ok(Expr::f64(tok.location(), f)),
+                    Err(_) => panic!(
+                        "lexing bug causes a float token can't be convert to number: {:?}",
                         num
-                    )
+                    ),
                 }
             }
             TkType::Identifier => {
                 let name = self.parse_access_identifier()?;
                 match self.peek(0)?.tk_type() {
-                    TkType::OpenBrace => {
-                        let mut field_inits = HashMap::new();
+                    TkType::OpenBrace if !self.in_condition => {
+                        let mut field_inits = BTreeMap::new();
                         let exprs = self.parse_many(
                             TkType::OpenBrace,
                             TkType::CloseBrace,
@@ -601,15 +780,196 @@ impl Parser {
                 self.take()?;
                 Ok(Expr::bool(tok.location(), false))
             }
-            TkType::String => self.parse_string(),
+            TkType::String | TkType::TemplateHead => self.parse_string(),
+            TkType::RawString => self.parse_raw_string(),
             TkType::OpenBracket => {
                 let list = self.parse_list()?;
                 Ok(Expr::list(tok.location(), list))
             }
+            TkType::Match => self.parse_match(),
+            TkType::If => self.parse_if_expression(),
+            TkType::OpenParen => {
+                // `()` and `(x:` start the parameters of lambda
+                let is_lambda = match self.peek(1)?.tk_type() {
+                    TkType::CloseParen => true,
+                    TkType::Identifier => self.peek(2)?.tk_type() == &TkType::Colon,
+                    _ => false,
+                };
+                if is_lambda {
+                    self.parse_lambda()
+                } else {
+                    self.parse_tuple()
+                }
+            }
             _ => {
                 use TkType::*;
                 Err(ParseError::not_expected_token(
-                    vec![Integer, Identifier, True, False, String, OpenBracket],
+                    vec![
+                        Integer,
+                        Float,
+                        Identifier,
+                        True,
+                        False,
+                        String,
+                        RawString,
+                        OpenBracket,
+                        Match,
+                        If,
+                        OpenParen,
+                    ],
+                    tok,
+                ))
+            }
+        }
+    }
+    /// parse_lambda:
+    ///
+    /// `(<parameters>): <type> => <expr>`
+    pub fn parse_lambda(&mut self) -> Result<Expr> {
+        let location = self.peek(0)?.location();
+        // a lambda is called as a value of function type, so its parameters have no default
+        let parameters = self.parse_parameters(false)?;
+        self.consume(vec![TkType::Colon])?;
+        let ret_typ = self.parse_type()?;
+        self.consume(vec![TkType::FatArrow])?;
+        let body = self.parse_expression(None, None)?;
+        Ok(Expr::lambda(location, parameters, ret_typ, body))
+    }
+    /// parse_tuple:
+    ///
+    /// `(<expr>, <expr>, ...)`, `(<expr>)` is the expression itself
+    pub fn parse_tuple(&mut self) -> Result<Expr> {
+        let location = self.peek(0)?.location();
+        let mut elements = self.parse_many(
+            TkType::OpenParen,
+            TkType::CloseParen,
+            TkType::Comma,
+            |parser| parser.parse_expression(None, None),
+        )?;
+        if elements.len() == 1 {
+            Ok(elements.remove(0))
+        } else {
+            Ok(Expr::tuple(location, elements))
+        }
+    }
+    /// parse_match:
+    ///
+    /// `match <expr> { <pattern> => <expr>, ... }`
+    pub fn parse_match(&mut self) -> Result<Expr> {
+        let location = self.peek(0)?.location();
+        self.consume(vec![TkType::Match])?;
+        // `{` after the value starts arms, not a class construction
+        let value = self.parse_condition()?;
+        let arms = self.parse_many(
+            TkType::OpenBrace,
+            TkType::CloseBrace,
+            TkType::Comma,
+            |parser| {
+                let location = parser.peek(0)?.location();
+                let pattern = parser.parse_pattern()?;
+                parser.consume(vec![TkType::FatArrow])?;
+                let expr = parser.parse_expression(None, None)?;
+                Ok(MatchArm::new(location, pattern, expr))
+            },
+        )?;
+        Ok(Expr::match_expr(location, value, arms))
+    }
+    /// parse_if_expression:
+    ///
+    /// `if <expr> <value-block> (else if <expr> <value-block>)* else <value-block>`
+    pub fn parse_if_expression(&mut self) -> Result<Expr> {
+        let location = self.peek(0)?.location();
+        self.consume(vec![TkType::If])?;
+        let mut clauses = vec![(self.parse_condition()?, self.parse_value_block()?)];
+        loop {
+            self.consume(vec![TkType::Else])?;
+            if self.consume(vec![TkType::If]).is_ok() {
+                clauses.push((self.parse_condition()?, self.parse_value_block()?));
+            } else {
+                let else_block = self.parse_value_block()?;
+                return Ok(Expr::if_expr(location, clauses, else_block));
+            }
+        }
+    }
+    /// parse_value_block:
+    ///
+    /// `{ <statement>* <expr> }`
+    pub fn parse_value_block(&mut self) -> Result<ValueBlock> {
+        let location = self.peek(0)?.location();
+        self.consume(vec![TkType::OpenBrace])?;
+        let mut block = Block::new(location);
+        loop {
+            let tok = self.peek(0)?;
+            if tok.tk_type() == &TkType::Identifier && self.peek(1)?.tk_type() == &TkType::Colon {
+                block.append(self.parse_statement()?);
+                continue;
+            }
+            let expr = self.parse_expression(None, None)?;
+            if self.consume(vec![TkType::Semicolon]).is_ok() {
+                block.append(Statement::expression(tok.location(), expr));
+            } else {
+                self.consume(vec![TkType::CloseBrace])?;
+                return Ok(ValueBlock::new(block, expr));
+            }
+        }
+    }
+    /// parse_pattern:
+    ///
+    /// `_`
+    /// | <identifier>
+    /// | <integer>
+    /// | <bool>
+    /// | <string>
+    /// | `Shape::Circle(r)`
+    /// | `m.Shape::Circle(r)`
+    pub fn parse_pattern(&mut self) -> Result<Pattern> {
+        let tok = self.peek(0)?;
+        match tok.tk_type() {
+            TkType::Identifier => {
+                let mut name = self.parse_access_identifier()?;
+                // `m.Shape::Circle` is a variant of enum `Shape` in imported module `m`
+                while self.predict(vec![TkType::Dot, TkType::Identifier]).is_ok() {
+                    self.consume(vec![TkType::Dot])?;
+                    name = format!("{}.{}", name, self.parse_access_identifier()?);
+                }
+                if name == "_" {
+                    Ok(Pattern::Wildcard)
+                } else if name.contains("::") {
+                    let bindings = if self.predict(vec![TkType::OpenParen]).is_ok() {
+                        self.parse_many(
+                            TkType::OpenParen,
+                            TkType::CloseParen,
+                            TkType::Comma,
+                            |parser| parser.parse_identifier(),
+                        )?
+                    } else {
+                        vec![]
+                    };
+                    Ok(Pattern::Variant(name, bindings))
+                } else {
+                    Ok(Pattern::Binding(name))
+                }
+            }
+            TkType::Integer => {
+                let num = self.take()?.value();
+                Ok(Pattern::Int(parse_integer(&tok.location(), num)?))
+            }
+            TkType::True => {
+                self.take()?;
+                Ok(Pattern::Bool(true))
+            }
+            TkType::False => {
+                self.take()?;
+                Ok(Pattern::Bool(false))
+            }
+            TkType::String => {
+                let s = self.take()?;
+                Ok(Pattern::String(self.parse_string_content(&s)?))
+            }
+            _ => {
+                use TkType::*;
+                Err(ParseError::not_expected_token(
+                    vec![Identifier, Integer, True, False, String],
                     tok,
                 ))
             }
@@ -651,59 +1011,132 @@ impl Parser {
         )?;
         Ok(list)
     }
+    /// parse_string:
+    ///
+    /// <string>
+    /// | <template_head> <expr> (<template_middle> <expr>)* <template_tail>
     pub fn parse_string(&mut self) -> Result<Expr> {
-        self.predict(vec![TkType::String])?;
+        self.predict(vec![TkType::String])
+            .or_else(|_| self.predict(vec![TkType::TemplateHead]))?;
         let tok = self.take()?;
-        // lexer didn't trim "" of string, so here we have to remove it.
-        let s = tok.value();
-        let s = s.trim_start_matches('"').trim_end_matches('"');
-        self.parse_string_template(tok.location(), s.chars().collect())
-    }
-    fn parse_string_template(&mut self, location: lexer::Location, s: Vec<char>) -> Result<Expr> {
-        let mut tmp_s = String::new();
-        let mut index = 0;
-        while index < s.len() {
-            let c = s[index];
-            match c {
-                '\\' => {
-                    index += 1;
-                    if index < s.len() {
-                        tmp_s.push(s[index]);
-                        index += 1;
-                    } else {
-                        break;
+        let location = tok.location();
+        let head = Expr::string(location.clone(), self.parse_string_content(&tok)?);
+        if tok.tk_type() == &TkType::String {
+            return Ok(head);
+        }
+        // `"a {x} b"` is `"a " + x.to_string() + " b"`
+        let mut result = head;
+        loop {
+            // class construction is fine in `{}`, even the template is in a condition
+            let in_condition = self.in_condition;
+            self.in_condition = false;
+            let expr = self.parse_expression(None, None)?;
+            self.in_condition = in_condition;
+            let interpolation = Expr::interpolation(expr.location.clone(), expr);
+            result = Expr::binary(location.clone(), result, interpolation, Operator::Plus);
+            let tok = self.take()?;
+            match tok.tk_type() {
+                TkType::TemplateMiddle | TkType::TemplateTail => {
+                    let s = Expr::string(tok.location(), self.parse_string_content(&tok)?);
+                    result = Expr::binary(location.clone(), result, s, Operator::Plus);
+                    if tok.tk_type() == &TkType::TemplateTail {
+                        return Ok(result);
                     }
                 }
-                '{' => {
-                    let left_string = Expr::string(location.clone(), tmp_s.clone());
-                    // consume `{`
-                    index += 1;
-                    // reset it
-                    tmp_s = String::new();
-                    while index < s.len() && s[index] != '}' && s[index - 1] != '\\' {
-                        tmp_s.push(s[index]);
+                _ => {
+                    return Err(ParseError::not_expected_token(
+                        vec![TkType::TemplateMiddle, TkType::TemplateTail],
+                        tok,
+                    ))
+                }
+            }
+        }
+    }
+    /// parse_string_content removes the delimiters(`"`, `{` or `}`) of a string token and
+    /// resolves escape sequences
+    fn parse_string_content(&self, tok: &Token) -> Result<String> {
+        let chars: Vec<char> = tok.value().chars().collect();
+        // unterminated string has no closing delimiter
+        let end = match chars.last() {
+            Some('"') | Some('{') if chars.len() > 1 => chars.len() - 1,
+            _ => chars.len(),
+        };
+        let mut s = String::new();
+        let mut index = 1;
+        while index < end {
+            match chars[index] {
+                '\\' if chars.get(index + 1) == Some(&'\n') => {
+                    // `\` at the end of line continues the string, leading spaces of the next
+                    // line are skipped
+                    index += 2;
+                    while index < end && (chars[index] == ' ' || chars[index] == '\t') {
                         index += 1;
                     }
-                    let mut p = Parser::new(self.file_name.clone(), tmp_s);
-                    let mid_expr = p.parse_expression(None, None)?;
-                    index += 1;
-                    let rest_string =
-                        self.parse_string_template(location.clone(), s[index..].to_vec())?;
-                    let result = Expr::binary(
-                        location.clone(),
-                        Expr::binary(location, left_string, mid_expr, Operator::Plus),
-                        rest_string,
-                        Operator::Plus,
-                    );
-                    return Ok(result);
                 }
-                _ => {
-                    tmp_s.push(c);
+                '\\' => {
+                    let location = tok.location().shift(&chars[..index]);
+                    let (c, length) = self.parse_escape(&location, &chars[index + 1..end])?;
+                    s.push(c);
+                    index += length + 1;
+                }
+                c => {
+                    s.push(c);
                     index += 1;
                 }
             }
         }
-        Ok(Expr::string(location, tmp_s))
+        Ok(s)
+    }
+    /// parse_raw_string: `r"..."` or `r#"..."#`, content is taken as it is, no escape and template
+    pub fn parse_raw_string(&mut self) -> Result<Expr> {
+        self.predict(vec![TkType::RawString])?;
+        let tok = self.take()?;
+        let s = tok.value();
+        let s = s.trim_start_matches('r');
+        let hashes = s.len() - s.trim_start_matches('#').len();
+        // unterminated raw string has no closing `"#`
+        let content = s.get(hashes + 1..s.len() - hashes - 1).unwrap_or("");
+        Ok(Expr::string(tok.location(), content))
+    }
+    /// parse_escape parses the escape sequence after `\`, returns the char it represents and the
+    /// number of chars it used. Strings are terminated by NUL, so an escape cannot be NUL.
+    fn parse_escape(&self, location: &lexer::Location, s: &[char]) -> Result<(char, usize)> {
+        let c = match s.first() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('{') => '{',
+            Some('}') => '}',
+            Some('u') => {
+                // `\u{1F600}`
+                let end = s.iter().position(|&c| c == '}');
+                let code_point = match end {
+                    Some(end) if s.get(1) == Some(&'{') && end > 2 && end <= 8 => {
+                        let hex: String = s[2..end].iter().collect();
+                        u32::from_str_radix(hex.as_str(), 16)
+                            .ok()
+                            .and_then(std::char::from_u32)
+                    }
+                    _ => None,
+                };
+                return match (code_point, end) {
+                    (Some('\0'), Some(end)) => {
+                        Err(ParseError::nul_escape(location, s[..=end].iter().collect()))
+                    }
+                    (Some(c), Some(end)) => Ok((c, end + 1)),
+                    _ => {
+                        let seq: String = s.iter().take(end.map_or(1, |e| e + 1)).collect();
+                        Err(ParseError::invalid_escape(location, seq))
+                    }
+                };
+            }
+            Some('0') => return Err(ParseError::nul_escape(location, "0".to_string())),
+            Some(c) => return Err(ParseError::invalid_escape(location, c.to_string())),
+            None => return Err(ParseError::invalid_escape(location, "".to_string())),
+        };
+        Ok((c, 1))
     }
 }
 
@@ -713,11 +1146,30 @@ fn is_right_associative(_op: Token) -> bool {
 
 fn precedence(op: Token) -> u64 {
     match op.tk_type() {
+        TkType::EqualEqual => 1,
         TkType::Plus => 2,
         _ => 0,
     }
 }
 
+/// parse_integer converts integer literal to its value, it can have prefix `0x`, `0o` or `0b` and
+/// `_` between digits
+fn parse_integer(location: &Location, literal: String) -> Result<u64> {
+    let (radix, digits) = match literal.get(..2) {
+        Some("0x") => (16, &literal[2..]),
+        Some("0o") => (8, &literal[2..]),
+        Some("0b") => (2, &literal[2..]),
+        _ => (10, literal.as_str()),
+    };
+    match u64::from_str_radix(&digits.replace('_', ""), radix) {
+        Ok(value) => Ok(value),
+        Err(e) if e.kind() == &IntErrorKind::PosOverflow => {
+            Err(ParseError::integer_too_large(location, literal))
+        }
+        Err(_) => Err(ParseError::invalid_integer(location, literal)),
+    }
+}
+
 /// This block puts fundamental helpers
 impl Parser {
     pub fn parse_program<T: Into<String> + Clone>(file_name: T, code: T) -> Result<Module> {
@@ -732,6 +1184,7 @@ impl Parser {
             file_name,
             tokens,
             offset: 0,
+            in_condition: false,
         }
     }
     /// peek get the token by (current position + n)
@@ -1,7 +1,7 @@
 use super::*;
 use crate::lexer::Location;
 use crate::lexer::TkType::EOF;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[test]
 fn parse_function_with_block_body() {
@@ -55,6 +55,53 @@ fn parse_function_with_expression_body() {
     )
 }
 
+#[test]
+fn parse_parameter_default_and_named_argument() {
+    let code = "\
+    offset(x: int, by: int = 1): int = add(by: by, x);
+    ";
+
+    let mut parser = Parser::new("", code);
+
+    let func = parser.parse_function(None).unwrap();
+    assert_eq!(
+        func,
+        Function::new(
+            Location::from(1, 0),
+            None,
+            "offset",
+            vec![
+                Parameter::new("x", ParsedType::type_name("int")),
+                Parameter::with_default(
+                    "by",
+                    ParsedType::type_name("int"),
+                    Expr::int(Location::from(1, 25), 1)
+                ),
+            ],
+            ParsedType::type_name("int"),
+            Body::Expr(Expr::func_call(
+                Location::from(1, 35),
+                Expr::identifier(Location::from(1, 35), "add"),
+                vec![
+                    Argument::new(
+                        Location::from(1, 43),
+                        Some("by".to_string()),
+                        Expr::identifier(Location::from(1, 43), "by")
+                    ),
+                    Argument::new(
+                        Location::from(1, 47),
+                        None,
+                        Expr::identifier(Location::from(1, 47), "x")
+                    ),
+                ]
+            ))
+        )
+    );
+    // lambda is called as a value, its parameters have no default
+    let mut parser = Parser::new("", "(x: int = 1): int => x");
+    assert!(parser.parse_expression(None, None).is_err());
+}
+
 #[test]
 fn parse_function_declaration() {
     let code = "\
@@ -97,6 +144,26 @@ fn parse_variable_define() {
     )
 }
 
+#[test]
+fn parse_variable_declaration() {
+    let code = "\
+    stdin: _c_file;
+    ";
+
+    let mut parser = Parser::new("", code);
+
+    let var = parser.parse_variable(None).unwrap();
+    assert_eq!(
+        var,
+        Variable::new_declaration(
+            Location::from(1, 0),
+            None,
+            "stdin",
+            ParsedType::type_name("_c_file"),
+        )
+    )
+}
+
 #[test]
 fn parse_variable_define_with_list_value() {
     let code = "\
@@ -125,6 +192,63 @@ fn parse_variable_define_with_list_value() {
     )
 }
 
+#[test]
+fn parse_variable_define_with_function_type() {
+    let code = "\
+    f: (int, (int): int): void = foo;
+    ";
+
+    let mut parser = Parser::new("", code);
+
+    let var = parser.parse_variable(None).unwrap();
+    assert_eq!(
+        var,
+        Variable::new(
+            Location::from(1, 0),
+            None,
+            "f",
+            ParsedType::function_type(
+                vec![
+                    ParsedType::type_name("int"),
+                    ParsedType::function_type(
+                        vec![ParsedType::type_name("int")],
+                        ParsedType::type_name("int")
+                    ),
+                ],
+                ParsedType::type_name("void")
+            ),
+            Expr::identifier(Location::from(1, 29), "foo")
+        )
+    )
+}
+
+#[test]
+fn parse_qualified_type_static_method_and_pattern() {
+    let code = "p: m.Point = m.Point::new(1);";
+
+    let mut parser = Parser::new("", code);
+
+    let var = parser.parse_variable(None).unwrap();
+    assert_eq!(var.typ, ParsedType::type_name("m.Point"));
+    assert_eq!(
+        var.expr,
+        Some(Expr::func_call(
+            Location::from(1, 14),
+            Expr::identifier(Location::from(1, 14), "m.Point::new"),
+            vec![Argument::new(
+                Location::from(1, 26),
+                None,
+                Expr::int(Location::from(1, 26), 1)
+            )]
+        ))
+    );
+    let mut parser = Parser::new("", "a.b.Shape::Circle(r)");
+    assert_eq!(
+        parser.parse_pattern().unwrap(),
+        Pattern::Variant("a.b.Shape::Circle".to_string(), vec!["r".to_string()])
+    );
+}
+
 #[test]
 fn parse_statement_if_block() {
     let code = "if true {} else if false {} else {}";
@@ -150,6 +274,188 @@ fn parse_statement_if_block() {
     )
 }
 
+#[test]
+fn parse_if_expression() {
+    let code = "if a { 1 } else if b { foo(); c: int = 2; c } else { 3 }";
+
+    let mut parser = Parser::new("", code);
+    let mut block = Block::new(Location::from(1, 21));
+    block.append(Statement::expression(
+        Location::from(1, 23),
+        Expr::func_call(
+            Location::from(1, 23),
+            Expr::identifier(Location::from(1, 23), "foo"),
+            vec![],
+        ),
+    ));
+    block.append(Statement::variable(
+        Location::from(1, 30),
+        Variable::new(
+            Location::from(1, 30),
+            None,
+            "c",
+            ParsedType::type_name("int"),
+            Expr::int(Location::from(1, 39), 2),
+        ),
+    ));
+    assert_eq!(
+        parser.parse_expression(None, None).unwrap(),
+        Expr::if_expr(
+            Location::from(1, 0),
+            vec![
+                (
+                    Expr::identifier(Location::from(1, 3), "a"),
+                    ValueBlock::new(
+                        Block::new(Location::from(1, 5)),
+                        Expr::int(Location::from(1, 7), 1)
+                    )
+                ),
+                (
+                    Expr::identifier(Location::from(1, 19), "b"),
+                    ValueBlock::new(block, Expr::identifier(Location::from(1, 42), "c"))
+                ),
+            ],
+            ValueBlock::new(
+                Block::new(Location::from(1, 51)),
+                Expr::int(Location::from(1, 53), 3)
+            )
+        )
+    )
+}
+
+#[test]
+fn parse_lambda() {
+    let code = "apply((x: int): int => x + n, 1)";
+
+    let mut parser = Parser::new("", code);
+    assert_eq!(
+        parser.parse_expression(None, None).unwrap(),
+        Expr::func_call(
+            Location::from(1, 0),
+            Expr::identifier(Location::from(1, 0), "apply"),
+            vec![
+                Argument::new(
+                    Location::from(1, 6),
+                    None,
+                    Expr::lambda(
+                        Location::from(1, 6),
+                        vec![Parameter::new("x", ParsedType::type_name("int"))],
+                        ParsedType::type_name("int"),
+                        Expr::binary(
+                            Location::from(1, 23),
+                            Expr::identifier(Location::from(1, 23), "x"),
+                            Expr::identifier(Location::from(1, 27), "n"),
+                            Operator::Plus,
+                        ),
+                    ),
+                ),
+                Argument::new(
+                    Location::from(1, 30),
+                    None,
+                    Expr::int(Location::from(1, 30), 1)
+                ),
+            ],
+        )
+    )
+}
+
+#[test]
+fn parse_tuple_type_literal_access_and_destructure() {
+    let mut parser = Parser::new("", "(int, (string, int))");
+    assert_eq!(
+        parser.parse_type().unwrap(),
+        ParsedType::tuple_type(vec![
+            ParsedType::type_name("int"),
+            ParsedType::tuple_type(vec![
+                ParsedType::type_name("string"),
+                ParsedType::type_name("int"),
+            ]),
+        ])
+    );
+
+    let mut parser = Parser::new("", "(a, (1)).1");
+    assert_eq!(
+        parser.parse_expression(None, None).unwrap(),
+        Expr::tuple_access(
+            Location::from(1, 8),
+            Expr::tuple(
+                Location::from(1, 0),
+                vec![
+                    Expr::identifier(Location::from(1, 1), "a"),
+                    Expr::int(Location::from(1, 5), 1),
+                ],
+            ),
+            1,
+        )
+    );
+
+    let mut parser = Parser::new("", "(q, _) := div(7, 2);");
+    assert_eq!(
+        parser.parse_statement().unwrap(),
+        Statement::destructure(
+            Location::from(1, 0),
+            vec!["q".to_string(), "_".to_string()],
+            Expr::func_call(
+                Location::from(1, 10),
+                Expr::identifier(Location::from(1, 10), "div"),
+                vec![
+                    Argument::new(
+                        Location::from(1, 14),
+                        None,
+                        Expr::int(Location::from(1, 14), 7)
+                    ),
+                    Argument::new(
+                        Location::from(1, 17),
+                        None,
+                        Expr::int(Location::from(1, 17), 2)
+                    ),
+                ],
+            ),
+        )
+    );
+}
+
+#[test]
+fn parse_statement_if_block_with_identifier_condition() {
+    let code = "if a {}";
+
+    let mut parser = Parser::new("", code);
+
+    assert_eq!(
+        parser.parse_statement().unwrap(),
+        Statement::if_block(
+            Location::from(1, 0),
+            vec![(
+                Expr::identifier(Location::from(1, 3), "a"),
+                Block::new(Location::from(1, 5))
+            )],
+            Block::new(Location::from(1, 0))
+        )
+    )
+}
+
+#[test]
+fn parse_equal_has_lower_precedence_than_plus() {
+    let code = "a + 1 == b";
+
+    let mut parser = Parser::new("", code);
+
+    assert_eq!(
+        parser.parse_expression(None, None).unwrap(),
+        Expr::binary(
+            Location::from(1, 0),
+            Expr::binary(
+                Location::from(1, 0),
+                Expr::identifier(Location::from(1, 0), "a"),
+                Expr::int(Location::from(1, 4), 1),
+                Operator::Plus
+            ),
+            Expr::identifier(Location::from(1, 9), "b"),
+            Operator::Equal
+        )
+    )
+}
+
 #[test]
 fn parse_expr_string() {
     let code = "\
@@ -164,21 +470,176 @@ fn parse_expr_string() {
         location.clone(),
         Expr::binary(
             location.clone(),
-            Expr::string(location.clone(), "str \"\\ value "),
-            Expr::identifier(location.clone(), "a"),
+            Expr::string(location, "str \"\\ value "),
+            Expr::interpolation(
+                Location::from(1, 17),
+                Expr::identifier(Location::from(1, 17), "a"),
+            ),
             Operator::Plus,
         ),
-        Expr::string(location, ""),
+        Expr::string(Location::from(1, 18), ""),
         Operator::Plus,
     );
     assert_eq!(s, expected)
 }
 
+#[test]
+fn parse_string_escape() {
+    let code = r#""\n\t\r\\\"\{\}\u{1F600}\u{e9}""#;
+
+    let mut parser = Parser::new("", code);
+
+    assert_eq!(
+        parser.parse_string().unwrap(),
+        Expr::string(Location::from(1, 0), "\n\t\r\\\"{}\u{1F600}\u{e9}")
+    )
+}
+
+#[test]
+fn parse_string_with_invalid_escape() {
+    for code in &[r#""\q""#, r#""\u{110000}""#, r#""\u{}""#, r#""\u1234""#] {
+        let mut parser = Parser::new("", *code);
+        assert!(parser.parse_string().is_err(), "{} should be invalid", code);
+    }
+}
+
+#[test]
+fn string_cannot_contain_nul() {
+    // strings are terminated by NUL
+    for (code, sequence) in &[(r#""a\0b""#, "0"), (r#""a\u{0}b""#, "u{0}")] {
+        let mut parser = Parser::new("", *code);
+        let err = parser.parse_string().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ParseError::nul_escape(&Location::from(1, 2), sequence.to_string()).to_string()
+        );
+    }
+}
+
+#[test]
+fn parse_multi_line_string() {
+    let code = "\"a\n  b \\\n    c\"";
+
+    let mut parser = Parser::new("", code);
+
+    assert_eq!(
+        parser.parse_string().unwrap(),
+        Expr::string(Location::from(1, 0), "a\n  b c")
+    )
+}
+
+#[test]
+fn parse_raw_string() {
+    let code = r###"r#"\n "{a}" "#"###;
+
+    let mut parser = Parser::new("", code);
+
+    assert_eq!(
+        parser.parse_expression(None, None).unwrap(),
+        Expr::string(Location::from(1, 0), r#"\n "{a}" "#)
+    )
+}
+
+#[test]
+fn parse_integer_literals() {
+    for (code, value) in &[
+        ("0xFF", 255),
+        ("0o17", 15),
+        ("0b1010", 10),
+        ("1_000_000", 1_000_000),
+        ("0xFFFF_FFFF_FFFF_FFFF", u64::MAX),
+    ] {
+        let mut parser = Parser::new("", code);
+        assert_eq!(
+            parser.parse_expression(None, None).unwrap(),
+            Expr::int(Location::from(1, 0), *value)
+        );
+    }
+}
+
+#[test]
+fn invalid_integer_literal_is_reported() {
+    for (code, message) in &[
+        ("x: int = 0b102;", "invalid integer literal `0b102`"),
+        ("x: int = 0x;", "invalid integer literal `0x`"),
+        (
+            "x: int = 18446744073709551616;",
+            "integer literal `18446744073709551616` is larger than the maximum integer `18446744073709551615`",
+        ),
+    ] {
+        let mut parser = Parser::new("", code);
+        let err = parser.parse_variable(None).unwrap_err();
+        assert_eq!(err.location(), Location::from(1, 9));
+        assert!(err.to_string().ends_with(message), "{}", err);
+    }
+}
+
+#[test]
+fn parse_nested_string_template() -> Result<()> {
+    let code = "\"a {f(\"{x}\")}\"";
+
+    let mut parser = Parser::new("", code);
+
+    let s = parser.parse_string()?;
+    let inner = Expr::binary(
+        Location::from(1, 6),
+        Expr::binary(
+            Location::from(1, 6),
+            Expr::string(Location::from(1, 6), ""),
+            Expr::interpolation(
+                Location::from(1, 8),
+                Expr::identifier(Location::from(1, 8), "x"),
+            ),
+            Operator::Plus,
+        ),
+        Expr::string(Location::from(1, 9), ""),
+        Operator::Plus,
+    );
+    let call = Expr::func_call(
+        Location::from(1, 4),
+        Expr::identifier(Location::from(1, 4), "f"),
+        vec![Argument::new(Location::from(1, 6), None, inner)],
+    );
+    let expected = Expr::binary(
+        Location::from(1, 0),
+        Expr::binary(
+            Location::from(1, 0),
+            Expr::string(Location::from(1, 0), "a "),
+            Expr::interpolation(Location::from(1, 4), call),
+            Operator::Plus,
+        ),
+        Expr::string(Location::from(1, 12), ""),
+        Operator::Plus,
+    );
+    assert_eq!(s, expected);
+    Ok(())
+}
+
+#[test]
+fn error_in_string_template_has_correct_location() {
+    let code = "x: string = \"abc {x y}\";";
+
+    let mut parser = Parser::new("", code);
+
+    let err = parser.parse_variable(None).unwrap_err();
+    assert_eq!(err.location(), Location::from(1, 20));
+}
+
+#[test]
+fn invalid_escape_has_correct_location() {
+    let code = "\"abc {x} d\\q\"";
+
+    let mut parser = Parser::new("", code);
+
+    let err = parser.parse_string().unwrap_err();
+    assert_eq!(err.location(), Location::from(1, 10));
+}
+
 #[test]
 fn parse_expr_class_construction() {
     let code = "Car { name: \"\", price: 10000 }";
 
-    let mut fields_inits = HashMap::<String, Expr>::new();
+    let mut fields_inits = BTreeMap::<String, Expr>::new();
     fields_inits.insert("name".to_string(), Expr::string(Location::from(1, 12), ""));
     fields_inits.insert("price".to_string(), Expr::int(Location::from(1, 23), 10000));
 
@@ -190,6 +651,92 @@ fn parse_expr_class_construction() {
     )
 }
 
+#[test]
+fn parse_cast() {
+    let code = "x + y.len() as u8";
+
+    let mut parser = Parser::new("", code);
+    let len = Expr::func_call(
+        Location::from(1, 5),
+        Expr::member_access(
+            Location::from(1, 5),
+            Expr::identifier(Location::from(1, 4), "y"),
+            "len",
+        ),
+        vec![],
+    );
+    assert_eq!(
+        parser.parse_expression(None, None).unwrap(),
+        Expr::binary(
+            Location::from(1, 0),
+            Expr::identifier(Location::from(1, 0), "x"),
+            Expr::cast(Location::from(1, 12), len, ParsedType::type_name("u8")),
+            Operator::Plus,
+        )
+    )
+}
+
+#[test]
+fn parse_match() {
+    let code = "match x {
+      0 => \"zero\",
+      Shape::Rect(w, _) => w,
+      Shape::Empty => e,
+      true => t,
+      \"s\" => s,
+      n => n,
+      _ => 1,
+    }";
+
+    let mut parser = Parser::new("", code);
+    let arm = |line: u32, pattern: Pattern, expr: Expr| {
+        MatchArm::new(Location::from(line, 6), pattern, expr)
+    };
+    assert_eq!(
+        parser.parse_expression(None, None).unwrap(),
+        Expr::match_expr(
+            Location::from(1, 0),
+            Expr::identifier(Location::from(1, 6), "x"),
+            vec![
+                arm(
+                    2,
+                    Pattern::Int(0),
+                    Expr::string(Location::from(2, 11), "zero")
+                ),
+                arm(
+                    3,
+                    Pattern::Variant(
+                        "Shape::Rect".to_string(),
+                        vec!["w".to_string(), "_".to_string()]
+                    ),
+                    Expr::identifier(Location::from(3, 27), "w")
+                ),
+                arm(
+                    4,
+                    Pattern::Variant("Shape::Empty".to_string(), vec![]),
+                    Expr::identifier(Location::from(4, 22), "e")
+                ),
+                arm(
+                    5,
+                    Pattern::Bool(true),
+                    Expr::identifier(Location::from(5, 14), "t")
+                ),
+                arm(
+                    6,
+                    Pattern::String("s".to_string()),
+                    Expr::identifier(Location::from(6, 13), "s")
+                ),
+                arm(
+                    7,
+                    Pattern::Binding("n".to_string()),
+                    Expr::identifier(Location::from(7, 11), "n")
+                ),
+                arm(8, Pattern::Wildcard, Expr::int(Location::from(8, 11), 1)),
+            ]
+        )
+    )
+}
+
 #[test]
 fn parse_class() {
     let code = "\
@@ -235,6 +782,43 @@ fn parse_class() {
     )
 }
 
+#[test]
+fn parse_enum() {
+    let code = "\
+                enum Shape {\n\
+                Circle(r: f64),\n\
+                Rect(w: f64, h: f64),\n\
+                Empty,\n\
+                }";
+
+    let mut parser = Parser::new("", code);
+    let e = parser.parse_enum(None).unwrap();
+    assert_eq!(
+        e,
+        Enum::new(
+            Location::from(1, 0),
+            None,
+            "Shape",
+            vec![
+                EnumVariant::new(
+                    Location::from(2, 0),
+                    "Circle",
+                    vec![Parameter::new("r", ParsedType::type_name("f64"))],
+                ),
+                EnumVariant::new(
+                    Location::from(3, 0),
+                    "Rect",
+                    vec![
+                        Parameter::new("w", ParsedType::type_name("f64")),
+                        Parameter::new("h", ParsedType::type_name("f64")),
+                    ],
+                ),
+                EnumVariant::new(Location::from(4, 0), "Empty", vec![]),
+            ]
+        )
+    )
+}
+
 #[test]
 fn parse_class_inherit() {
     let code = "class Foo <: Bar {}";
@@ -282,6 +866,7 @@ fn module() {
     assert_eq!(
         module,
         Module {
+            location: Location::from(1, 0),
             name: "foo.bar".to_string(),
             top_list: vec![]
         }
@@ -299,6 +884,7 @@ fn import_all() {
         Import {
             location: Location::from(1, 0),
             import_path: "foo.bar".to_string(),
+            alias: None,
             imported_component: vec![]
         }
     )
@@ -315,11 +901,69 @@ fn import() {
         Import {
             location: Location::from(1, 0),
             import_path: "foo".to_string(),
-            imported_component: vec!["bar".to_string()]
+            alias: None,
+            imported_component: vec![ImportedComponent::name(Location::from(1, 13), "bar")]
         }
     )
 }
 
+#[test]
+fn import_with_alias_and_wildcard() {
+    let code = "import foo as f ( *, bar as baz )";
+
+    let mut parser = Parser::new("", code);
+    let i = parser.parse_import().unwrap();
+    assert_eq!(
+        i,
+        Import {
+            location: Location::from(1, 0),
+            import_path: "foo".to_string(),
+            alias: Some("f".to_string()),
+            imported_component: vec![
+                ImportedComponent::All(Location::from(1, 18)),
+                ImportedComponent::Name {
+                    location: Location::from(1, 21),
+                    name: "bar".to_string(),
+                    alias: Some("baz".to_string()),
+                },
+            ]
+        }
+    )
+}
+
+#[test]
+fn pub_definitions_and_members() {
+    let code = "pub class Foo {
+      pub a: int;
+      b: int;
+      pub ::new(): Foo;
+      bar(): void;
+    }
+    pub foo(): void;
+    ";
+
+    let mut parser = Parser::new("", code);
+    let top_list = parser.parse_top_list(TkType::EOF).unwrap();
+    let class = match &top_list[0] {
+        TopAst::Class(c) => c,
+        top => panic!("expected class but got {:?}", top),
+    };
+    assert!(class.public);
+    let member_visibility: Vec<bool> = class
+        .members
+        .iter()
+        .map(|member| match member {
+            ClassMember::Field(f) => f.public,
+            ClassMember::Method(f) | ClassMember::StaticMethod(f) => f.public,
+        })
+        .collect();
+    assert_eq!(member_visibility, vec![true, false, true, false]);
+    match &top_list[1] {
+        TopAst::Function(f) => assert!(f.public),
+        top => panic!("expected function but got {:?}", top),
+    }
+}
+
 #[test]
 fn parse_tag() {
     let code = "@builtin";
@@ -3,3 +3,8 @@ use rust_embed::RustEmbed;
 #[derive(RustEmbed)]
 #[folder = "lib/prelude/"]
 pub struct Asset;
+
+/// Std is the standard library, module `std.io` is `lib/std/io.elz`
+#[derive(RustEmbed)]
+#[folder = "lib/std/"]
+pub struct Std;
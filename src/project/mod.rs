@@ -0,0 +1,193 @@
+use crate::loader::module_path;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[cfg(test)]
+mod tests;
+
+pub const MANIFEST_FILE: &str = "elz.toml";
+
+/// Manifest is the content of `elz.toml`
+///
+/// ```toml
+/// [package]
+/// name = "hello"
+/// # module has `main` function, default is `main`
+/// entry = "main"
+/// # directories of source code, default is `["src"]`
+/// source-dirs = ["src"]
+///
+/// [dependencies]
+/// util = { path = "../util" }
+/// ```
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Manifest {
+    pub package: Package,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Package {
+    pub name: String,
+    #[serde(default = "default_entry")]
+    pub entry: String,
+    #[serde(default = "default_source_dirs")]
+    pub source_dirs: Vec<String>,
+}
+
+fn default_entry() -> String {
+    "main".to_string()
+}
+fn default_source_dirs() -> Vec<String> {
+    vec!["src".to_string()]
+}
+
+/// Dependency is another project on local file system, its modules can be imported
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Dependency {
+    pub path: String,
+}
+
+impl Manifest {
+    /// new gives the manifest of a new package named `name` with default values
+    pub fn new(name: &str) -> Manifest {
+        Manifest {
+            package: Package {
+                name: name.to_string(),
+                entry: default_entry(),
+                source_dirs: default_source_dirs(),
+            },
+            dependencies: BTreeMap::new(),
+        }
+    }
+}
+
+impl std::str::FromStr for Manifest {
+    type Err = toml::de::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+/// Project is a directory has `elz.toml`
+pub struct Project {
+    pub root: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Project {
+    pub fn open(root: &Path) -> Result<Project, Box<dyn std::error::Error>> {
+        let manifest_path = root.join(MANIFEST_FILE);
+        let content = std::fs::read_to_string(&manifest_path)
+            .map_err(|err| format!("cannot read {}: {}", manifest_path.display(), err))?;
+        let manifest = content
+            .parse()
+            .map_err(|err| format!("invalid {}: {}", manifest_path.display(), err))?;
+        Ok(Project {
+            root: root.to_path_buf(),
+            manifest,
+        })
+    }
+
+    /// create scaffolds a new project named `name` in directory `root`
+    pub fn create(root: &Path, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !is_package_name(name) {
+            return Err(format!("`{}` is not a valid package name", name).into());
+        }
+        if root.exists() {
+            return Err(format!("{} already exists", root.display()).into());
+        }
+        std::fs::create_dir_all(root.join("src"))?;
+        std::fs::write(
+            root.join(MANIFEST_FILE),
+            toml::to_string(&Manifest::new(name))?,
+        )?;
+        std::fs::write(
+            root.join("src").join("main.elz"),
+            "module main\n\nmain(): void {\n  println(\"Hello, World!\");\n}\n",
+        )?;
+        std::fs::write(root.join(".gitignore"), "build/\n")?;
+        Ok(())
+    }
+
+    pub fn source_dirs(&self) -> Vec<PathBuf> {
+        self.manifest
+            .package
+            .source_dirs
+            .iter()
+            .map(|dir| self.root.join(dir))
+            .collect()
+    }
+
+    /// source_files returns all `*.elz` files under source directories, in the same order every
+    /// time
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![];
+        for dir in self.source_dirs() {
+            let mut dir_files: Vec<PathBuf> = WalkDir::new(dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .map(|e| e.into_path())
+                .filter(|path| path.is_file() && path.extension() == Some("elz".as_ref()))
+                .collect();
+            dir_files.sort();
+            files.extend(dir_files);
+        }
+        files
+    }
+
+    /// search_paths returns source directories of the project, then source directories of its
+    /// dependencies
+    pub fn search_paths(&self) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let mut search_paths = vec![];
+        let mut visited = HashSet::new();
+        self.collect_search_paths(&mut search_paths, &mut visited)?;
+        Ok(search_paths)
+    }
+    fn collect_search_paths(
+        &self,
+        search_paths: &mut Vec<PathBuf>,
+        visited: &mut HashSet<String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // dependencies can depend on each other
+        if !visited.insert(self.manifest.package.name.clone()) {
+            return Ok(());
+        }
+        search_paths.extend(self.source_dirs());
+        for (name, dependency) in &self.manifest.dependencies {
+            let dependency_project = Project::open(&self.root.join(&dependency.path))?;
+            if &dependency_project.manifest.package.name != name {
+                return Err(format!(
+                    "dependency `{}` at {} is package `{}`",
+                    name, dependency.path, dependency_project.manifest.package.name
+                )
+                .into());
+            }
+            dependency_project.collect_search_paths(search_paths, visited)?;
+        }
+        Ok(())
+    }
+
+    /// entry_file returns the file of entry module
+    pub fn entry_file(&self) -> Option<PathBuf> {
+        let relative_path = module_path(&self.manifest.package.entry);
+        self.source_dirs()
+            .into_iter()
+            .map(|dir| dir.join(&relative_path))
+            .find(|path| path.is_file())
+    }
+}
+
+/// is_package_name checks `name` is an identifier, e.g. `hello_world`, so it can be used as a file
+/// name
+pub fn is_package_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
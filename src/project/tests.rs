@@ -0,0 +1,94 @@
+use super::*;
+use crate::temp_dir::TempDir;
+
+#[test]
+fn manifest_with_default_values() {
+    let manifest: Manifest = "[package]\nname = \"hello\"\n".parse().unwrap();
+    assert_eq!(
+        manifest,
+        Manifest {
+            package: Package {
+                name: "hello".to_string(),
+                entry: "main".to_string(),
+                source_dirs: vec!["src".to_string()],
+            },
+            dependencies: BTreeMap::new(),
+        }
+    );
+}
+
+#[test]
+fn manifest_with_dependencies() {
+    let manifest: Manifest = "
+    [package]
+    name = \"hello\"
+    entry = \"app.main\"
+    source-dirs = [\"src\", \"gen\"]
+
+    [dependencies]
+    util = { path = \"../util\" }
+    "
+    .parse()
+    .unwrap();
+    assert_eq!(manifest.package.entry, "app.main");
+    assert_eq!(manifest.package.source_dirs, vec!["src", "gen"]);
+    assert_eq!(manifest.dependencies["util"].path, "../util");
+}
+
+#[test]
+fn manifest_must_have_package_name() {
+    let manifest: Result<Manifest, _> = "[package]\nentry = \"main\"\n".parse();
+    assert!(manifest.is_err());
+}
+
+#[test]
+fn created_project_can_be_opened() {
+    let root = temp_dir("created");
+    Project::create(&root.join("hello"), "hello").unwrap();
+    let project = Project::open(&root.join("hello")).unwrap();
+    assert_eq!(project.manifest.package.name, "hello");
+    assert_eq!(
+        project.entry_file(),
+        Some(root.join("hello").join("src").join("main.elz"))
+    );
+    assert_eq!(
+        project.source_files(),
+        vec![root.join("hello").join("src").join("main.elz")]
+    );
+    assert!(Project::create(&root.join("hello"), "hello").is_err());
+}
+
+#[test]
+fn search_paths_include_dependencies() {
+    let root = temp_dir("dependencies");
+    Project::create(&root.join("app"), "app").unwrap();
+    Project::create(&root.join("util"), "util").unwrap();
+    std::fs::write(
+        root.join("app").join(MANIFEST_FILE),
+        "[package]\nname = \"app\"\n[dependencies]\nutil = { path = \"../util\" }\n",
+    )
+    .unwrap();
+    let project = Project::open(&root.join("app")).unwrap();
+    assert_eq!(
+        project.search_paths().unwrap(),
+        vec![
+            root.join("app").join("src"),
+            root.join("app").join("../util").join("src"),
+        ]
+    );
+}
+
+#[test]
+fn project_must_be_named_by_identifier() {
+    let root = temp_dir("invalid_name");
+    assert!(Project::create(&root.join("a"), "a/b").is_err());
+    assert!(Project::create(&root.join("a"), "a\"\nb").is_err());
+    assert!(!root.join("a").exists());
+    assert!(is_package_name("hello_world2"));
+    assert!(!is_package_name(""));
+}
+
+// helpers, must put tests before this line
+fn temp_dir(name: &str) -> TempDir {
+    TempDir::new(&format!("project-{}", name))
+}
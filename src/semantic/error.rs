@@ -1,4 +1,5 @@
 use super::type_checker::Type;
+use crate::ast::Operator;
 use crate::lexer::Location;
 use thiserror::Error;
 
@@ -21,6 +22,14 @@ enum SemanticErrorVariant {
     NoVariableNamed(String),
     #[error("no type named: `{}`",  .0)]
     NoTypeNamed(String),
+    #[error("unsupported operation: `{}` {} `{}`", .left, .op, .right)]
+    UnsupportedBinaryOperation {
+        op: Operator,
+        left: Type,
+        right: Type,
+    },
+    #[error("cannot show type `{}` in string template, it has no method `to_string(): string`", .0)]
+    CannotShowType(Type),
     #[error("call on non-function type: `{}`", .0)]
     CallOnNonFunctionType(Type),
     #[error("following fields must be inited but haven't: {}", ShowFieldsList(.0.to_vec()))]
@@ -39,6 +48,17 @@ enum SemanticErrorVariant {
         class_name: String,
         previous_definition: Location,
     },
+    #[error("redefined variant `{}` in enum `{}`, already defined at {}", .variant_name, .enum_name, .previous_definition)]
+    RedefinedVariant {
+        variant_name: String,
+        enum_name: String,
+        previous_definition: Location,
+    },
+    #[error("symbol `{}` is defined twice in the program, already defined at {}", .name, .previous_definition)]
+    RedefinedSymbol {
+        name: String,
+        previous_definition: Location,
+    },
     #[error("class `{}` has no member named `{}`", .class_name, .member_name)]
     NoMemberNamed {
         class_name: String,
@@ -46,8 +66,60 @@ enum SemanticErrorVariant {
     },
     #[error("function `{}` is not an extern function, must have a body", .function_name)]
     NonExternFunctionMustHaveBody { function_name: String },
+    #[error("variable `{}` is not an extern variable, must have a value", .variable_name)]
+    NonExternVariableMustHaveValue { variable_name: String },
     #[error("no module named: `{}`", .module_name)]
     NoModuleNamed { module_name: String },
+    #[error("module `{}` has no definition named: `{}`", .module_name, .name)]
+    NoNameInModule { module_name: String, name: String },
+    #[error("`{}` is private in module `{}`, defined at {}", .name, .module_name, .definition)]
+    PrivateName {
+        module_name: String,
+        name: String,
+        definition: Location,
+    },
+    #[error("member `{}` of class `{}` is private, defined at {}", .member_name, .class_name, .definition)]
+    PrivateMember {
+        class_name: String,
+        member_name: String,
+        definition: Location,
+    },
+    #[error("initialization cycle: {}", ShowChain(.0.to_vec()))]
+    InitializationCycle(Vec<String>),
+    #[error("integer `{}` is out of range of `{}`", .value, .typ)]
+    IntegerOutOfRange { value: u64, typ: Type },
+    #[error("cannot cast `{}` to `{}`, only number types can be casted", .from, .to)]
+    CannotCast { from: Type, to: Type },
+    #[error("non-exhaustive match, patterns not covered: {}", ShowPatterns(.0.to_vec()))]
+    NonExhaustiveMatch(Vec<String>),
+    #[error("unreachable match arm, its pattern is covered by previous arms")]
+    UnreachableArm,
+    #[error("variant `{}` has {} fields, but the pattern binds {}", .variant, .expected, .actual)]
+    PatternFieldsMismatched {
+        variant: String,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("expected {} arguments, but got {}", .expected, .actual)]
+    ArgumentCountMismatched { expected: usize, actual: usize },
+    #[error("missing arguments for parameters: {}", ShowPatterns(.0.to_vec()))]
+    MissingArguments(Vec<String>),
+    #[error("no parameter named `{}`", .0)]
+    NoParameterNamed(String),
+    #[error("argument of parameter `{}` is given more than once", .0)]
+    DuplicatedArgument(String),
+    #[error("positional argument cannot follow named arguments")]
+    PositionalArgumentAfterNamed,
+    #[error(
+        "required parameter `{}` cannot follow parameter `{}` has default value",
+        .required,
+        .default
+    )]
+    RequiredParameterAfterDefault { required: String, default: String },
+    #[error("type `{}` has no element `{}`", .typ, .index)]
+    NoTupleElement { typ: Type, index: usize },
+    #[error("cannot destructure `{}` into {} names", .typ, .count)]
+    DestructureMismatched { typ: Type, count: usize },
 }
 
 impl SemanticError {
@@ -63,6 +135,23 @@ impl SemanticError {
     pub(crate) fn message(&self) -> String {
         format!("{}", self)
     }
+    /// related returns other locations should be shown with the error
+    pub(crate) fn related(&self) -> Vec<(Location, String)> {
+        use SemanticErrorVariant::*;
+        match &self.err {
+            PrivateName { definition, .. } | PrivateMember { definition, .. } => {
+                vec![(definition.clone(), "defined without `pub` here".to_string())]
+            }
+            RedefinedSymbol {
+                previous_definition,
+                ..
+            } => vec![(
+                previous_definition.clone(),
+                "already defined here".to_string(),
+            )],
+            _ => vec![],
+        }
+    }
 
     pub fn no_module_named(location: &Location, module_name: impl ToString) -> SemanticError {
         SemanticError::new(
@@ -72,6 +161,19 @@ impl SemanticError {
             },
         )
     }
+    pub fn no_name_in_module(
+        location: &Location,
+        module_name: impl ToString,
+        name: impl ToString,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::NoNameInModule {
+                module_name: module_name.to_string(),
+                name: name.to_string(),
+            },
+        )
+    }
     pub fn non_extern_function_must_have_body<T: ToString>(
         location: &Location,
         function_name: T,
@@ -83,6 +185,17 @@ impl SemanticError {
             },
         )
     }
+    pub fn non_extern_variable_must_have_value<T: ToString>(
+        location: &Location,
+        variable_name: T,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::NonExternVariableMustHaveValue {
+                variable_name: variable_name.to_string(),
+            },
+        )
+    }
     pub fn name_redefined<T: ToString>(location: &Location, name: T) -> SemanticError {
         SemanticError::new(
             location,
@@ -107,6 +220,24 @@ impl SemanticError {
             SemanticErrorVariant::NoTypeNamed(name.to_string()),
         )
     }
+    pub fn unsupported_binary_operation(
+        location: &Location,
+        op: &Operator,
+        left: &Type,
+        right: &Type,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::UnsupportedBinaryOperation {
+                op: op.clone(),
+                left: left.clone(),
+                right: right.clone(),
+            },
+        )
+    }
+    pub fn cannot_show_type(location: &Location, typ: &Type) -> SemanticError {
+        SemanticError::new(location, SemanticErrorVariant::CannotShowType(typ.clone()))
+    }
     pub fn call_on_non_function_type(location: &Location, typ: Type) -> SemanticError {
         SemanticError::new(location, SemanticErrorVariant::CallOnNonFunctionType(typ))
     }
@@ -133,6 +264,24 @@ impl SemanticError {
             },
         )
     }
+    pub fn integer_out_of_range(location: &Location, value: u64, typ: &Type) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::IntegerOutOfRange {
+                value,
+                typ: typ.clone(),
+            },
+        )
+    }
+    pub fn cannot_cast(location: &Location, from: &Type, to: &Type) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::CannotCast {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        )
+    }
     pub fn dead_code_after_return_statement(location: &Location) -> SemanticError {
         SemanticError::new(location, SemanticErrorVariant::DeadCodeAfterReturnStatement)
     }
@@ -151,6 +300,34 @@ impl SemanticError {
             },
         )
     }
+    pub fn redefined_variant(
+        location: &Location,
+        variant_name: String,
+        enum_name: String,
+        previous_definition: Location,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::RedefinedVariant {
+                variant_name,
+                enum_name,
+                previous_definition,
+            },
+        )
+    }
+    pub fn redefined_symbol(
+        location: &Location,
+        name: impl ToString,
+        previous_definition: Location,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::RedefinedSymbol {
+                name: name.to_string(),
+                previous_definition,
+            },
+        )
+    }
     pub fn no_member_named(
         location: &Location,
         class_name: String,
@@ -164,6 +341,119 @@ impl SemanticError {
             },
         )
     }
+    pub fn private_name(
+        location: &Location,
+        module_name: impl ToString,
+        name: impl ToString,
+        definition: Location,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::PrivateName {
+                module_name: module_name.to_string(),
+                name: name.to_string(),
+                definition,
+            },
+        )
+    }
+    pub fn private_member(
+        location: &Location,
+        class_name: String,
+        member_name: String,
+        definition: Location,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::PrivateMember {
+                class_name,
+                member_name,
+                definition,
+            },
+        )
+    }
+    pub fn initialization_cycle(location: &Location, chain: Vec<String>) -> SemanticError {
+        SemanticError::new(location, SemanticErrorVariant::InitializationCycle(chain))
+    }
+    pub fn non_exhaustive_match(location: &Location, missing: Vec<String>) -> SemanticError {
+        SemanticError::new(location, SemanticErrorVariant::NonExhaustiveMatch(missing))
+    }
+    pub fn unreachable_arm(location: &Location) -> SemanticError {
+        SemanticError::new(location, SemanticErrorVariant::UnreachableArm)
+    }
+    pub fn pattern_fields_mismatched(
+        location: &Location,
+        variant: String,
+        expected: usize,
+        actual: usize,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::PatternFieldsMismatched {
+                variant,
+                expected,
+                actual,
+            },
+        )
+    }
+    pub fn argument_count_mismatched(
+        location: &Location,
+        expected: usize,
+        actual: usize,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::ArgumentCountMismatched { expected, actual },
+        )
+    }
+    pub fn missing_arguments(location: &Location, parameters: Vec<String>) -> SemanticError {
+        SemanticError::new(location, SemanticErrorVariant::MissingArguments(parameters))
+    }
+    pub fn required_parameter_after_default(
+        location: &Location,
+        required: &str,
+        default: &str,
+    ) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::RequiredParameterAfterDefault {
+                required: required.to_string(),
+                default: default.to_string(),
+            },
+        )
+    }
+    pub fn no_parameter_named(location: &Location, name: &str) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::NoParameterNamed(name.to_string()),
+        )
+    }
+    pub fn duplicated_argument(location: &Location, name: &str) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::DuplicatedArgument(name.to_string()),
+        )
+    }
+    pub fn positional_argument_after_named(location: &Location) -> SemanticError {
+        SemanticError::new(location, SemanticErrorVariant::PositionalArgumentAfterNamed)
+    }
+    pub fn no_tuple_element(location: &Location, typ: &Type, index: usize) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::NoTupleElement {
+                typ: typ.clone(),
+                index,
+            },
+        )
+    }
+    pub fn destructure_mismatched(location: &Location, typ: &Type, count: usize) -> SemanticError {
+        SemanticError::new(
+            location,
+            SemanticErrorVariant::DestructureMismatched {
+                typ: typ.clone(),
+                count,
+            },
+        )
+    }
 }
 
 struct ShowFieldsList(Vec<String>);
@@ -175,3 +465,19 @@ impl std::fmt::Display for ShowFieldsList {
         write!(f, "")
     }
 }
+
+struct ShowPatterns(Vec<String>);
+impl std::fmt::Display for ShowPatterns {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let patterns: Vec<String> = self.0.iter().map(|p| format!("`{}`", p)).collect();
+        write!(f, "{}", patterns.join(", "))
+    }
+}
+
+struct ShowChain(Vec<String>);
+impl std::fmt::Display for ShowChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let names: Vec<String> = self.0.iter().map(|name| format!("`{}`", name)).collect();
+        write!(f, "{}", names.join(" -> "))
+    }
+}
@@ -0,0 +1,299 @@
+use super::error::{Result, SemanticError};
+use crate::ast::*;
+use crate::lexer::Location;
+use std::collections::{HashMap, HashSet};
+
+/// initialization_order returns global variables in the order they must be initialized, a variable
+/// comes after all variables its initializer depends on, directly or through functions it calls.
+///
+/// e.g.
+/// ```elz
+/// x: int = f();
+/// f(): int = y;
+/// y: int = 1;
+/// ```
+/// has order `y`, `x`, and a cycle like `x: int = f(); f(): int = x;` is an error. The class of
+/// the receiver of method call is unknown here, so `a.get()` depends on method `get` of all
+/// classes.
+pub(crate) fn initialization_order(top_list: &Vec<TopAst>) -> Result<Vec<&Variable>> {
+    let mut definitions = HashMap::new();
+    for top in top_list {
+        match top {
+            TopAst::Variable(v) => {
+                definitions.insert(v.name.clone(), Definition::Variable(v));
+            }
+            TopAst::Function(f) => {
+                definitions.insert(f.name.clone(), Definition::Function(f));
+            }
+            TopAst::Class(c) => {
+                for member in &c.members {
+                    match member {
+                        ClassMember::StaticMethod(f) => {
+                            definitions
+                                .insert(format!("{}::{}", c.name, f.name), Definition::Function(f));
+                        }
+                        ClassMember::Method(f) => {
+                            definitions.insert(
+                                format!("{}::{}", c.name, f.name),
+                                Definition::Method(c, f),
+                            );
+                        }
+                        ClassMember::Field(_) => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    let mut init_order = InitOrder {
+        definitions,
+        visiting: vec![],
+        visited: HashSet::new(),
+        order: vec![],
+    };
+    for top in top_list {
+        if let TopAst::Variable(v) = top {
+            init_order.visit(&v.name)?;
+        }
+    }
+    Ok(init_order.order)
+}
+
+#[derive(Clone, Copy)]
+enum Definition<'a> {
+    Variable(&'a Variable),
+    Function(&'a Function),
+    Method(&'a Class, &'a Function),
+}
+
+impl<'a> Definition<'a> {
+    fn location(&self) -> &Location {
+        match self {
+            Definition::Variable(v) => &v.location,
+            Definition::Function(f) | Definition::Method(_, f) => &f.location,
+        }
+    }
+    fn dependencies(&self) -> Vec<String> {
+        let mut dependencies = vec![];
+        match self {
+            Definition::Variable(v) => {
+                if let Some(expr) = &v.expr {
+                    expr_dependencies(expr, &HashSet::new(), &mut dependencies)
+                }
+            }
+            Definition::Function(f) => function_dependencies(f, HashSet::new(), &mut dependencies),
+            Definition::Method(c, f) => {
+                // fields can be used without `self.` in method
+                let mut bound: HashSet<String> = c
+                    .members
+                    .iter()
+                    .filter_map(|member| match member {
+                        ClassMember::Field(field) => Some(field.name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                bound.insert("self".to_string());
+                function_dependencies(f, bound, &mut dependencies)
+            }
+        }
+        dependencies
+    }
+}
+
+fn function_dependencies(f: &Function, mut bound: HashSet<String>, dependencies: &mut Vec<String>) {
+    // default values of parameters are evaluated by calls to the function
+    for p in &f.parameters {
+        if let Some(default) = &p.default {
+            expr_dependencies(default, &HashSet::new(), dependencies);
+        }
+    }
+    bound.extend(f.parameters.iter().map(|p| p.name.clone()));
+    match &f.body {
+        Some(Body::Expr(e)) => expr_dependencies(e, &bound, dependencies),
+        Some(Body::Block(b)) => block_dependencies(b, &mut bound, dependencies),
+        None => (),
+    }
+}
+
+struct InitOrder<'a> {
+    definitions: HashMap<String, Definition<'a>>,
+    // the path from a variable to the current definition
+    visiting: Vec<String>,
+    visited: HashSet<String>,
+    order: Vec<&'a Variable>,
+}
+
+impl<'a> InitOrder<'a> {
+    fn visit(&mut self, name: &str) -> Result<()> {
+        if self.visited.contains(name) {
+            return Ok(());
+        }
+        // `.get` is a call of method `get` on a receiver of unknown class
+        if let Some(method) = name.strip_prefix('.') {
+            let suffix = format!("::{}", method);
+            let mut methods: Vec<String> = self
+                .definitions
+                .iter()
+                .filter(|(k, d)| matches!(d, Definition::Method(..)) && k.ends_with(&suffix))
+                .map(|(k, _)| k.clone())
+                .collect();
+            methods.sort();
+            for m in methods {
+                self.visit(&m)?;
+            }
+            return Ok(());
+        }
+        let definition = match self.definitions.get(name) {
+            Some(definition) => *definition,
+            // local names, imported names and builtins have nothing to initialize
+            None => return Ok(()),
+        };
+        if let Some(start) = self.visiting.iter().position(|n| n == name) {
+            let cycle = &self.visiting[start..];
+            // recursive functions are fine, only a cycle through a variable is a problem
+            let through_variable = cycle
+                .iter()
+                .any(|n| matches!(self.definitions.get(n), Some(Definition::Variable(..))));
+            if !through_variable {
+                return Ok(());
+            }
+            let mut chain = cycle.to_vec();
+            chain.push(name.to_string());
+            return Err(SemanticError::initialization_cycle(
+                definition.location(),
+                chain,
+            ));
+        }
+        self.visiting.push(name.to_string());
+        for dependency in definition.dependencies() {
+            self.visit(&dependency)?;
+        }
+        self.visiting.pop();
+        self.visited.insert(name.to_string());
+        if let Definition::Variable(v) = definition {
+            self.order.push(v);
+        }
+        Ok(())
+    }
+}
+
+/// free_names returns names used by the body of lambda those are not its parameters or defined
+/// in it, in the order they are used
+pub(crate) fn free_names(parameters: &[Parameter], body: &Expr) -> Vec<String> {
+    let bound = parameters.iter().map(|p| p.name.clone()).collect();
+    let mut names = vec![];
+    expr_dependencies(body, &bound, &mut names);
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
+    names
+}
+
+fn block_dependencies(b: &Block, bound: &mut HashSet<String>, dependencies: &mut Vec<String>) {
+    for stmt in &b.statements {
+        use StatementVariant::*;
+        match &stmt.value {
+            Return(Some(e)) | Expression(e) => expr_dependencies(e, bound, dependencies),
+            Return(None) => (),
+            Variable(v) => {
+                if let Some(expr) = &v.expr {
+                    expr_dependencies(expr, bound, dependencies);
+                }
+                bound.insert(v.name.clone());
+            }
+            Destructure { names, expr } => {
+                expr_dependencies(expr, bound, dependencies);
+                bound.extend(names.iter().cloned());
+            }
+            IfBlock {
+                clauses,
+                else_block,
+            } => {
+                for (cond, block) in clauses {
+                    expr_dependencies(cond, bound, dependencies);
+                    block_dependencies(block, &mut bound.clone(), dependencies);
+                }
+                block_dependencies(else_block, &mut bound.clone(), dependencies);
+            }
+        }
+    }
+}
+
+fn value_block_dependencies(
+    b: &ValueBlock,
+    bound: &HashSet<String>,
+    dependencies: &mut Vec<String>,
+) {
+    let mut bound = bound.clone();
+    block_dependencies(&b.block, &mut bound, dependencies);
+    expr_dependencies(&b.value, &bound, dependencies);
+}
+
+fn expr_dependencies(e: &Expr, bound: &HashSet<String>, dependencies: &mut Vec<String>) {
+    use ExprVariant::*;
+    match &e.value {
+        Identifier(name) => {
+            if !bound.contains(name) {
+                dependencies.push(name.clone());
+            }
+        }
+        Binary(lhs, rhs, _) => {
+            expr_dependencies(lhs, bound, dependencies);
+            expr_dependencies(rhs, bound, dependencies);
+        }
+        FuncCall(f, args) => {
+            expr_dependencies(f, bound, dependencies);
+            if let MemberAccess(_, method) = &f.value {
+                dependencies.push(format!(".{}", method));
+            }
+            for arg in args {
+                expr_dependencies(&arg.expr, bound, dependencies);
+            }
+        }
+        MemberAccess(from, _) | Interpolation(from) | Cast(from, _) | TupleAccess(from, _) => {
+            expr_dependencies(from, bound, dependencies)
+        }
+        List(exprs) | Tuple(exprs) => {
+            for e in exprs {
+                expr_dependencies(e, bound, dependencies);
+            }
+        }
+        ClassConstruction(_, field_inits) => {
+            for e in field_inits.values() {
+                expr_dependencies(e, bound, dependencies);
+            }
+        }
+        Match(value, arms) => {
+            expr_dependencies(value, bound, dependencies);
+            for arm in arms {
+                let mut bound = bound.clone();
+                match &arm.pattern {
+                    Pattern::Binding(name) => {
+                        bound.insert(name.clone());
+                    }
+                    Pattern::Variant(_, bindings) => bound.extend(bindings.iter().cloned()),
+                    _ => (),
+                }
+                expr_dependencies(&arm.expr, &bound, dependencies);
+            }
+        }
+        If {
+            clauses,
+            else_block,
+        } => {
+            for (cond, block) in clauses {
+                expr_dependencies(cond, bound, dependencies);
+                value_block_dependencies(block, bound, dependencies);
+            }
+            value_block_dependencies(else_block, bound, dependencies);
+        }
+        // the body runs when the lambda is called, it can be called by the initializer
+        Lambda {
+            parameters, body, ..
+        } => {
+            let mut bound = bound.clone();
+            bound.extend(parameters.iter().map(|p| p.name.clone()));
+            expr_dependencies(body, &bound, dependencies);
+        }
+        F64(..) | Int(..) | Bool(..) | String(..) => (),
+    }
+}
@@ -1,14 +1,18 @@
 use crate::ast::*;
+use crate::ast_printer::AstPrinter;
 use crate::lexer::Location;
 
 mod error;
+mod init_order;
 mod tag;
 mod type_checker;
 
-use error::{Result, SemanticError};
+use error::Result;
+pub use error::SemanticError;
+pub(crate) use init_order::{free_names, initialization_order};
 use std::collections::HashMap;
 use tag::SemanticTag;
-use type_checker::{Type, TypeEnv};
+use type_checker::{Signature, Type, TypeEnv};
 
 pub struct SemanticChecker {
     top_env: TypeEnv,
@@ -23,10 +27,74 @@ impl SemanticChecker {
 }
 
 impl SemanticChecker {
+    /// check_program checks modules, then reports definitions have the same symbol in the
+    /// generated program. Definitions are prefixed with their module name, except definitions of
+    /// prelude, extern functions and variables and the entry function `main`. An extern function
+    /// or variable can be declared by many modules with the same signature.
     pub fn check_program(&mut self, modules: &Vec<Module>) -> Result<()> {
+        self.check_modules(modules)?;
+        let mut symbols: HashMap<&String, (&Location, Option<ExternSignature>)> = HashMap::new();
+        for m in modules {
+            for top in &m.top_list {
+                let (name, location, signature) = match top {
+                    TopAst::Function(f) if f.tag.is_builtin() => continue,
+                    TopAst::Function(f) if f.tag.is_extern() => {
+                        (&f.name, &f.location, Some(extern_signature(f)))
+                    }
+                    TopAst::Function(f)
+                        if m.name == "prelude" || m.name == "main" && f.name == "main" =>
+                    {
+                        (&f.name, &f.location, None)
+                    }
+                    TopAst::Variable(v) if v.tag.is_extern() => {
+                        (&v.name, &v.location, Some((None, v.typ.name(), false)))
+                    }
+                    TopAst::Variable(v) if m.name == "prelude" => (&v.name, &v.location, None),
+                    _ => continue,
+                };
+                match symbols.get(name) {
+                    Some((previous, previous_signature))
+                        if signature.is_none() || *previous_signature != signature =>
+                    {
+                        return Err(SemanticError::redefined_symbol(
+                            location,
+                            name,
+                            (*previous).clone(),
+                        ));
+                    }
+                    Some(_) => (),
+                    None => {
+                        symbols.insert(name, (location, signature));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// print_typed shows modules checked by `check_program` as AST with the type of each
+    /// expression
+    ///
+    /// ```text
+    /// module main
+    ///   variable x: int
+    ///     binary + : int
+    ///       int 1 : int
+    ///       identifier y : int
+    /// ```
+    pub fn print_typed(&self, modules: &[Module]) -> String {
+        let expr_types = self.top_env.expr_types.borrow();
+        let mut printer = AstPrinter::typed(|e| expr_types.get(&e.id).map(|typ| typ.to_string()));
+        for m in modules {
+            printer.module(m);
+        }
+        printer.finish()
+    }
+
+    fn check_modules(&mut self, modules: &Vec<Module>) -> Result<HashMap<String, TypeEnv>> {
         let mut module_envs = HashMap::new();
         for m in modules {
-            let module_env = self.prepare_imports(m)?;
+            let module_env = self.prepare_imports(m, modules)?;
             module_envs.insert(m.name.clone(), module_env);
         }
         for m in modules {
@@ -38,21 +106,69 @@ impl SemanticChecker {
         for m in modules {
             self.check_module(m, &mut module_envs)?;
         }
-        Ok(())
+        Ok(module_envs)
     }
 
-    fn prepare_imports(&mut self, module: &Module) -> Result<TypeEnv> {
+    fn prepare_imports(&mut self, module: &Module, modules: &[Module]) -> Result<TypeEnv> {
         let mut module_env = TypeEnv::with_parent(&self.top_env);
+        module_env.module_name = module.name.clone();
         for top in &module.top_list {
             use TopAst::*;
             match &top {
                 Import(i) => {
-                    for component in &i.imported_component {
+                    let imported_module = match modules.iter().find(|m| m.name == i.import_path) {
+                        Some(m) => m,
+                        None => {
+                            return Err(SemanticError::no_module_named(&i.location, &i.import_path))
+                        }
+                    };
+                    let definitions = imported_module.definitions();
+                    // `io.println`, or `io2.println` for `import io as io2`, using a private
+                    // name used in qualified form is reported when it is looked up
+                    let qualifier = i.alias.as_ref().unwrap_or(&i.import_path);
+                    for name in &definitions {
                         module_env.imports.insert(
-                            component.clone(),
-                            with_module_name(i.import_path.clone(), component),
+                            format!("{}.{}", qualifier, name),
+                            with_module_name(i.import_path.clone(), name),
                         );
                     }
+                    for component in &i.imported_component {
+                        match component {
+                            ImportedComponent::All(_) => {
+                                for name in imported_module.public_definitions() {
+                                    module_env.imports.insert(
+                                        name.to_string(),
+                                        with_module_name(i.import_path.clone(), name),
+                                    );
+                                }
+                            }
+                            ImportedComponent::Name {
+                                location,
+                                name,
+                                alias,
+                            } => {
+                                if !definitions.contains(&name) {
+                                    return Err(SemanticError::no_name_in_module(
+                                        location,
+                                        &i.import_path,
+                                        name,
+                                    ));
+                                }
+                                if let Some(definition) = imported_module.private_definition(name) {
+                                    return Err(SemanticError::private_name(
+                                        location,
+                                        &i.import_path,
+                                        name,
+                                        definition.clone(),
+                                    ));
+                                }
+                                module_env.imports.insert(
+                                    alias.as_ref().unwrap_or(name).clone(),
+                                    with_module_name(i.import_path.clone(), name),
+                                );
+                            }
+                        }
+                    }
                 }
                 _ => (),
             }
@@ -70,13 +186,30 @@ impl SemanticChecker {
             match &top {
                 Class(c) => {
                     let typ = module_env.new_class(c)?;
-                    self.top_env.add_type(
-                        &c.location,
-                        &with_module_name(module.name.clone(), &c.name),
-                        typ.clone(),
-                    )?;
+                    let key = with_module_name(module.name.clone(), &c.name);
+                    self.top_env.add_type(&c.location, &key, typ.clone())?;
+                    if !c.public {
+                        self.top_env.mark_private(key);
+                    }
                     module_env.add_type(&c.location, &c.name, typ)?;
                 }
+                Enum(e) => {
+                    let typ = module_env.new_enum(e);
+                    let key = with_module_name(module.name.clone(), &e.name);
+                    self.top_env.add_type(&e.location, &key, typ.clone())?;
+                    if !e.public {
+                        self.top_env.mark_private(key);
+                    }
+                    module_env.add_type(&e.location, &e.name, typ)?;
+                }
+                _ => (),
+            }
+        }
+        for top in &module.top_list {
+            use TopAst::*;
+            match &top {
+                Class(c) => module_env.add_class_members(c)?,
+                Enum(e) => module_env.add_enum_variants(e)?,
                 _ => (),
             }
         }
@@ -96,24 +229,52 @@ impl SemanticChecker {
                         match member {
                             ClassMember::StaticMethod(static_method) => {
                                 let typ = module_env.new_function_type(static_method)?;
-                                self.top_env.add_variable(
+                                let key = with_module_name(
+                                    module.name.clone(),
+                                    &format!("{}::{}", c.name, static_method.name),
+                                );
+                                let signature = Signature::of(static_method);
+                                self.top_env.add_function(
                                     &static_method.location,
-                                    &with_module_name(
-                                        module.name.clone(),
-                                        &format!("{}::{}", c.name, static_method.name),
-                                    ),
+                                    &key,
                                     typ.clone(),
+                                    signature.clone(),
                                 )?;
-                                module_env.add_variable(
+                                // a static method can't be used out of module if its class is
+                                // private
+                                if !c.public || !static_method.public {
+                                    self.top_env.mark_private(key);
+                                }
+                                module_env.add_function(
                                     &static_method.location,
                                     &format!("{}::{}", c.name, static_method.name),
                                     typ,
+                                    signature,
                                 )?;
                             }
                             _ => (),
                         }
                     }
                 }
+                // `Shape::Circle` is used like a static method
+                Enum(e) => {
+                    for variant in &e.variants {
+                        let typ = module_env.variant_constructor_type(e, variant)?;
+                        let name = format!("{}::{}", e.name, variant.name);
+                        let key = with_module_name(module.name.clone(), &name);
+                        let signature = Signature::of_variant(variant);
+                        self.top_env.add_function(
+                            &variant.location,
+                            &key,
+                            typ.clone(),
+                            signature.clone(),
+                        )?;
+                        if !e.public {
+                            self.top_env.mark_private(key);
+                        }
+                        module_env.add_function(&variant.location, &name, typ, signature)?;
+                    }
+                }
                 _ => (),
             }
         }
@@ -122,21 +283,23 @@ impl SemanticChecker {
             match &top {
                 Variable(v) => {
                     let typ = module_env.from(&v.typ)?;
-                    self.top_env.add_variable(
-                        &v.location,
-                        &with_module_name(module.name.clone(), &v.name),
-                        typ.clone(),
-                    )?;
+                    let key = with_module_name(module.name.clone(), &v.name);
+                    self.top_env.add_variable(&v.location, &key, typ.clone())?;
+                    if !v.public {
+                        self.top_env.mark_private(key);
+                    }
                     module_env.add_variable(&v.location, &v.name, typ)?;
                 }
                 Function(f) => {
                     let typ = module_env.new_function_type(f)?;
-                    self.top_env.add_variable(
-                        &f.location,
-                        &with_module_name(module.name.clone(), &f.name),
-                        typ.clone(),
-                    )?;
-                    module_env.add_variable(&f.location, &f.name, typ)?;
+                    let key = with_module_name(module.name.clone(), &f.name);
+                    let signature = Signature::of(f);
+                    self.top_env
+                        .add_function(&f.location, &key, typ.clone(), signature.clone())?;
+                    if !f.public {
+                        self.top_env.mark_private(key);
+                    }
+                    module_env.add_function(&f.location, &f.name, typ, signature)?;
                 }
                 _ => (),
             }
@@ -155,13 +318,29 @@ impl SemanticChecker {
             match &top {
                 Import(_) => (),
                 Variable(v) => {
-                    let typ = module_env.type_of_expr(&v.expr)?;
-                    // show where error happened
-                    // we are unifying <expr> and <type>, so <expr> location is better than
-                    // variable define statement location
-                    module_env.unify(&v.expr.location, &module_env.from(&v.typ)?, &typ)?
+                    let var_def_typ = module_env.from(&v.typ)?;
+                    match &v.expr {
+                        Some(expr) => {
+                            let typ = module_env.type_of_expr_against(expr, &var_def_typ)?;
+                            // show where error happened
+                            // we are unifying <expr> and <type>, so <expr> location is better
+                            // than variable define statement location
+                            module_env.unify(&expr.location, &var_def_typ, &typ)?
+                        }
+                        // extern variable is defined by the linked library
+                        None if v.tag.is_extern() => (),
+                        None => {
+                            return Err(SemanticError::non_extern_variable_must_have_value(
+                                &v.location,
+                                &v.name,
+                            ))
+                        }
+                    }
+                }
+                Function(f) => {
+                    self.check_parameter_defaults(&f.parameters, module_env)?;
+                    self.check_function_body(&f.location, f, module_env)?
                 }
-                Function(f) => self.check_function_body(&f.location, &f, &module_env)?,
                 Class(c) => {
                     let mut class_type_env = TypeEnv::with_parent(&module_env);
                     for member in &c.members {
@@ -177,6 +356,10 @@ impl SemanticChecker {
                     for member in &c.members {
                         match member {
                             ClassMember::StaticMethod(static_method) => {
+                                self.check_parameter_defaults(
+                                    &static_method.parameters,
+                                    module_env,
+                                )?;
                                 self.check_function_body(
                                     &static_method.location,
                                     &static_method,
@@ -184,37 +367,81 @@ impl SemanticChecker {
                                 )?;
                             }
                             ClassMember::Method(method) => {
-                                self.check_function_body(
-                                    &method.location,
-                                    &method,
-                                    &class_type_env,
-                                )?;
+                                self.check_parameter_defaults(&method.parameters, module_env)?;
+                                // method can refer to the instance by `self`
+                                let mut method_env = TypeEnv::with_parent(&class_type_env);
+                                let self_type = method_env.from(&ParsedType::type_name(&c.name))?;
+                                method_env.add_variable(&method.location, "self", self_type)?;
+                                self.check_function_body(&method.location, method, &method_env)?;
                             }
                             _ => (),
                         }
                     }
                 }
+                // field types of variants were checked when the enum type was prepared
+                Enum(e) => {
+                    for variant in &e.variants {
+                        self.check_parameter_defaults(&variant.fields, module_env)?;
+                    }
+                }
                 Trait(_) => unimplemented!(),
             }
         }
+        initialization_order(&module.top_list)?;
+        Ok(())
+    }
+
+    /// check_parameter_defaults checks default values of parameters, they're evaluated where the
+    /// function is called, so they can only use definitions of module, but not other parameters
+    /// or fields. A parameter with default value cannot be followed by a required one, since its
+    /// argument could never be omitted.
+    fn check_parameter_defaults(&self, parameters: &Vec<Parameter>, env: &TypeEnv) -> Result<()> {
+        let mut type_env = TypeEnv::with_parent(env);
+        let mut last_default: Option<&Parameter> = None;
+        for p in parameters {
+            match (&p.default, last_default) {
+                (Some(default), _) => {
+                    let typ = type_env.from(&p.typ)?;
+                    let default_type = type_env.type_of_expr_against(default, &typ)?;
+                    type_env.unify(&default.location, &typ, &default_type)?;
+                    last_default = Some(p);
+                }
+                (
+                    None,
+                    Some(Parameter {
+                        name,
+                        default: Some(default),
+                        ..
+                    }),
+                ) => {
+                    return Err(SemanticError::required_parameter_after_default(
+                        &default.location,
+                        &p.name,
+                        name,
+                    ));
+                }
+                (None, _) => (),
+            }
+        }
         Ok(())
     }
 
     fn check_function_body(&self, location: &Location, f: &Function, env: &TypeEnv) -> Result<()> {
         let return_type = env.from(&f.ret_typ)?;
         let mut type_env = TypeEnv::with_parent(env);
-        for Parameter { name, typ } in &f.parameters {
+        for Parameter { name, typ, .. } in &f.parameters {
             type_env.add_variable(location, name, type_env.from(typ)?)?;
         }
         match &f.body {
             Some(Body::Expr(e)) => {
-                let e_type = type_env.type_of_expr(e)?;
+                let e_type = type_env.type_of_expr_against(e, &return_type)?;
                 type_env.unify(location, &return_type, &e_type)
             }
             Some(Body::Block(b)) => self.check_block(&type_env, b, &return_type),
             None => {
-                if f.tag.is_extern() {
-                    // extern function declaration don't have body need to check
+                if f.tag.is_extern() || f.tag.is_builtin() {
+                    // extern function declaration don't have body need to check, so as builtin
+                    // function, code generator generates code for calls to it
                     // e.g.
                     // ```
                     // foo(): void;
@@ -251,7 +478,7 @@ impl SemanticChecker {
                 match &stmt.value {
                     Return(e) => {
                         let typ = match e {
-                            Some(e) => type_env.type_of_expr(e)?,
+                            Some(e) => type_env.type_of_expr_against(e, return_type)?,
                             None => type_env.lookup_type(location, "void")?.typ,
                         };
                         if i != b.statements.len() - 1 {
@@ -260,10 +487,7 @@ impl SemanticChecker {
                         type_env.unify(location, return_type, &typ)?;
                     }
                     Variable(v) => {
-                        let var_def_typ = type_env.from(&v.typ)?;
-                        let var_typ = type_env.type_of_expr(&v.expr)?;
-                        type_env.unify(location, &var_def_typ, &var_typ)?;
-                        type_env.add_variable(location, &v.name, var_def_typ)?;
+                        type_env.check_local_variable(location, v)?;
                         if i == b.statements.len() - 1 {
                             type_env.unify(
                                 location,
@@ -287,6 +511,16 @@ impl SemanticChecker {
                             )?;
                         }
                     }
+                    Destructure { names, expr } => {
+                        type_env.check_destructure(location, names, expr)?;
+                        if i == b.statements.len() - 1 {
+                            type_env.unify(
+                                location,
+                                return_type,
+                                &type_env.lookup_type(location, "void")?.typ,
+                            )?;
+                        }
+                    }
                     IfBlock {
                         clauses,
                         else_block,
@@ -309,7 +543,19 @@ impl SemanticChecker {
     }
 }
 
-fn with_module_name(mut module_name: String, name: &String) -> String {
+/// ExternSignature is the parameter types, return type and if the extern function is variadic,
+/// an extern variable has no parameter types but its type
+type ExternSignature = (Option<Vec<String>>, String, bool);
+
+fn extern_signature(f: &Function) -> ExternSignature {
+    (
+        Some(f.parameters.iter().map(|p| p.typ.name()).collect()),
+        f.ret_typ.name(),
+        f.tag.is_variadic(),
+    )
+}
+
+fn with_module_name(mut module_name: String, name: &str) -> String {
     module_name.push('.');
     module_name.push_str(name);
     module_name
@@ -2,17 +2,75 @@ use crate::ast::Tag;
 
 pub(crate) trait SemanticTag {
     fn is_extern(&self) -> bool;
+    fn is_builtin(&self) -> bool;
+    fn is_variadic(&self) -> bool;
+    fn primitive(&self) -> Option<Primitive>;
+}
+
+/// Primitive is the number type of a builtin class, arithmetic and casts are defined on them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Primitive {
+    /// `@builtin(i32)` or `@builtin(u8)`
+    Int { signed: bool, bits: u32 },
+    /// `@builtin(f64)`
+    Float(u32),
+}
+
+impl Primitive {
+    /// contains reports whether integer literal `value` can be represented by the primitive
+    pub fn contains(&self, value: u64) -> bool {
+        match *self {
+            Primitive::Int { signed: true, bits } => value < 1 << (bits - 1),
+            Primitive::Int {
+                signed: false,
+                bits,
+            } => bits == 64 || value < 1 << bits,
+            Primitive::Float(_) => true,
+        }
+    }
 }
 
 impl SemanticTag for Option<Tag> {
     fn is_extern(&self) -> bool {
         match self {
             Some(tag) => {
-                tag.name.as_str() == "extern"
-                    && tag.properties.len() == 1
-                    && tag.properties.last() == Some(&"c".to_string())
+                // `@extern(c)` or `@extern(c, variadic)`
+                tag.name.as_str() == "extern" && tag.properties.first() == Some(&"c".to_string())
             }
             None => false,
         }
     }
+    fn is_builtin(&self) -> bool {
+        match self {
+            Some(tag) => tag.name.as_str() == "builtin",
+            None => false,
+        }
+    }
+    /// is_variadic reports whether the function is `@extern(c, variadic)`, it takes more
+    /// arguments than its parameters
+    fn is_variadic(&self) -> bool {
+        match self {
+            Some(tag) => tag.name == "extern" && tag.properties.contains(&"variadic".to_string()),
+            None => false,
+        }
+    }
+    /// primitive returns the number type of `@builtin(iN)`, `@builtin(uN)` or `@builtin(fN)`,
+    /// `bool` is `@builtin(i1)` but it's not a number
+    fn primitive(&self) -> Option<Primitive> {
+        let properties = match self {
+            Some(tag) if tag.name == "builtin" && tag.properties.len() == 1 => &tag.properties,
+            _ => return None,
+        };
+        let p = properties[0].as_str();
+        let bits: u32 = p.get(1..)?.parse().ok()?;
+        match (&p[..1], bits) {
+            ("i", 8 | 16 | 32 | 64) => Some(Primitive::Int { signed: true, bits }),
+            ("u", 8 | 16 | 32 | 64) => Some(Primitive::Int {
+                signed: false,
+                bits,
+            }),
+            ("f", 32 | 64) => Some(Primitive::Float(bits)),
+            _ => None,
+        }
+    }
 }
@@ -351,6 +351,1075 @@ fn no_member_name() {
     assert_eq!(result.is_err(), true);
 }
 
+#[test]
+fn string_concat_and_equal() -> Result<()> {
+    let code = "
+    foo(a: string, b: string): bool = a + b == \"ab\";
+    ";
+    check_code(code)
+}
+
+#[test]
+fn string_length() -> Result<()> {
+    let code = "
+    foo(s: string): int = s.len() + 1;
+    ";
+    check_code(code)
+}
+
+#[test]
+fn cannot_add_int_and_bool() {
+    let code = "
+    x: int = 1 + true;
+    ";
+    let result = check_code(code);
+    assert!(result.is_err());
+}
+
+#[test]
+fn equal_result_is_bool() {
+    let code = "
+    x: int = 1 == 1;
+    ";
+    let result = check_code(code);
+    assert!(result.is_err());
+}
+
+#[test]
+fn method_can_refer_to_its_class() -> Result<()> {
+    let code = "
+    class Node {
+      value: int;
+      ::new(value: int): Node = Node {value: value};
+      next(): Node = Node::new(value + 1);
+    }
+    main(): void {
+      n: Node = Node::new(1).next().next();
+    }
+    ";
+    check_code(code)
+}
+
+#[test]
+fn underscore_variable_can_be_defined_many_times() -> Result<()> {
+    let code = "
+    foo(): int = 1;
+    main(): void {
+      _: int = foo();
+      _: int = foo();
+    }
+    ";
+    check_code(code)
+}
+
+#[test]
+fn interpolation_of_builtin_types() -> Result<()> {
+    let code = "
+    foo(i: int, f: f64, b: bool, s: string): string = \"{i} {f} {b} {s}\";
+    ";
+    check_code(code)
+}
+
+#[test]
+fn interpolation_of_class_with_to_string() -> Result<()> {
+    let code = "
+    class Foo {
+      ::new(): Foo = Foo {};
+      to_string(): string = \"foo\";
+    }
+    x: string = \"{Foo::new()}\";
+    ";
+    check_code(code)
+}
+
+#[test]
+fn cannot_show_class_without_to_string() {
+    let code = "
+    class Foo {
+      ::new(): Foo = Foo {};
+    }
+    x: string = \"{Foo::new()}\";
+    ";
+    let result = check_code(code);
+    assert!(result.is_err());
+}
+
+#[test]
+fn cannot_show_void() {
+    let code = "
+    foo(): void {}
+    x: string = \"{foo()}\";
+    ";
+    let result = check_code(code);
+    assert!(result.is_err());
+}
+
+#[test]
+fn global_variable_initialized_by_function_call() -> Result<()> {
+    let code = "
+    x: int = foo();
+    foo(): int = y;
+    y: int = 1;
+    ";
+    check_code(code)
+}
+
+#[test]
+fn global_variables_initialization_cycle() {
+    let code = "
+    x: int = y;
+    y: int = x;
+    ";
+    let result = check_code(code);
+    assert!(result.is_err());
+}
+
+#[test]
+fn global_variable_initialization_cycle_through_function() {
+    let code = "
+    x: int = foo();
+    foo(): int = bar();
+    bar(): int = x;
+    ";
+    let result = check_code(code);
+    assert!(result.is_err());
+}
+
+#[test]
+fn global_variable_initialization_cycle_through_method() {
+    let code = "
+    x: int = Foo::new().get();
+    class Foo {
+      ::new(): Foo = Foo {};
+      get(): int = x;
+    }
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::initialization_cycle(
+            &Location::from(2, 4),
+            vec!["x".to_string(), "Foo::get".to_string(), "x".to_string()],
+        )
+        .message()
+    );
+    // a field used without `self.` is not the global variable
+    let code = "
+    x: int = Foo::new().get();
+    class Foo {
+      x: int = 1;
+      ::new(): Foo = Foo {};
+      get(): int = x;
+    }
+    ";
+    assert!(check_code(code).is_ok());
+}
+
+#[test]
+fn recursive_function_and_local_shadowing_are_not_initialization_cycle() -> Result<()> {
+    let code = "
+    x: int = foo(1);
+    foo(x: int): int {
+      if x == 0 {
+        return foo(x);
+      } else {
+        return x;
+      }
+    }
+    ";
+    check_code(code)
+}
+
+#[test]
+fn print_typed_shows_type_of_expressions() -> Result<()> {
+    let code = "module test
+    import prelude (int, u8, string)
+    x: int = 1;
+    add(x: int, y: u8 = 2): int = x + y as int;
+    class Foo {
+      a: string;
+      ::new(): Foo = Foo {a: \"\"};
+      get(): string = a;
+    }
+    ";
+    let module = Parser::parse_program("", code).unwrap();
+    let mut checker = SemanticChecker::new();
+    let modules = vec![parse_prelude(), module];
+    checker.check_program(&modules)?;
+    assert_eq!(
+        checker.print_typed(&modules[1..]),
+        "module test
+  import prelude (int, u8, string)
+  variable x: int
+    int 1 : int
+  function add(x: int, y: u8): int
+    default y
+      int 2 : u8
+    binary + : int
+      identifier x : int
+      cast int : int
+        identifier y : u8
+  class Foo
+    field a: string
+    static method ::new(): Foo
+      construct Foo : Foo
+        field a
+          string \"\" : string
+    method get(): string
+      identifier a : string
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn print_typed_tells_expressions_at_same_location_apart() {
+    // a call and its callee start at the same location
+    let code = "module test
+    import prelude (int)
+    one(): int = 1;
+    x: int = one();
+    ";
+    let module = Parser::parse_program("", code).unwrap();
+    let mut checker = SemanticChecker::new();
+    let modules = vec![parse_prelude(), module];
+    checker.check_program(&modules).unwrap();
+    assert_eq!(
+        checker.print_typed(&modules[1..]),
+        "module test
+  import prelude (int)
+  function one(): int
+    int 1 : int
+  variable x: int
+    call : int
+      identifier one : (): int
+"
+    );
+}
+
+#[test]
+fn class_construction_with_field_of_wrong_type() {
+    let code = "
+    class Foo {
+      a: int;
+      ::new(): Foo = Foo {a: \"a\"};
+    }
+    ";
+    let result = check_code(code);
+    assert!(result.is_err());
+}
+
+#[test]
+fn import_from_missing_module() {
+    let code = "module main
+    import lib (foo)
+    ";
+    let module = Parser::parse_program("", code).unwrap();
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![parse_prelude(), module]);
+    assert_eq!(
+        result.unwrap_err().message(),
+        SemanticError::no_module_named(&Location::from(2, 4), "lib").message()
+    );
+}
+
+#[test]
+fn imported_class_brings_its_static_methods() -> Result<()> {
+    let lib = "module lib
+    import prelude (int)
+    pub class Foo {
+      a: int;
+      pub ::new(): Foo = Foo {a: 1};
+    }
+    ";
+    let main = "module main
+    import lib (Foo)
+    foo: Foo = Foo::new();
+    ";
+    let mut checker = SemanticChecker::new();
+    checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ])
+}
+
+#[test]
+fn qualified_types_and_static_methods_of_imported_module() {
+    let lib = "module lib.shapes
+    import prelude (int)
+    pub class Point {
+      x: int;
+      pub ::new(x: int): Point = Point {x: x};
+    }
+    pub enum Shape {
+      Circle(r: int),
+      Empty,
+    }
+    ";
+    let main = "module main
+    import prelude (int)
+    import lib.shapes as s
+    import lib.shapes
+    origin: s.Point = lib.shapes.Point::new(0);
+    size(shape: lib.shapes.Shape): int = match shape {
+      s.Shape::Circle(r) => r,
+      lib.shapes.Shape::Empty => 0,
+    };
+    unit: int = size(s.Shape::Circle(1));
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ]);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn import_with_alias_wildcard_and_qualified_access() -> Result<()> {
+    let lib = "module lib
+    import prelude (*)
+    pub add(x: int, y: int): int = x + y;
+    pub one: int = 1;
+    ";
+    let main = "module main
+    import prelude (*)
+    import lib as l (add as plus)
+    import lib (*)
+    a: int = plus(one, l.one);
+    b: int = lib.add(add(1, 2), l.add(3, 4));
+    ";
+    let mut checker = SemanticChecker::new();
+    checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ])
+}
+
+#[test]
+fn import_undefined_name() {
+    let lib = "module lib
+    one: int = 1;
+    ";
+    let main = "module main
+    import lib (two)
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ]);
+    assert_eq!(
+        result.unwrap_err().message(),
+        SemanticError::no_name_in_module(&Location::from(2, 16), "lib", "two").message()
+    );
+}
+
+#[test]
+fn local_variable_hides_module_of_qualified_access() {
+    let lib = "module lib
+    import prelude (*)
+    pub one: int = 1;
+    ";
+    let main = "module main
+    import prelude (*)
+    import lib
+    f(lib: int): int = lib.one;
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn extern_function_declared_by_many_modules() {
+    let lib = "module lib
+    import prelude (*)
+    @extern(c)
+    pub close(fd: i32): i32;
+    ";
+    let same = "module main
+    import prelude (*)
+    @extern(c)
+    close(fd: i32): i32;
+    ";
+    let different = "module main
+    import prelude (*)
+    @extern(c)
+    close(fd: int): i32;
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", same).unwrap(),
+    ]);
+    assert!(result.is_ok());
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", different).unwrap(),
+    ]);
+    assert_eq!(
+        result.unwrap_err().message(),
+        SemanticError::redefined_symbol(&Location::from(4, 4), "close", Location::from(4, 8))
+            .message()
+    );
+}
+
+#[test]
+fn extern_function_cannot_have_symbol_of_prelude() {
+    let code = "module main
+    import prelude (*)
+    @extern(c)
+    println(s: _c_string): i32;
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", code).unwrap(),
+    ]);
+    assert!(result
+        .unwrap_err()
+        .message()
+        .contains("symbol `println` is defined twice in the program"));
+}
+
+#[test]
+fn import_private_name() {
+    let lib = "module lib
+    import prelude (*)
+    one: int = 1;
+    ";
+    let main = "module main
+    import lib (one)
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ]);
+    assert_eq!(
+        result.unwrap_err().message(),
+        SemanticError::private_name(&Location::from(2, 16), "lib", "one", Location::from(3, 4))
+            .message()
+    );
+}
+
+#[test]
+fn private_names_are_hidden_from_qualified_access_and_wildcard() {
+    let lib = "module lib
+    import prelude (*)
+    pub class Foo {
+      ::new(): Foo = Foo {};
+    }
+    one: int = 1;
+    ";
+    for main in &[
+        "module main\n import prelude (*)\n import lib\n x: int = lib.one;",
+        "module main\n import prelude (*)\n import lib (*)\n x: int = one;",
+        "module main\n import lib (Foo)\n foo: Foo = Foo::new();",
+    ] {
+        let mut checker = SemanticChecker::new();
+        let result = checker.check_program(&vec![
+            parse_prelude(),
+            Parser::parse_program("", lib).unwrap(),
+            Parser::parse_program("", main).unwrap(),
+        ]);
+        assert!(result.is_err(), "{}", main);
+    }
+}
+
+#[test]
+fn private_member_can_only_be_used_in_its_module() {
+    let lib = "module lib
+    import prelude (*)
+    pub class Counter {
+      pub count: int = 0;
+      step: int = 1;
+      pub ::new(): Counter = Counter {};
+      pub next(): int = count + step;
+    }
+    pub counter: Counter = Counter::new();
+    pub step: int = counter.step;
+    ";
+    let main = "module main
+    import prelude (*)
+    import lib (Counter)
+    c: Counter = Counter::new();
+    count: int = c.count;
+    step: int = c.step;
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ]);
+    assert_eq!(
+        result.unwrap_err().message(),
+        SemanticError::private_member(
+            &Location::from(6, 17),
+            "Counter".to_string(),
+            "step".to_string(),
+            Location::from(5, 6)
+        )
+        .message()
+    );
+}
+
+#[test]
+fn integer_literal_has_expected_type() {
+    let code = "
+    limit: u8 = 200 + 55;
+    double(x: i16): i16 = x + x;
+    @extern(c)
+    putchar(c: i32): i32;
+    foo(x: u8): bool {
+      y: u32 = 4294967295;
+      _: i32 = putchar(65);
+      _: i16 = double(1000);
+      return x + 1 == limit;
+    }
+    ";
+    assert!(check_code(code).is_ok());
+}
+
+#[test]
+fn integer_literal_out_of_range() {
+    let code = "
+    x: i8 = 128;
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(2, 12));
+    assert!(err
+        .message()
+        .ends_with("integer `128` is out of range of `i8`"));
+    let code = "
+    foo(x: u8): u8 = x + 256;
+    ";
+    assert!(check_code(code).is_err());
+    let code = "
+    x: int = 9223372036854775808;
+    ";
+    assert!(check_code(code).is_err());
+    let code = "
+    x: int = 0x7FFF_FFFF_FFFF_FFFF;
+    y: u64 = 0xFFFF_FFFF_FFFF_FFFF;
+    ";
+    assert!(check_code(code).is_ok());
+}
+
+#[test]
+fn sized_integers_are_different_types() {
+    let code = "
+    x: int = 1;
+    y: u8 = x;
+    ";
+    assert!(check_code(code).is_err());
+    let code = "
+    foo(x: u8, y: i8): bool = x == y;
+    ";
+    assert!(check_code(code).is_err());
+}
+
+#[test]
+fn cast_between_number_types() {
+    let code = "
+    x: int = 300;
+    y: u8 = x as u8;
+    z: f64 = y as f64 + 0.5;
+    w: int = z as int;
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    x: int = \"1\" as int;
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(2, 17));
+    assert!(err
+        .message()
+        .ends_with("cannot cast `string` to `int`, only number types can be casted"));
+}
+
+#[test]
+fn enum_variant_constructs_enum() {
+    let code = "
+    enum Shape {
+      Circle(r: f64),
+      Rect(w: f64, h: f64),
+      Empty,
+    }
+    area(s: Shape): f64 = 0.0;
+    circle: Shape = Shape::Circle(1.0);
+    empty: Shape = Shape::Empty;
+    x: f64 = area(Shape::Rect(1.0, 2.0));
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    enum Shape {
+      Circle(r: f64),
+    }
+    circle: Shape = Shape::Circle(1);
+    ";
+    assert!(check_code(code).is_err());
+    let code = "
+    enum Shape {
+      Empty,
+    }
+    x: int = Shape::Empty;
+    ";
+    assert!(check_code(code).is_err());
+}
+
+#[test]
+fn redefined_variant() {
+    let code = "
+    enum Shape {
+      Empty,
+      Empty(x: int),
+    }
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::redefined_variant(
+            &Location::from(4, 6),
+            "Empty".to_string(),
+            "Shape".to_string(),
+            Location::from(3, 6)
+        )
+        .message()
+    );
+}
+
+#[test]
+fn private_enum_is_hidden_from_other_modules() {
+    let lib = "module lib
+    import prelude (*)
+    enum Shape {
+      Empty,
+    }
+    pub enum Color {
+      Red,
+    }
+    ";
+    let main = "module main
+    import lib (Color)
+    red: Color = Color::Red;
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ]);
+    assert!(result.is_ok());
+    let main = "module main
+    import lib (Shape)
+    empty: Shape = Shape::Empty;
+    ";
+    let mut checker = SemanticChecker::new();
+    let result = checker.check_program(&vec![
+        parse_prelude(),
+        Parser::parse_program("", lib).unwrap(),
+        Parser::parse_program("", main).unwrap(),
+    ]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn match_arms_bind_pattern_and_have_the_same_type() {
+    let code = "
+    enum Shape {
+      Circle(r: f64),
+      Rect(w: f64, h: f64),
+      Empty,
+    }
+    area(s: Shape): f64 = match s {
+      Shape::Circle(r) => r,
+      Shape::Rect(w, _) => w,
+      Shape::Empty => 0.0,
+    };
+    name(n: u8): string = match n {
+      0 => \"zero\",
+      255 => \"max\",
+      other => other.to_string(),
+    };
+    answer(s: string): bool = match s { \"yes\" => true, _ => false };
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    foo(x: int): int = match x {
+      0 => 1,
+      _ => \"many\",
+    };
+    ";
+    assert!(check_code(code).is_err());
+    // names bound by pattern are only visible in the arm
+    let code = "
+    foo(x: int): int {
+      y: int = match x { n => n };
+      return n;
+    }
+    ";
+    assert!(check_code(code).is_err());
+}
+
+#[test]
+fn non_exhaustive_match() {
+    let code = "
+    enum Shape {
+      Circle(r: f64),
+      Rect(w: f64, h: f64),
+      Empty,
+    }
+    area(s: Shape): f64 = match s {
+      Shape::Circle(r) => r,
+    };
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::non_exhaustive_match(
+            &Location::from(7, 26),
+            vec!["Shape::Rect".to_string(), "Shape::Empty".to_string()]
+        )
+        .message()
+    );
+    let code = "
+    foo(x: int): int = match x { 0 => 1 };
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(2, 23));
+    assert!(err
+        .message()
+        .ends_with("non-exhaustive match, patterns not covered: `_`"));
+    let code = "
+    foo(x: bool): int = match x { true => 1, false => 0 };
+    ";
+    assert!(check_code(code).is_ok());
+}
+
+#[test]
+fn unreachable_match_arm() {
+    for code in &[
+        "foo(x: int): int = match x { _ => 1, 0 => 0 };",
+        "foo(x: int): int = match x { 0 => 1, 0 => 0, _ => 2 };",
+        "foo(x: bool): int = match x { true => 1, false => 0, _ => 2 };",
+    ] {
+        let err = check_code(code).unwrap_err();
+        assert!(
+            err.message()
+                .ends_with("unreachable match arm, its pattern is covered by previous arms"),
+            "{}",
+            code
+        );
+    }
+}
+
+#[test]
+fn wildcard_arm_of_enum_without_variants_is_reachable() {
+    let code = "
+    enum Never {}
+    foo(n: Never): int = match n { _ => 1 };
+    bar(n: Never): int = match n { m => 1 };
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    enum Never {}
+    foo(n: Never): int = match n { _ => 1, _ => 2 };
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(3, 43));
+    assert!(err
+        .message()
+        .ends_with("unreachable match arm, its pattern is covered by previous arms"));
+}
+
+#[test]
+fn pattern_must_bind_all_fields_of_variant() {
+    let code = "
+    enum Shape {
+      Rect(w: f64, h: f64),
+    }
+    width(s: Shape): f64 = match s {
+      Shape::Rect(w) => w,
+    };
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::pattern_fields_mismatched(
+            &Location::from(6, 6),
+            "Shape::Rect".to_string(),
+            2,
+            1
+        )
+        .message()
+    );
+}
+
+#[test]
+fn if_expression_has_the_type_of_branches() {
+    let code = "
+    pick(a: bool, b: bool): u8 = if a { 1 } else if b { 2 } else { 255 };
+    describe(n: int): string {
+      s: string = if n == 1 {
+        one: string = \"one\";
+        one + \"!\"
+      } else {
+        \"other\"
+      };
+      return s;
+    }
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    foo(a: bool): int = if a { 1 } else { \"s\" };
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(2, 42));
+    assert!(err
+        .message()
+        .ends_with("type mismatched, expected: `int` but got: `string`"));
+    let code = "
+    foo(a: int): int = if a { 1 } else { 2 };
+    ";
+    assert!(check_code(code).is_err());
+    // variables defined in a branch are only visible in it
+    let code = "
+    foo(a: bool): int {
+      x: int = if a { y: int = 1; y } else { 2 };
+      return y;
+    }
+    ";
+    assert!(check_code(code).is_err());
+}
+
+#[test]
+fn function_is_a_value() {
+    let code = "
+    add(a: int, b: int): int = a + b;
+    apply(f: (int, int): int, x: int): int = f(x, 1);
+    class Handler {
+      on: (int): void;
+    }
+    op: (int, int): int = add;
+    x: int = apply(add, 1) + op(1, 2);
+    call(h: Handler): void = h.on(1);
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    add(a: int, b: int): int = a + b;
+    f: (int): int = add;
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(3, 20));
+    assert!(err
+        .message()
+        .ends_with("type mismatched, expected: `(int): int` but got: `(int, int): int`"));
+}
+
+#[test]
+fn lambda_captures_names_of_enclosing_scope() {
+    let code = "
+    apply(f: (int): int, x: int): int = f(x);
+    adder(n: int): (int): int = (x: int): int => x + n;
+    class Counter {
+      step: int;
+      stepper(): (int): int = (x: int): int => x + step;
+    }
+    main(): void {
+      n: int = 1;
+      y: int = apply((x: int): int => x + n, 2);
+    }
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    f: (int): int = (x: int): int => \"x\";
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(2, 37));
+    assert!(err
+        .message()
+        .ends_with("type mismatched, expected: `int` but got: `string`"));
+    // parameters of lambda are not visible out of it
+    let code = "
+    f: (int): int = (x: int): int => x;
+    y: int = x;
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(3, 13));
+}
+
+#[test]
+fn arguments_are_matched_to_parameters() {
+    let code = "
+    offset(x: int, by: int = 1, scale: int = 1): int = x + by + scale;
+    class Box {
+      size: int;
+      ::new(size: int = 0): Box = Box {size: size};
+      grow(by: int = 1): int = size + by;
+    }
+    enum Shape {
+      Rect(w: int, h: int = 1),
+    }
+    a: int = offset(1) + offset(1, 2) + offset(scale: 3, x: 1) + offset(1, scale: 2);
+    b: int = Box::new().grow() + Box::new(size: 1).grow(by: 2);
+    c: Shape = Shape::Rect(h: 2, w: 1);
+    ";
+    assert!(check_code(code).is_ok());
+
+    let code = "
+    offset(x: int, by: int = 1): int = x + by;
+    a: int = offset(1, 2, 3);
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::argument_count_mismatched(&Location::from(3, 13), 2, 3).message()
+    );
+    let code = "
+    offset(x: int, by: int = 1): int = x + by;
+    a: int = offset(by: 2);
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::missing_arguments(&Location::from(3, 13), vec!["x".to_string()]).message()
+    );
+    let code = "
+    offset(x: int, by: int = 1): int = x + by;
+    a: int = offset(1, step: 2);
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::no_parameter_named(&Location::from(3, 29), "step").message()
+    );
+    let code = "
+    offset(x: int, by: int = 1): int = x + by;
+    a: int = offset(1, x: 2);
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::duplicated_argument(&Location::from(3, 26), "x").message()
+    );
+    let code = "
+    offset(x: int, by: int = 1): int = x + by;
+    a: int = offset(by: 2, 1);
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::positional_argument_after_named(&Location::from(3, 27)).message()
+    );
+    // parameters of function value have no name, so all arguments must be given by position
+    let code = "
+    apply(f: (int, int): int): int = f(1);
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::argument_count_mismatched(&Location::from(2, 37), 2, 1).message()
+    );
+}
+
+#[test]
+fn default_value_must_have_type_of_parameter() {
+    let code = "
+    offset(x: int, by: int = \"1\"): int = x + by;
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(2, 29));
+    assert!(err
+        .message()
+        .ends_with("type mismatched, expected: `int` but got: `string`"));
+    // default value is evaluated where the function is called, it cannot use other parameters
+    let code = "
+    offset(x: int, by: int = x): int = x + by;
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::no_variable(&Location::from(2, 29), "x").message()
+    );
+}
+
+#[test]
+fn required_parameter_cannot_follow_default_value() {
+    let code = "
+    f(a: int = 1, b: int): int = a + b;
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::required_parameter_after_default(&Location::from(2, 15), "b", "a").message()
+    );
+    let code = "
+    enum Shape {
+      Rect(w: int = 1, h: int)
+    }
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::required_parameter_after_default(&Location::from(3, 20), "h", "w").message()
+    );
+}
+
+#[test]
+fn tuple_elements_are_accessed_and_destructured() {
+    let code = "
+    div_mod(a: int, b: int): (int, u8) = (a, 1);
+    main(): void {
+      (q, _) := div_mod(7, 2);
+      t: (int, string) = (q, \"r\");
+      s: string = t.1;
+    }
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    t: (int, string) = (1, \"one\");
+    s: string = t.2;
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(3, 17));
+    assert!(err
+        .message()
+        .ends_with("type `(int, string)` has no element `2`"));
+    let code = "
+    pair(): (int, string) = (1, \"one\");
+    main(): void {
+      (a, b, c) := pair();
+    }
+    ";
+    let err = check_code(code).unwrap_err();
+    assert_eq!(err.location(), Location::from(4, 6));
+    assert!(err
+        .message()
+        .ends_with("cannot destructure `(int, string)` into 3 names"));
+}
+
+#[test]
+fn only_extern_variable_can_have_no_value() {
+    let code = "
+    @extern(c)
+    errno: i32;
+    main(): void {
+      e: i32 = errno;
+    }
+    ";
+    assert!(check_code(code).is_ok());
+    let code = "
+    errno: i32;
+    ";
+    assert_eq!(
+        check_code(code).unwrap_err().message(),
+        SemanticError::non_extern_variable_must_have_value(&Location::from(2, 4), "errno")
+            .message()
+    );
+}
+
 // helpers, must put tests before this line
 fn check_code(code: &'static str) -> Result<()> {
     let mut parser = Parser::new("", code);
@@ -365,15 +1434,8 @@ fn check_code(code: &'static str) -> Result<()> {
     code.push(TopAst::Import(Import {
         location: Location::none(),
         import_path: "prelude".to_string(),
-        imported_component: vec![
-            "int".to_string(),
-            "void".to_string(),
-            "f64".to_string(),
-            "bool".to_string(),
-            "string".to_string(),
-            "List".to_string(),
-            "println".to_string(),
-        ],
+        alias: None,
+        imported_component: vec![ImportedComponent::All(Location::none())],
     }));
 
     let prelude = parse_prelude();
@@ -382,6 +1444,7 @@ fn check_code(code: &'static str) -> Result<()> {
         .check_program(&vec![
             prelude,
             Module {
+                location: Location::none(),
                 name: "test".to_string(),
                 top_list: code,
             },
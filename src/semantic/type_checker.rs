@@ -1,47 +1,80 @@
 use super::error::Result;
 use super::error::SemanticError;
+use super::tag::{Primitive, SemanticTag};
 use crate::ast;
 use crate::ast::*;
 use crate::ast::{Function, ParsedType};
 use crate::lexer::Location;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// integer_literal returns the type of integer literal, which is `typ` if `value` is in its range
+fn integer_literal(location: &Location, value: u64, typ: &Type) -> Result<Type> {
+    match typ.primitive() {
+        Some(primitive) if !primitive.contains(value) => {
+            Err(SemanticError::integer_out_of_range(location, value, typ))
+        }
+        _ => Ok(typ.clone()),
+    }
+}
+
+/// ExprTypes maps the id of a checked expression to its type
+pub(crate) type ExprTypes = Rc<RefCell<HashMap<ExprId, Type>>>;
 
 pub struct TypeEnv {
     parent: Option<*const TypeEnv>,
     /// imports store information about how to lookup imported name
     pub(crate) imports: HashMap<String, String>,
+    /// module_name is the module being checked, private members of its classes can be used
+    pub(crate) module_name: String,
     variables: HashMap<String, TypeInfo>,
     types: HashMap<String, TypeInfo>,
+    /// private stores `module.name` of definitions without `pub`, they can be found in top
+    /// environment only by other modules, so finding one there is an error
+    private: HashSet<String>,
     free_var_count: usize,
+    /// expr_types records the type of each checked expression by its id, it's shared by all
+    /// environments made by `with_parent`, so the typed AST can be shown after checking
+    pub(crate) expr_types: ExprTypes,
     // flag
     pub in_class_scope: bool,
 }
 
 impl TypeEnv {
     pub(crate) fn type_of_expr(&mut self, expr: &Expr) -> Result<Type> {
+        let typ = self.infer_expr(expr)?;
+        self.record(expr, &typ);
+        Ok(typ)
+    }
+    fn record(&self, expr: &Expr, typ: &Type) {
+        self.expr_types.borrow_mut().insert(expr.id, typ.clone());
+    }
+
+    fn infer_expr(&mut self, expr: &Expr) -> Result<Type> {
         use ExprVariant::*;
         let location = &expr.location;
         match &expr.value {
             Binary(l, r, op) => {
-                let left_type = self.type_of_expr(l)?;
-                let right_type = self.type_of_expr(r)?;
-                match (left_type, right_type, op) {
-                    (
-                        Type::ClassType { name: n1, .. },
-                        Type::ClassType { name: n2, .. },
-                        Operator::Plus,
-                    ) => {
-                        if n1.as_str() == "int" && n1 == n2 {
-                            Ok(self.lookup_type(location, "int")?.typ)
-                        } else {
-                            panic!("sadasdasda")
-                        }
+                // integer literal takes the type of another side, e.g. `x == 1` for `x: u8`
+                let (left_type, right_type) = match &l.value {
+                    Int(_) => {
+                        let right_type = self.type_of_expr(r)?;
+                        (self.type_of_expr_against(l, &right_type)?, right_type)
                     }
-                    (l, r, op) => panic!("unsupported operator, {} {:?} {}", l, op, r),
-                }
+                    _ => {
+                        let left_type = self.type_of_expr(l)?;
+                        let right_type = self.type_of_expr_against(r, &left_type)?;
+                        (left_type, right_type)
+                    }
+                };
+                self.type_of_binary(location, &left_type, &right_type, op)
             }
             F64(_) => Ok(self.lookup_type(location, "f64")?.typ),
-            Int(_) => Ok(self.lookup_type(location, "int")?.typ),
+            Int(value) => {
+                let int_type = self.lookup_type(location, "int")?.typ;
+                integer_literal(location, *value, &int_type)
+            }
             Bool(_) => Ok(self.lookup_type(location, "bool")?.typ),
             String(_) => Ok(self.lookup_type(location, "string")?.typ),
             List(es) => {
@@ -65,10 +98,8 @@ impl TypeEnv {
                 let f_type = self.type_of_expr(f)?;
                 match f_type {
                     Type::FunctionType(params, ret_typ) => {
-                        for (p, arg) in params.iter().zip(args.iter()) {
-                            let typ = self.type_of_expr(&arg.expr)?;
-                            self.unify(&arg.location, p, &typ)?;
-                        }
+                        let signature = self.signature_of(f)?;
+                        self.check_arguments(location, &params, signature, args)?;
                         Ok(*ret_typ)
                     }
                     _ => Err(SemanticError::call_on_non_function_type(
@@ -78,19 +109,52 @@ impl TypeEnv {
                 }
             }
             MemberAccess(from, access) => {
+                // `io.println` refers to `println` of imported module `io`
+                if let Some(qualified_name) = self.qualified_name(expr) {
+                    return Ok(self.lookup_variable(location, &qualified_name)?.typ);
+                }
                 let typ = self.type_of_expr(from)?;
                 match typ {
                     Type::ClassType { name, members, .. } => {
-                        let member = members.get_member(location, name, access)?;
-                        Ok(member.typ.clone())
+                        let member =
+                            members.get_member(location, name, access, &self.module_name)?;
+                        Ok(member.typ)
                     }
-                    _ => unreachable!(),
+                    typ => Err(SemanticError::no_member_named(
+                        location,
+                        typ.to_string(),
+                        access.clone(),
+                    )),
                 }
             }
             Identifier(id) => {
                 let type_info = self.lookup_variable(location, id.as_str())?;
                 Ok(type_info.typ)
             }
+            Interpolation(e) => {
+                // any type with method `to_string(): string` can be shown in string template
+                let typ = self.type_of_expr(e)?;
+                let string_type = self.lookup_type(location, "string")?.typ;
+                let to_string_type = Type::FunctionType(vec![], string_type.clone().into());
+                match &typ {
+                    Type::ClassType { name, members, .. } => {
+                        match members.get_member(
+                            &e.location,
+                            name.clone(),
+                            &"to_string".to_string(),
+                            &self.module_name,
+                        ) {
+                            Ok(member)
+                                if self.unify(location, &to_string_type, &member.typ).is_ok() =>
+                            {
+                                Ok(string_type)
+                            }
+                            _ => Err(SemanticError::cannot_show_type(&e.location, &typ)),
+                        }
+                    }
+                    _ => Err(SemanticError::cannot_show_type(&e.location, &typ)),
+                }
+            }
             ClassConstruction(name, field_inits) => {
                 if !self.in_class_scope {
                     return Err(SemanticError::cannot_use_class_construction_out_of_class(
@@ -100,9 +164,21 @@ impl TypeEnv {
                 let type_info = self.lookup_type(location, name)?;
                 match &type_info.typ {
                     Type::ClassType {
+                        name,
+                        members,
                         uninitialized_fields,
                         ..
                     } => {
+                        for (field, e) in field_inits {
+                            let member = members.get_member(
+                                &e.location,
+                                name.clone(),
+                                field,
+                                &self.module_name,
+                            )?;
+                            let typ = self.type_of_expr_against(e, &member.typ)?;
+                            self.unify(&e.location, &member.typ, &typ)?;
+                        }
                         let should_inits = uninitialized_fields;
                         let mut missing_init_fields = vec![];
                         for should_init in should_inits {
@@ -126,6 +202,436 @@ impl TypeEnv {
                 }
                 Ok(type_info.typ)
             }
+            Cast(e, typ) => {
+                let from = self.type_of_expr(e)?;
+                let to = self.from(typ)?;
+                match (from.primitive(), to.primitive()) {
+                    (Some(_), Some(_)) => Ok(to),
+                    _ => Err(SemanticError::cannot_cast(location, &from, &to)),
+                }
+            }
+            Match(value, arms) => self.type_of_match(location, value, arms, None),
+            If {
+                clauses,
+                else_block,
+            } => self.type_of_if(clauses, else_block, None),
+            Lambda {
+                parameters,
+                ret_typ,
+                body,
+            } => self.type_of_lambda(location, parameters, ret_typ, body),
+            Tuple(elements) => {
+                let mut element_types = vec![];
+                for e in elements {
+                    element_types.push(self.type_of_expr(e)?);
+                }
+                Ok(Type::TupleType(element_types))
+            }
+            TupleAccess(tuple, index) => match self.type_of_expr(tuple)? {
+                Type::TupleType(element_types) if *index < element_types.len() => {
+                    Ok(element_types[*index].clone())
+                }
+                typ => Err(SemanticError::no_tuple_element(location, &typ, *index)),
+            },
+        }
+    }
+
+    /// signature_of returns the signature of function called by name, a value of function type
+    /// has no signature
+    fn signature_of(&mut self, f: &Expr) -> Result<Option<Signature>> {
+        match &f.value {
+            ExprVariant::Identifier(name) => Ok(self.lookup_variable(&f.location, name)?.signature),
+            ExprVariant::MemberAccess(from, access) => {
+                if let Some(qualified_name) = self.qualified_name(f) {
+                    return Ok(self
+                        .lookup_variable(&f.location, &qualified_name)?
+                        .signature);
+                }
+                match self.type_of_expr(from)? {
+                    Type::ClassType { name, members, .. } => Ok(members
+                        .get_member(&f.location, name, access, &self.module_name)?
+                        .signature),
+                    _ => Ok(None),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// check_arguments matches arguments to parameters and checks their types. Positional
+    /// arguments fill parameters in order, a named argument goes to the parameter with its name,
+    /// positional arguments cannot follow named ones, and a parameter with default value can be
+    /// left out. A value of function type has no
+    /// signature, so its arguments must be positional and cover all parameters.
+    fn check_arguments(
+        &mut self,
+        location: &Location,
+        params: &[Type],
+        signature: Option<Signature>,
+        args: &Vec<Argument>,
+    ) -> Result<()> {
+        let parameters = signature
+            .as_ref()
+            .map(|s| s.parameters.clone())
+            .unwrap_or_default();
+        let variadic = signature.as_ref().is_some_and(|s| s.variadic);
+        let mut given = vec![false; params.len()];
+        let mut next = 0;
+        let mut named = false;
+        for arg in args {
+            let i = match &arg.name {
+                Some(name) => {
+                    named = true;
+                    match parameters.iter().position(|p| &p.name == name) {
+                        Some(i) => i,
+                        None => return Err(SemanticError::no_parameter_named(&arg.location, name)),
+                    }
+                }
+                None if named => {
+                    return Err(SemanticError::positional_argument_after_named(
+                        &arg.location,
+                    ))
+                }
+                None => {
+                    next += 1;
+                    next - 1
+                }
+            };
+            if i >= params.len() {
+                if variadic {
+                    self.type_of_expr(&arg.expr)?;
+                    continue;
+                }
+                return Err(SemanticError::argument_count_mismatched(
+                    location,
+                    params.len(),
+                    args.len(),
+                ));
+            }
+            if given[i] {
+                return Err(SemanticError::duplicated_argument(
+                    &arg.location,
+                    &parameters[i].name,
+                ));
+            }
+            given[i] = true;
+            let typ = self.type_of_expr_against(&arg.expr, &params[i])?;
+            self.unify(&arg.location, &params[i], &typ)?;
+        }
+        if signature.is_none() {
+            if args.len() != params.len() {
+                return Err(SemanticError::argument_count_mismatched(
+                    location,
+                    params.len(),
+                    args.len(),
+                ));
+            }
+            return Ok(());
+        }
+        let missing: Vec<String> = parameters
+            .iter()
+            .zip(given.iter())
+            .filter(|(p, given)| !**given && p.default.is_none())
+            .map(|(p, _)| p.name.clone())
+            .collect();
+        if !missing.is_empty() {
+            return Err(SemanticError::missing_arguments(location, missing));
+        }
+        Ok(())
+    }
+
+    /// type_of_expr_against is `type_of_expr` with the type expected by context, integer literal
+    /// has the expected integer type, e.g. `x: u8 = 1;`, otherwise it's `int`
+    pub(crate) fn type_of_expr_against(&mut self, expr: &Expr, expected: &Type) -> Result<Type> {
+        let typ = self.infer_expr_against(expr, expected)?;
+        self.record(expr, &typ);
+        Ok(typ)
+    }
+    fn infer_expr_against(&mut self, expr: &Expr, expected: &Type) -> Result<Type> {
+        match (&expr.value, expected.primitive()) {
+            (ExprVariant::Int(value), Some(Primitive::Int { .. })) => {
+                integer_literal(&expr.location, *value, expected)
+            }
+            (ExprVariant::Binary(l, r, op @ Operator::Plus), Some(Primitive::Int { .. })) => {
+                let left_type = self.type_of_expr_against(l, expected)?;
+                let right_type = self.type_of_expr_against(r, expected)?;
+                self.type_of_binary(&expr.location, &left_type, &right_type, op)
+            }
+            (ExprVariant::Match(value, arms), _) => {
+                self.type_of_match(&expr.location, value, arms, Some(expected))
+            }
+            (
+                ExprVariant::If {
+                    clauses,
+                    else_block,
+                },
+                _,
+            ) => self.type_of_if(clauses, else_block, Some(expected)),
+            // `t: (u8, int) = (1, 2);`, each element takes the expected element type
+            (ExprVariant::Tuple(elements), _) => match expected {
+                Type::TupleType(element_types) if element_types.len() == elements.len() => {
+                    let mut types = vec![];
+                    for (e, typ) in elements.iter().zip(element_types) {
+                        types.push(self.type_of_expr_against(e, typ)?);
+                    }
+                    Ok(Type::TupleType(types))
+                }
+                _ => self.type_of_expr(expr),
+            },
+            _ => self.type_of_expr(expr),
+        }
+    }
+
+    /// check_local_variable checks the initializer of local variable, then defines it
+    pub(crate) fn check_local_variable(&mut self, location: &Location, v: &Variable) -> Result<()> {
+        let expr = match &v.expr {
+            Some(expr) => expr,
+            None => {
+                return Err(SemanticError::non_extern_variable_must_have_value(
+                    location, &v.name,
+                ))
+            }
+        };
+        let var_def_typ = self.from(&v.typ)?;
+        let var_typ = self.type_of_expr_against(expr, &var_def_typ)?;
+        self.unify(location, &var_def_typ, &var_typ)?;
+        // `_: int = foo();` only evaluates the expression and drops the result
+        if v.name != "_" {
+            self.add_variable(location, &v.name, var_def_typ)?;
+        }
+        Ok(())
+    }
+
+    /// check_destructure checks `(a, b) := expr;`, then defines names by elements of the tuple
+    pub(crate) fn check_destructure(
+        &mut self,
+        location: &Location,
+        names: &[String],
+        expr: &Expr,
+    ) -> Result<()> {
+        let typ = self.type_of_expr(expr)?;
+        match &typ {
+            Type::TupleType(element_types) if element_types.len() == names.len() => {
+                for (name, element_type) in names.iter().zip(element_types) {
+                    if name != "_" {
+                        self.add_variable(location, name, element_type.clone())?;
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(SemanticError::destructure_mismatched(
+                location,
+                &typ,
+                names.len(),
+            )),
+        }
+    }
+
+    /// type_of_if returns the type of all branches of if expression
+    fn type_of_if(
+        &mut self,
+        clauses: &Vec<(Expr, ValueBlock)>,
+        else_block: &ValueBlock,
+        expected: Option<&Type>,
+    ) -> Result<Type> {
+        let bool_type = self.lookup_type(&else_block.block.location, "bool")?.typ;
+        for (condition, _) in clauses {
+            let cond_type = self.type_of_expr(condition)?;
+            self.unify(&condition.location, &bool_type, &cond_type)?;
+        }
+        let mut branches_type = expected.cloned();
+        let blocks = clauses.iter().map(|(_, b)| b).chain(Some(else_block));
+        for b in blocks {
+            let typ = self.type_of_value_block(b, branches_type.as_ref())?;
+            match &branches_type {
+                Some(branches_type) => self.unify(&b.value.location, branches_type, &typ)?,
+                None => branches_type = Some(typ),
+            }
+        }
+        Ok(branches_type.unwrap())
+    }
+
+    /// type_of_value_block returns the type of the value of block, its statements are checked in
+    /// its own environment
+    fn type_of_value_block(&mut self, b: &ValueBlock, expected: Option<&Type>) -> Result<Type> {
+        let mut type_env = TypeEnv::with_parent(self);
+        for stmt in &b.block.statements {
+            match &stmt.value {
+                StatementVariant::Variable(v) => {
+                    type_env.check_local_variable(&stmt.location, v)?
+                }
+                StatementVariant::Expression(e) => {
+                    let typ = type_env.type_of_expr(e)?;
+                    let void_type = type_env.lookup_type(&stmt.location, "void")?.typ;
+                    type_env.unify(&stmt.location, &void_type, &typ)?;
+                }
+                _ => unreachable!("parser only produces variables and expressions in value block"),
+            }
+        }
+        match expected {
+            Some(typ) => type_env.type_of_expr_against(&b.value, typ),
+            None => type_env.type_of_expr(&b.value),
+        }
+    }
+
+    /// type_of_lambda checks the body of lambda in an environment whose parent is the enclosing
+    /// one, names of enclosing scope used by the body are found through it and would be captured
+    fn type_of_lambda(
+        &mut self,
+        location: &Location,
+        parameters: &Vec<Parameter>,
+        ret_typ: &ParsedType,
+        body: &Expr,
+    ) -> Result<Type> {
+        let ret_type = self.from(ret_typ)?;
+        let mut lambda_env = TypeEnv::with_parent(self);
+        let mut param_types = vec![];
+        for p in parameters {
+            let typ = lambda_env.from(&p.typ)?;
+            lambda_env.add_variable(location, &p.name, typ.clone())?;
+            param_types.push(typ);
+        }
+        let body_type = lambda_env.type_of_expr_against(body, &ret_type)?;
+        lambda_env.unify(&body.location, &ret_type, &body_type)?;
+        Ok(Type::FunctionType(param_types, ret_type.into()))
+    }
+
+    /// type_of_match returns the type of all arms, each arm is checked in its own environment
+    /// where names bound by the pattern are defined. Arms must cover all values of the matched
+    /// expression, and an arm covered by previous arms is unreachable.
+    fn type_of_match(
+        &mut self,
+        location: &Location,
+        value: &Expr,
+        arms: &Vec<MatchArm>,
+        expected: Option<&Type>,
+    ) -> Result<Type> {
+        let value_type = self.type_of_expr(value)?;
+        let mut coverage = MatchCoverage::new(&value_type);
+        let mut arms_type = expected.cloned();
+        for arm in arms {
+            // an enum without variants is covered before any arm, but `_` or binding of its
+            // value is not unreachable
+            let covers_any = matches!(arm.pattern, Pattern::Wildcard | Pattern::Binding(_));
+            if coverage.is_complete() && !(covers_any && coverage.is_untouched()) {
+                return Err(SemanticError::unreachable_arm(&arm.location));
+            }
+            let mut arm_env = TypeEnv::with_parent(self);
+            arm_env.bind_pattern(&arm.location, &arm.pattern, &value_type, &mut coverage)?;
+            match &arms_type {
+                Some(typ) => {
+                    let arm_type = arm_env.type_of_expr_against(&arm.expr, typ)?;
+                    self.unify(&arm.expr.location, typ, &arm_type)?;
+                }
+                None => arms_type = Some(arm_env.type_of_expr(&arm.expr)?),
+            }
+        }
+        let missing = coverage.missing();
+        if !missing.is_empty() {
+            return Err(SemanticError::non_exhaustive_match(location, missing));
+        }
+        match arms_type {
+            Some(typ) => Ok(typ),
+            None => Ok(self.lookup_type(location, "void")?.typ),
+        }
+    }
+
+    /// bind_pattern checks pattern against the type of matched value, and defines names bound by
+    /// the pattern
+    fn bind_pattern(
+        &mut self,
+        location: &Location,
+        pattern: &Pattern,
+        value_type: &Type,
+        coverage: &mut MatchCoverage,
+    ) -> Result<()> {
+        match pattern {
+            Pattern::Wildcard => {
+                coverage.cover_all();
+                Ok(())
+            }
+            Pattern::Binding(name) => {
+                self.add_variable(location, name, value_type.clone())?;
+                coverage.cover_all();
+                Ok(())
+            }
+            Pattern::Int(value) => {
+                let typ = match value_type.primitive() {
+                    Some(Primitive::Int { .. }) => integer_literal(location, *value, value_type)?,
+                    _ => self.lookup_type(location, "int")?.typ,
+                };
+                self.unify(location, value_type, &typ)?;
+                coverage.cover(location, value.to_string())
+            }
+            Pattern::Bool(value) => {
+                self.unify(
+                    location,
+                    value_type,
+                    &self.lookup_type(location, "bool")?.typ,
+                )?;
+                coverage.cover(location, value.to_string())
+            }
+            Pattern::String(value) => {
+                self.unify(
+                    location,
+                    value_type,
+                    &self.lookup_type(location, "string")?.typ,
+                )?;
+                coverage.cover(location, format!("{:?}", value))
+            }
+            Pattern::Variant(path, bindings) => {
+                let (enum_name, variant_name) = path.rsplit_once("::").unwrap();
+                let enum_type = self.lookup_type(location, enum_name)?.typ;
+                self.unify(location, value_type, &enum_type)?;
+                let (name, variant) = match &enum_type {
+                    Type::EnumType { name, variants } => (name, variants.get(variant_name)),
+                    _ => unreachable!("parser only produces variant pattern with `::`"),
+                };
+                let variant = variant.ok_or_else(|| {
+                    SemanticError::no_member_named(location, name.clone(), variant_name.to_string())
+                })?;
+                if bindings.len() != variant.fields.len() {
+                    return Err(SemanticError::pattern_fields_mismatched(
+                        location,
+                        path.clone(),
+                        variant.fields.len(),
+                        bindings.len(),
+                    ));
+                }
+                for (binding, (_, typ)) in bindings.iter().zip(variant.fields.iter()) {
+                    if binding != "_" {
+                        self.add_variable(location, binding, typ.clone())?;
+                    }
+                }
+                coverage.cover(location, format!("{}::{}", name, variant_name))
+            }
+        }
+    }
+
+    fn type_of_binary(
+        &self,
+        location: &Location,
+        left_type: &Type,
+        right_type: &Type,
+        op: &Operator,
+    ) -> Result<Type> {
+        let unsupported =
+            || SemanticError::unsupported_binary_operation(location, op, left_type, right_type);
+        let name = match (left_type, right_type) {
+            (Type::ClassType { name: n1, .. }, Type::ClassType { name: n2, .. }) if n1 == n2 => {
+                n1.as_str()
+            }
+            _ => return Err(unsupported()),
+        };
+        let is_number = left_type.primitive().is_some();
+        match (op, name) {
+            (Operator::Plus, _) if is_number => Ok(left_type.clone()),
+            (Operator::Plus, "string") => Ok(left_type.clone()),
+            (Operator::Equal, _) if is_number => Ok(self.lookup_type(location, "bool")?.typ),
+            (Operator::Equal, "bool") | (Operator::Equal, "string") => {
+                Ok(self.lookup_type(location, "bool")?.typ)
+            }
+            _ => Err(unsupported()),
         }
     }
 
@@ -157,9 +663,24 @@ impl TypeEnv {
                     Ok(())
                 }
             }
+            (EnumType { name, .. }, EnumType { name: name2, .. }) if name == name2 => Ok(()),
             (FunctionType(ft, arg), FunctionType(ft_p, arg_p)) => {
-                self.unify_type_list(location, ft, ft_p)?;
-                self.unify(location, arg, arg_p)
+                // function values can be passed around, so a mismatch shows whole types
+                if ft.len() != ft_p.len()
+                    || self.unify_type_list(location, ft, ft_p).is_err()
+                    || self.unify(location, arg, arg_p).is_err()
+                {
+                    return Err(SemanticError::type_mismatched(location, expected, actual));
+                }
+                Ok(())
+            }
+            (TupleType(elements), TupleType(elements2)) => {
+                if elements.len() != elements2.len()
+                    || self.unify_type_list(location, elements, elements2).is_err()
+                {
+                    return Err(SemanticError::type_mismatched(location, expected, actual));
+                }
+                Ok(())
             }
             (FreeVar(_), t) => self.unify(location, t, expected),
             (t, f @ FreeVar(_)) => {
@@ -201,9 +722,12 @@ impl TypeEnv {
         TypeEnv {
             parent: None,
             imports: HashMap::new(),
+            module_name: String::new(),
             variables: HashMap::new(),
             types: HashMap::new(),
+            private: HashSet::new(),
             free_var_count: 1,
+            expr_types: Rc::new(RefCell::new(HashMap::new())),
             in_class_scope: false,
         }
     }
@@ -213,9 +737,29 @@ impl TypeEnv {
         // inherit the attribute from parent
         // if parent is in class scope, this of course is in class scope
         type_env.in_class_scope = parent.in_class_scope;
+        type_env.module_name = parent.module_name.clone();
+        type_env.expr_types = parent.expr_types.clone();
         type_env
     }
     pub fn from(&self, typ: &ParsedType) -> Result<Type> {
+        if let ParsedType::FunctionType {
+            parameters,
+            ret_typ,
+        } = typ
+        {
+            let mut param_types = vec![];
+            for param in parameters {
+                param_types.push(self.from(param)?);
+            }
+            return Ok(Type::FunctionType(param_types, self.from(ret_typ)?.into()));
+        }
+        if let ParsedType::TupleType(elements) = typ {
+            let mut element_types = vec![];
+            for element in elements {
+                element_types.push(self.from(element)?);
+            }
+            return Ok(Type::TupleType(element_types));
+        }
         Ok(self
             .lookup_type(&Location::none(), typ.name().as_str())?
             .typ)
@@ -230,9 +774,40 @@ impl TypeEnv {
             self.from(&f.ret_typ)?.into(),
         ))
     }
+    /// new_class creates the type of class without members, members would be filled by
+    /// `add_class_members` after all types are known, so member can refer to any class
     pub fn new_class(&mut self, c: &Class) -> Result<Type> {
         let mut uninitialized_fields = vec![];
-        let mut members = ClassMembers::new();
+        for member in &c.members {
+            match member {
+                ast::ClassMember::Field(field) if field.expr.is_none() => {
+                    uninitialized_fields.push(field.name.clone())
+                }
+                _ => (),
+            }
+        }
+        let mut parents = vec![];
+        for p_name in &c.parents {
+            let parent_typ = self.lookup_type(&c.location, p_name.as_str())?;
+            match &parent_typ.typ {
+                Type::TraitType => parents.push(parent_typ.typ),
+                t => return Err(SemanticError::only_trait_can_be_super_type(&c.location, t)),
+            }
+        }
+        Ok(Type::ClassType {
+            name: c.name.clone(),
+            parents,
+            type_parameters: vec![],
+            uninitialized_fields,
+            members: ClassMembers::new(),
+            primitive: c.tag.primitive(),
+        })
+    }
+    pub fn add_class_members(&mut self, c: &Class) -> Result<()> {
+        let members = match self.lookup_type(&c.location, &c.name)?.typ {
+            Type::ClassType { members, .. } => members,
+            _ => unreachable!("class `{}` must be a class type", c.name),
+        };
         for member in &c.members {
             match member {
                 ast::ClassMember::Field(field) => {
@@ -243,15 +818,15 @@ impl TypeEnv {
                             name: field.name.clone(),
                             location: field.location.clone(),
                             typ: field_type.clone(),
+                            signature: None,
+                            public: field.public,
+                            module_name: self.module_name.clone(),
                         },
                     )?;
-                    match &field.expr {
-                        None => uninitialized_fields.push(field.name.clone()),
-                        Some(expr) => {
-                            // check expression type same as field type
-                            let expr_type = self.type_of_expr(expr)?;
-                            self.unify(&field.location, &field_type, &expr_type)?;
-                        }
+                    if let Some(expr) = &field.expr {
+                        // check expression type same as field type
+                        let expr_type = self.type_of_expr(expr)?;
+                        self.unify(&field.location, &field_type, &expr_type)?;
                     }
                 }
                 ast::ClassMember::Method(method) => {
@@ -261,27 +836,58 @@ impl TypeEnv {
                             name: method.name.clone(),
                             location: method.location.clone(),
                             typ: self.new_function_type(method)?,
+                            signature: Some(Signature::of(method)),
+                            public: method.public,
+                            module_name: self.module_name.clone(),
                         },
                     )?;
                 }
                 _ => (),
             }
         }
-        let mut parents = vec![];
-        for p_name in &c.parents {
-            let parent_typ = self.lookup_type(&c.location, p_name.as_str())?;
-            match &parent_typ.typ {
-                Type::TraitType => parents.push(parent_typ.typ),
-                t => return Err(SemanticError::only_trait_can_be_super_type(&c.location, t)),
+        Ok(())
+    }
+    /// new_enum creates the type of enum without variants, variants would be filled by
+    /// `add_enum_variants` after all types are known, so a variant can refer to the enum itself
+    pub fn new_enum(&self, e: &Enum) -> Type {
+        Type::EnumType {
+            name: e.name.clone(),
+            variants: EnumVariants::new(),
+        }
+    }
+    pub fn add_enum_variants(&mut self, e: &Enum) -> Result<()> {
+        let variants = match self.lookup_type(&e.location, &e.name)?.typ {
+            Type::EnumType { variants, .. } => variants,
+            _ => unreachable!("enum `{}` must be an enum type", e.name),
+        };
+        for variant in &e.variants {
+            let mut fields = vec![];
+            for field in &variant.fields {
+                fields.push((field.name.clone(), self.from(&field.typ)?));
             }
+            variants.add_variant(
+                &e.name,
+                EnumVariant {
+                    name: variant.name.clone(),
+                    location: variant.location.clone(),
+                    fields,
+                },
+            )?;
         }
-        Ok(Type::ClassType {
-            name: c.name.clone(),
-            parents,
-            type_parameters: vec![],
-            uninitialized_fields,
-            members,
-        })
+        Ok(())
+    }
+    /// variant_constructor_type returns the type of `Shape::Circle`, a function creates the enum
+    /// from fields, or the enum itself for a variant without fields, e.g. `Shape::Empty`
+    pub fn variant_constructor_type(&self, e: &Enum, variant: &ast::EnumVariant) -> Result<Type> {
+        let enum_type = self.lookup_type(&e.location, &e.name)?.typ;
+        if variant.fields.is_empty() {
+            return Ok(enum_type);
+        }
+        let mut field_types = vec![];
+        for field in &variant.fields {
+            field_types.push(self.from(&field.typ)?);
+        }
+        Ok(Type::FunctionType(field_types, enum_type.into()))
     }
 }
 
@@ -295,19 +901,83 @@ impl TypeEnv {
             Ok(())
         }
     }
+    /// add_function defines function `key`, calls to it are checked against its signature
+    pub(crate) fn add_function(
+        &mut self,
+        location: &Location,
+        key: &str,
+        typ: Type,
+        signature: Signature,
+    ) -> Result<()> {
+        self.add_variable(location, key, typ)?;
+        self.variables.get_mut(key).unwrap().signature = Some(signature);
+        Ok(())
+    }
+    /// qualified_name returns `io.println` when expression `io.println` refers to a name of
+    /// imported module, a variable named `io` hides the module
+    pub(crate) fn qualified_name(&self, e: &Expr) -> Option<String> {
+        let path = e.access_path()?;
+        let head = path.split('.').next().unwrap();
+        if self.lookup_variable(&e.location, head).is_ok() {
+            return None;
+        }
+        if self.is_imported(&path) {
+            Some(path)
+        } else {
+            None
+        }
+    }
+    fn is_imported(&self, k: &str) -> bool {
+        if self.imports.contains_key(k) {
+            return true;
+        }
+        match self.parent {
+            Some(env) => unsafe { env.as_ref() }.unwrap().is_imported(k),
+            None => false,
+        }
+    }
+    /// imported_name returns the name of `k` in its module, static method `Foo::new` is imported
+    /// with class `Foo`
+    fn imported_name(&self, k: &str) -> String {
+        if let Some(v) = self.imports.get(k) {
+            return v.clone();
+        }
+        let mut parts = k.splitn(2, "::");
+        let class_name = parts.next().unwrap();
+        match (self.imports.get(class_name), parts.next()) {
+            (Some(v), Some(static_method)) => format!("{}::{}", v, static_method),
+            _ => k.to_string(),
+        }
+    }
+    /// mark_private makes definition `k` in top environment can't be used by other modules
+    pub(crate) fn mark_private(&mut self, k: String) {
+        self.private.insert(k);
+    }
+    fn check_private(&self, location: &Location, k: &str, info: &TypeInfo) -> Result<TypeInfo> {
+        if self.private.contains(k) {
+            let mut parts = k.rsplitn(2, '.');
+            let name = parts.next().unwrap();
+            let module_name = parts.next().unwrap_or("");
+            Err(SemanticError::private_name(
+                location,
+                module_name,
+                name,
+                info.location.clone(),
+            ))
+        } else {
+            Ok(info.clone())
+        }
+    }
     pub(crate) fn lookup_variable(&self, location: &Location, k: &str) -> Result<TypeInfo> {
         let result = self.variables.get(k);
         match result {
-            Some(t) => Ok(t.clone()),
+            Some(t) => self.check_private(location, k, t),
             None => match self.parent {
                 Some(env) => {
-                    let k = match self.imports.get(k) {
-                        None => k,
-                        Some(v) => v,
-                    };
+                    let k = self.imported_name(k);
                     unsafe { env.as_ref() }
                         .unwrap()
-                        .lookup_variable(location, k)
+                        .lookup_variable(location, &k)
                 }
                 None => Err(SemanticError::no_variable(location, k)),
             },
@@ -326,14 +996,11 @@ impl TypeEnv {
     pub(crate) fn lookup_type(&self, location: &Location, k: &str) -> Result<TypeInfo> {
         let result = self.types.get(k);
         match result {
-            Some(t) => Ok(t.clone()),
+            Some(t) => self.check_private(location, k, t),
             None => match self.parent {
                 Some(env) => {
-                    let k = match self.imports.get(k) {
-                        None => k,
-                        Some(v) => v,
-                    };
-                    unsafe { env.as_ref() }.unwrap().lookup_type(location, k)
+                    let k = self.imported_name(k);
+                    unsafe { env.as_ref() }.unwrap().lookup_type(location, &k)
                 }
                 None => Err(SemanticError::no_type(location, k)),
             },
@@ -345,6 +1012,8 @@ impl TypeEnv {
 pub struct TypeInfo {
     pub location: Location,
     pub typ: Type,
+    /// parameters of function definition, a variable of function type has none
+    pub signature: Option<Signature>,
 }
 
 impl TypeInfo {
@@ -352,6 +1021,31 @@ impl TypeInfo {
         TypeInfo {
             location: location.clone(),
             typ,
+            signature: None,
+        }
+    }
+}
+
+/// Signature is the parameters of a function definition, a call to the function can name its
+/// arguments and omit arguments of parameters with default value
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    parameters: Vec<Parameter>,
+    // variadic function takes more arguments than its parameters
+    variadic: bool,
+}
+
+impl Signature {
+    pub(crate) fn of(f: &Function) -> Signature {
+        Signature {
+            parameters: f.parameters.clone(),
+            variadic: f.tag.is_variadic(),
+        }
+    }
+    pub(crate) fn of_variant(variant: &ast::EnumVariant) -> Signature {
+        Signature {
+            parameters: variant.fields.clone(),
+            variadic: false,
         }
     }
 }
@@ -361,19 +1055,26 @@ pub struct ClassMember {
     name: String,
     location: Location,
     typ: Type,
+    // methods have signature, fields don't
+    signature: Option<Signature>,
+    public: bool,
+    // module defines the class
+    module_name: String,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct ClassMembers(HashMap<String, ClassMember>);
+/// ClassMembers is shared by all copies of the same class type, so the members of a class can
+/// refer to the class itself, e.g. `concat(other: string): string` in class `string`
+#[derive(Clone)]
+pub struct ClassMembers(Rc<RefCell<HashMap<String, ClassMember>>>);
 
 impl ClassMembers {
     fn new() -> ClassMembers {
-        ClassMembers(HashMap::new())
+        ClassMembers(Rc::new(RefCell::new(HashMap::new())))
     }
-    fn add_member(&mut self, class_name: String, member: ClassMember) -> Result<()> {
+    fn add_member(&self, class_name: String, member: ClassMember) -> Result<()> {
         let location = &member.location.clone();
         let member_name = member.name.clone();
-        match self.0.insert(member.name.clone(), member) {
+        match self.0.borrow_mut().insert(member.name.clone(), member) {
             Some(previous_field) => Err(SemanticError::redefined_member(
                 location,
                 member_name,
@@ -383,14 +1084,25 @@ impl ClassMembers {
             None => Ok(()),
         }
     }
+    /// get_member returns member `name`, private member can only be used in the module defines
+    /// the class
     fn get_member(
         &self,
         location: &Location,
         class_name: String,
         name: &String,
-    ) -> Result<&ClassMember> {
-        match self.0.get(name) {
-            Some(v) => Ok(v),
+        module_name: &str,
+    ) -> Result<ClassMember> {
+        match self.0.borrow().get(name) {
+            Some(v) if !v.public && v.module_name != module_name => {
+                Err(SemanticError::private_member(
+                    location,
+                    class_name,
+                    name.clone(),
+                    v.location.clone(),
+                ))
+            }
+            Some(v) => Ok(v.clone()),
             None => Err(SemanticError::no_member_named(
                 location,
                 class_name,
@@ -400,6 +1112,138 @@ impl ClassMembers {
     }
 }
 
+impl PartialEq for ClassMembers {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for ClassMembers {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // members can refer to the class itself, so only show names here
+        f.debug_set().entries(self.0.borrow().keys()).finish()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumVariant {
+    pub(crate) name: String,
+    location: Location,
+    pub(crate) fields: Vec<(String, Type)>,
+}
+
+/// EnumVariants is shared by all copies of the same enum type like `ClassMembers`, variants are
+/// kept in the order of definition
+#[derive(Clone)]
+pub struct EnumVariants(Rc<RefCell<Vec<EnumVariant>>>);
+
+impl EnumVariants {
+    fn new() -> EnumVariants {
+        EnumVariants(Rc::new(RefCell::new(vec![])))
+    }
+    fn add_variant(&self, enum_name: &str, variant: EnumVariant) -> Result<()> {
+        if let Some(previous) = self.get(&variant.name) {
+            return Err(SemanticError::redefined_variant(
+                &variant.location,
+                variant.name,
+                enum_name.to_string(),
+                previous.location,
+            ));
+        }
+        self.0.borrow_mut().push(variant);
+        Ok(())
+    }
+    pub(crate) fn get(&self, name: &str) -> Option<EnumVariant> {
+        self.0.borrow().iter().find(|v| v.name == name).cloned()
+    }
+    pub(crate) fn all(&self) -> Vec<EnumVariant> {
+        self.0.borrow().clone()
+    }
+}
+
+impl PartialEq for EnumVariants {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for EnumVariants {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // fields can refer to the enum itself, so only show names here
+        let names: Vec<String> = self.0.borrow().iter().map(|v| v.name.clone()).collect();
+        f.debug_set().entries(names).finish()
+    }
+}
+
+/// MatchCoverage records values covered by arms of a match
+struct MatchCoverage {
+    // all values of a type which has finite values, variants of enum or `true` and `false` of
+    // `bool`, other types can only be covered by `_` or binding
+    finite: Option<Vec<String>>,
+    covered: Vec<String>,
+    any: bool,
+}
+
+impl MatchCoverage {
+    fn new(typ: &Type) -> MatchCoverage {
+        let finite = match typ {
+            Type::EnumType { name, variants } => Some(
+                variants
+                    .all()
+                    .iter()
+                    .map(|v| format!("{}::{}", name, v.name))
+                    .collect(),
+            ),
+            Type::ClassType { name, .. } if name == "bool" => {
+                Some(vec!["true".to_string(), "false".to_string()])
+            }
+            _ => None,
+        };
+        MatchCoverage {
+            finite,
+            covered: vec![],
+            any: false,
+        }
+    }
+    fn is_complete(&self) -> bool {
+        self.any
+            || match &self.finite {
+                Some(all) => all.iter().all(|v| self.covered.contains(v)),
+                None => false,
+            }
+    }
+    /// is_untouched is true when no arm covers anything yet
+    fn is_untouched(&self) -> bool {
+        !self.any && self.covered.is_empty()
+    }
+    /// cover records a value covered by an arm, the arm is unreachable if previous arms cover it
+    fn cover(&mut self, location: &Location, value: String) -> Result<()> {
+        if self.covered.contains(&value) {
+            return Err(SemanticError::unreachable_arm(location));
+        }
+        self.covered.push(value);
+        Ok(())
+    }
+    fn cover_all(&mut self) {
+        self.any = true;
+    }
+    fn missing(&self) -> Vec<String> {
+        if self.is_complete() {
+            return vec![];
+        }
+        match &self.finite {
+            Some(all) => all
+                .iter()
+                .filter(|v| !self.covered.contains(v))
+                .cloned()
+                .collect(),
+            None => vec!["_".to_string()],
+        }
+    }
+}
+
+// variants are named like the ones of `ParsedType`, e.g. `ClassType`
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum Type {
     // TODO: complete definition
@@ -410,12 +1254,25 @@ pub enum Type {
         type_parameters: Vec<Type>,
         uninitialized_fields: Vec<String>,
         members: ClassMembers,
+        /// primitive is the number type of builtin class, e.g. `@builtin(u8) class u8 {}`
+        primitive: Option<Primitive>,
+    },
+    EnumType {
+        name: String,
+        variants: EnumVariants,
     },
     FunctionType(Vec<Type>, Box<Type>),
+    TupleType(Vec<Type>),
     FreeVar(usize),
 }
 
 impl Type {
+    pub(crate) fn primitive(&self) -> Option<Primitive> {
+        match self {
+            Type::ClassType { primitive, .. } => *primitive,
+            _ => None,
+        }
+    }
     fn occurs(&self, t: Type) -> bool {
         use Type::*;
         match t {
@@ -427,6 +1284,7 @@ impl Type {
                 }
                 self.occurs(*t2)
             }
+            TupleType(elements) => elements.into_iter().any(|t| self.occurs(t)),
             ClassType {
                 name,
                 type_parameters,
@@ -443,6 +1301,7 @@ impl Type {
                 }
             },
             TraitType => unimplemented!("trait type"),
+            EnumType { .. } => false,
             FreeVar(_) => self.clone() == t,
         }
     }
@@ -472,8 +1331,17 @@ impl std::fmt::Display for Type {
                 write!(f, "")
             }
             TraitType => unimplemented!("trait type"),
-            // FIXME: print format: `(int, int): int` not `<function>`
-            FunctionType(_params, _ret) => write!(f, "<function>"),
+            EnumType { name, .. } => write!(f, "{}", name),
+            // `(int, int): int`
+            FunctionType(params, ret) => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "({}): {}", params.join(", "), ret)
+            }
+            // `(int, string)`
+            TupleType(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", elements.join(", "))
+            }
             FreeVar(n) => write!(f, "'{}", n),
         }
     }
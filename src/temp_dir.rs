@@ -0,0 +1,36 @@
+use std::path::{Path, PathBuf};
+
+/// TempDir is a directory under the temporary directory of system for tests, it's removed on drop
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// new gives an empty directory `elz-<name>-<pid>`, the directory is not created yet
+    pub(crate) fn new(name: &str) -> TempDir {
+        let dir = std::env::temp_dir().join(format!("elz-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        TempDir(dir)
+    }
+    /// with_files creates `files` of `(relative path, content)` under a new directory
+    pub(crate) fn with_files(name: &str, files: Vec<(&str, &str)>) -> TempDir {
+        let dir = TempDir::new(name);
+        for (file_name, content) in files {
+            let path = dir.join(file_name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+}
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}